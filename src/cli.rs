@@ -0,0 +1,399 @@
+// Headless command-line interface. With a subcommand the program does its work and exits
+// without initialising GTK; without one, main() opens the window as before.
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::escape::split_list;
+use crate::domain::validation::Severity;
+use crate::services::desktop_file;
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::desktop_writer::DesktopWriter;
+use crate::services::discovery::Source;
+use crate::services::icon_theme::{self, IconResolver};
+use crate::services::keyfile::KeyFile;
+use crate::services::mime::MimeDb;
+use crate::services::provisioning::Provisioning;
+
+// Exit codes
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1; // the command ran and failed, or validate found errors
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "\
+Usage: launcher_studio [COMMAND]
+
+Without a command the editor window opens.
+
+Commands:
+  list [--all] [--source user|system|flatpak|snap]
+                          List launchers (ID, source, name, path); --all includes shadowed files
+  show <id|file> [--path] Print a launcher, or only where it is
+  validate <file>...      Report errors and warnings; exits 1 when there are errors
+  create --name NAME [--exec CMD] [--icon ICON] [--comment TEXT] [--generic-name TEXT]
+         [--categories A;B] [--mime-type A;B] [--keywords A;B] [--type Application|Link]
+         [--url URL] [--try-exec PROG] [--path DIR] [--terminal] [--no-display]
+         [--startup-notify] [--file-name NAME] [--dir DIR] [--force]
+                          Write a new launcher (to the user applications dir by default)
+  set <id|file> [--group GROUP] Key=Value...
+                          Set keys; values are written as they would appear in the file
+  unset <id|file> [--group GROUP] Key...
+                          Remove keys
+  delete <id|file>        Delete a launcher file and the icon installed for it
+  apply <dir> [--dry-run] Make the user launchers match a directory of manifests
+                          (.desktop, .json, .toml): create, update, and delete the ones
+                          an earlier apply of <dir> created; --dry-run only prints the plan
+  icon <name|path> [--theme NAME]
+                          Show the icon file used at each size (Icon Theme Specification
+                          lookup in the current theme, or NAME); exits 1 when none is found
+  help                    Show this help
+";
+
+// Command-line mistakes, reported with a pointer to the help and exit code 2
+#[derive(Debug)]
+struct Usage(String);
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Usage {}
+
+fn usage(message: impl Into<String>) -> anyhow::Error {
+    anyhow::Error::new(Usage(message.into()))
+}
+
+// Some(exit code) when `args` (without the program name) hold a subcommand
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let result = match command.as_str() {
+        "list" => list(rest),
+        "show" => show(rest),
+        "validate" => validate(rest),
+        "create" => create(rest),
+        "set" => set(rest),
+        "unset" => unset(rest),
+        "delete" => delete(rest),
+        "apply" => apply(rest),
+        "icon" => icon(rest),
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            Ok(EXIT_OK)
+        }
+        other => Err(usage(format!("unknown command \"{}\"", other))),
+    };
+    Some(match result {
+        Ok(code) => code,
+        Err(e) if e.is::<Usage>() => {
+            eprintln!("launcher_studio: {}\nRun \"launcher_studio help\" for usage.", e);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("launcher_studio: {:#}", e);
+            EXIT_FAILED
+        }
+    })
+}
+
+// Options and positional arguments of one subcommand
+struct Parsed {
+    positional: Vec<String>,
+    values: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Parsed {
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values.iter().rev().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+}
+
+// `--opt value`, `--opt=value` for `value_opts`, bare `--flag` for `flags`;
+// everything after `--` is positional
+fn parse_args(args: &[String], value_opts: &[&str], flags: &[&str]) -> Result<Parsed> {
+    let mut parsed = Parsed { positional: Vec::new(), values: Vec::new(), flags: Vec::new() };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            parsed.positional.extend(iter.by_ref().cloned());
+            break;
+        }
+        let Some(opt) = arg.strip_prefix("--") else {
+            parsed.positional.push(arg.clone());
+            continue;
+        };
+        let (name, inline) = match opt.split_once('=') {
+            Some((n, v)) => (n, Some(v.to_string())),
+            None => (opt, None),
+        };
+        if value_opts.contains(&name) {
+            let value = match inline {
+                Some(v) => v,
+                None => iter.next().cloned().ok_or_else(|| usage(format!("--{} needs a value", name)))?,
+            };
+            parsed.values.push((name.to_string(), value));
+        } else if flags.contains(&name) && inline.is_none() {
+            parsed.flags.push(name.to_string());
+        } else {
+            return Err(usage(format!("unknown option \"{}\"", arg)));
+        }
+    }
+    Ok(parsed)
+}
+
+// A path to an existing file, or a desktop-file ID ("firefox" or "firefox.desktop")
+// resolved the way menus do: the first match in precedence order
+fn resolve(target: &str) -> Result<PathBuf> {
+    let path = Path::new(target);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    let id = if target.ends_with(".desktop") { target.to_string() } else { format!("{}.desktop", target) };
+    DesktopReader::list_desktop_files()
+        .into_iter()
+        .find(|d| d.id == id && d.shadowed_by.is_none())
+        .map(|d| d.path)
+        .ok_or_else(|| anyhow!("No launcher file or desktop-file ID \"{}\"", target))
+}
+
+fn one_target(parsed: &Parsed, command: &str) -> Result<PathBuf> {
+    match parsed.positional.first() {
+        Some(target) => resolve(target),
+        None => Err(usage(format!("{} needs a launcher ID or file", command))),
+    }
+}
+
+fn list(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &["source"], &["all"])?;
+    if let Some(extra) = parsed.positional.first() {
+        return Err(usage(format!("unexpected argument \"{}\"", extra)));
+    }
+    let source = match parsed.value("source") {
+        None => None,
+        Some(s) => Some(
+            [Source::User, Source::System, Source::Flatpak, Source::Snap]
+                .into_iter()
+                .find(|src| src.label().eq_ignore_ascii_case(s))
+                .ok_or_else(|| usage(format!("unknown source \"{}\"", s)))?,
+        ),
+    };
+    for d in DesktopReader::list_desktop_files() {
+        if source.is_some_and(|s| s != d.source) || (d.shadowed_by.is_some() && !parsed.flag("all")) {
+            continue;
+        }
+        let name = DesktopReader::read_from_path(&d.path).map(|e| e.name).unwrap_or_default();
+        let note = if d.shadowed_by.is_some() { " (shadowed)" } else { "" };
+        println!("{}\t{}\t{}\t{}{}", d.id, d.source.label(), name, d.path.display(), note);
+    }
+    Ok(EXIT_OK)
+}
+
+fn show(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &[], &["path"])?;
+    let path = one_target(&parsed, "show")?;
+    if parsed.flag("path") {
+        println!("{}", path.display());
+    } else {
+        let content = fs::read_to_string(&path).with_context(|| format!("Reading {}", path.display()))?;
+        print!("{}", content);
+        if !content.ends_with('\n') {
+            println!();
+        }
+    }
+    Ok(EXIT_OK)
+}
+
+// Same checks as the Source tab: parse problems, then the spec rules and the icon lookup
+fn validate(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &[], &[])?;
+    if parsed.positional.is_empty() {
+        return Err(usage("validate needs at least one file"));
+    }
+    let mut errors = 0;
+    for target in &parsed.positional {
+        let path = resolve(target)?;
+        let content = fs::read_to_string(&path).with_context(|| format!("Reading {}", path.display()))?;
+        let (entry, mut diagnostics) = desktop_file::parse_with_diagnostics(&content);
+        if !diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            diagnostics.extend(desktop_file::validation_diagnostics(&content, &entry, Some(MimeDb::load_installed())));
+            diagnostics.extend(desktop_file::finding_diagnostics(&content, icon_theme::check_icons(&entry)));
+        }
+        diagnostics.sort_by_key(|d| (d.line, d.column));
+        for d in &diagnostics {
+            println!("{}:{}", path.display(), d);
+        }
+        errors += diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    }
+    Ok(if errors > 0 { EXIT_FAILED } else { EXIT_OK })
+}
+
+fn create(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(
+        args,
+        &["name", "exec", "icon", "comment", "generic-name", "categories", "mime-type", "keywords", "type", "url", "try-exec", "path", "file-name", "dir"],
+        &["terminal", "no-display", "startup-notify", "force"],
+    )?;
+    if let Some(extra) = parsed.positional.first() {
+        return Err(usage(format!("unexpected argument \"{}\"", extra)));
+    }
+    let name = parsed.value("name").ok_or_else(|| usage("create needs --name"))?;
+    let text = |key: &str| parsed.value(key).map(str::to_string);
+    let list = |key: &str| parsed.value(key).map(split_list).unwrap_or_default();
+    let entry = DesktopEntry {
+        type_field: text("type").unwrap_or_else(|| "Application".into()),
+        name: name.to_string(),
+        generic_name: text("generic-name"),
+        comment: text("comment"),
+        exec: text("exec").unwrap_or_default(),
+        icon: text("icon"),
+        terminal: parsed.flag("terminal"),
+        categories: list("categories"),
+        mime_type: list("mime-type"),
+        keywords: list("keywords"),
+        no_display: parsed.flag("no-display"),
+        startup_notify: parsed.flag("startup-notify"),
+        try_exec: text("try-exec"),
+        path: text("path"),
+        url: text("url"),
+        ..Default::default()
+    };
+    let file_name = parsed.value("file-name").unwrap_or(name);
+    let file_name = file_name.strip_suffix(".desktop").unwrap_or(file_name);
+    let written = match parsed.value("dir") {
+        Some(dir) => DesktopWriter::write_in(&entry, Path::new(dir), file_name, parsed.flag("force"))?,
+        None => DesktopWriter::write(&entry, file_name, parsed.flag("force"))?,
+    };
+    println!("{}", written.display());
+    report_conformance(&written, &entry);
+    Ok(EXIT_OK)
+}
+
+fn set(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &["group"], &[])?;
+    let path = one_target(&parsed, "set")?;
+    let pairs = &parsed.positional[1..];
+    if pairs.is_empty() {
+        return Err(usage("set needs at least one Key=Value"));
+    }
+    let group = edited_group(&parsed)?;
+    edit(&path, |doc| {
+        for pair in pairs {
+            let (key, value) = pair.split_once('=').ok_or_else(|| usage(format!("expected Key=Value, got \"{}\"", pair)))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(usage(format!("empty key in \"{}\"", pair)));
+            }
+            doc.set(group, key, value.trim());
+        }
+        Ok(())
+    })
+}
+
+fn unset(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &["group"], &[])?;
+    let path = one_target(&parsed, "unset")?;
+    let keys = &parsed.positional[1..];
+    if keys.is_empty() {
+        return Err(usage("unset needs at least one key"));
+    }
+    let group = edited_group(&parsed)?;
+    edit(&path, |doc| {
+        for key in keys {
+            doc.remove(group, key.trim());
+        }
+        Ok(())
+    })
+}
+
+// Only the groups a DesktopEntry models survive the save
+fn edited_group(parsed: &Parsed) -> Result<&str> {
+    let group = parsed.value("group").unwrap_or("Desktop Entry");
+    if group != "Desktop Entry" && !group.starts_with("Desktop Action ") {
+        bail!("Only [Desktop Entry] and [Desktop Action ...] groups can be edited, not [{}]", group);
+    }
+    Ok(group)
+}
+
+// Apply raw key changes, then save through DesktopWriter so the result is validated and
+// the rest of the file keeps its layout
+fn edit(path: &Path, change: impl FnOnce(&mut KeyFile) -> Result<()>) -> Result<i32> {
+    let content = fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+    let mut doc = KeyFile::parse(&content);
+    for line in desktop_file::drop_unreadable_lines(&mut doc) {
+        eprintln!("{}:{}: dropping a line that cannot be read", path.display(), line);
+    }
+    change(&mut doc)?;
+    let entry = desktop_file::parse(&doc.to_text()).context("The changed file does not parse")?;
+    let written = DesktopWriter::write_to_path(&entry, path)?;
+    println!("{}", written.display());
+    report_conformance(&written, &entry);
+    Ok(EXIT_OK)
+}
+
+// Spec errors do not stop a save, but are worth knowing about
+fn report_conformance(path: &Path, entry: &DesktopEntry) {
+    for finding in entry.conformance_errors() {
+        eprintln!("{}: {}", path.display(), finding);
+    }
+}
+
+fn delete(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &[], &[])?;
+    let path = one_target(&parsed, "delete")?;
+    if let Some(extra) = parsed.positional.get(1) {
+        return Err(usage(format!("unexpected argument \"{}\"", extra)));
+    }
+    let removed_icons = DesktopWriter::delete(&path)?;
+    println!("{}", path.display());
+    for icon in removed_icons {
+        println!("{}", icon.display());
+    }
+    Ok(EXIT_OK)
+}
+
+fn apply(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &[], &["dry-run"])?;
+    let [dir] = parsed.positional.as_slice() else {
+        return Err(usage("apply needs exactly one manifest directory"));
+    };
+    let plan = Provisioning::plan(Path::new(dir))?;
+    println!("{}", plan);
+    if !plan.problems.is_empty() {
+        return Ok(EXIT_FAILED);
+    }
+    if !parsed.flag("dry-run") {
+        let applied = Provisioning::apply(&plan)?;
+        println!("Applied {} change(s)", applied);
+    }
+    Ok(EXIT_OK)
+}
+
+fn icon(args: &[String]) -> Result<i32> {
+    let parsed = parse_args(args, &["theme"], &[])?;
+    let [name] = parsed.positional.as_slice() else {
+        return Err(usage("icon needs exactly one icon name or path"));
+    };
+    let theme = parsed.value("theme").map(str::to_string).unwrap_or_else(icon_theme::current_theme_name);
+    let mut resolver = IconResolver::new(&theme);
+    println!("Theme: {}", theme);
+    let report = resolver.report(name);
+    for (size, path) in &report {
+        match path {
+            Some(path) => println!("{}\t{}", size, path.display()),
+            None => println!("{}\t-", size),
+        }
+    }
+    if report.iter().all(|(_, p)| p.is_none()) {
+        eprintln!("{}", resolver.missing_message(name));
+        return Ok(EXIT_FAILED);
+    }
+    Ok(EXIT_OK)
+}
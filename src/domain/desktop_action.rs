@@ -0,0 +1,16 @@
+// One [Desktop Action <id>] group, referenced by name from the Actions= key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesktopAction {
+    pub id: String,                            // group suffix: [Desktop Action <id>]
+    pub name: String,                          // Name
+    pub name_localized: Vec<(String, String)>, // Name[lang]
+    pub icon: Option<String>,                  // Icon
+    pub exec: Option<String>,                  // Exec
+    pub extra: Vec<(String, String)>,          // Any other key (X-*, Icon[lang], ...), raw
+}
+
+impl DesktopAction {
+    pub fn group_name(&self) -> String {
+        format!("Desktop Action {}", self.id)
+    }
+}
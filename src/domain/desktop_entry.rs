@@ -1,6 +1,12 @@
 use std::fmt::Write as _;
 
-#[derive(Debug, Clone, Default)]
+use crate::domain::desktop_action::DesktopAction;
+use crate::domain::escape::{escape_string as escape, join_list};
+use crate::domain::exec;
+use crate::domain::mime::MimeDatabase;
+use crate::domain::validation::{validate_entry, Finding, Severity};
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct DesktopEntry {
     pub type_field: String,           // Type
     pub name: String,                 // Name
@@ -21,6 +27,7 @@ pub struct DesktopEntry {
     pub url: Option<String>,          // For Type=Link
     pub actions: Vec<String>,         // Actions (names)
     pub extra: Vec<(String, String)>, // Any extra key=value
+    pub desktop_actions: Vec<DesktopAction>, // [Desktop Action <name>] groups
 
     // Localized variants
     pub name_localized: Vec<(String, String)>,        // (lang, value) => Name[fr]=...
@@ -29,100 +36,161 @@ pub struct DesktopEntry {
 }
 
 impl DesktopEntry {
-    pub fn validate(&self) -> Result<(), String> {
-        if self.type_field.is_empty() {
-            return Err("Type is required".into());
+    // Every problem desktop-file-validate would report, errors and warnings alike; MimeType=
+    // is checked against `mime_db` when one is given
+    pub fn validate(&self, mime_db: Option<&MimeDatabase>) -> Vec<Finding> {
+        validate_entry(self, mime_db)
+    }
+
+    // Ok unless the entry cannot work as a launcher: no Type or Name, or an Exec that does
+    // not parse. Other errors validate() finds (a desktop missing from the registered
+    // OnlyShowIn list, a key without X-) are shown to the user but never block saving,
+    // as real-world files often have them.
+    pub fn check(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if self.type_field.trim().is_empty() {
+            errors.push("Type: required key is missing".to_string());
         }
         if self.name.trim().is_empty() {
-            return Err("Name is required".into());
-        }
-        match self.type_field.as_str() {
-            "Application" => {
-                if self.exec.trim().is_empty() {
-                    return Err("Exec is required for Type=Application".into());
-                }
-            }
-            "Link" => {
-                if self.url.as_deref().unwrap_or("").trim().is_empty() {
-                    return Err("URL is required for Type=Link".into());
-                }
+            errors.push("Name: required key is missing".to_string());
+        }
+        let execs = std::iter::once((None, Some(self.exec.as_str())))
+            .chain(self.desktop_actions.iter().map(|a| (Some(a.group_name()), a.exec.as_deref())));
+        for (group, value) in execs {
+            let Some(value) = value.filter(|v| !v.trim().is_empty()) else { continue };
+            for issue in exec::parse(value).1.into_iter().filter(|i| i.severity == Severity::Error) {
+                errors.push(match &group {
+                    Some(group) => format!("[{}] Exec: {}", group, issue.message),
+                    None => format!("Exec: {}", issue.message),
+                });
             }
-            "Directory" => {}
-            _ => return Err("Type must be one of Application, Link, Directory".into()),
         }
-        Ok(())
+        if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) }
+    }
+
+    // Errors validate() finds that check() lets through, to show after saving anyway
+    pub fn conformance_errors(&self) -> Vec<Finding> {
+        self.validate(None).into_iter().filter(|f| f.severity == Severity::Error).collect()
+    }
+
+    // Raw value of a key kept in `extra` (e.g. Version, DBusActivatable)
+    pub fn extra_value(&self, key: &str) -> Option<&str> {
+        self.extra.iter().rev().find(|(k, _)| k.trim() == key).map(|(_, v)| v.trim())
+    }
+
+    // Set (in place when present) or remove a key kept in `extra`
+    pub fn set_extra(&mut self, key: &str, value: Option<&str>) {
+        match value {
+            Some(v) => match self.extra.iter_mut().find(|(k, _)| k.trim() == key) {
+                Some(pair) => pair.1 = v.to_string(),
+                None => self.extra.push((key.to_string(), v.to_string())),
+            },
+            None => self.extra.retain(|(k, _)| k.trim() != key),
+        }
     }
 
     pub fn to_ini_string(&self) -> String {
         let mut s = String::new();
-        let _ = writeln!(&mut s, "[Desktop Entry]");
-        let _ = writeln!(&mut s, "Type={}", self.type_field);
-        let _ = writeln!(&mut s, "Name={}", escape(&self.name));
+        for (i, (group, keys)) in self.to_groups().iter().enumerate() {
+            if i > 0 {
+                let _ = writeln!(&mut s);
+            }
+            let _ = writeln!(&mut s, "[{}]", group);
+            for (k, v) in keys {
+                let _ = writeln!(&mut s, "{}={}", k, v);
+            }
+        }
+        s
+    }
+
+    // Every group with its key/value pairs, in the order to_ini_string writes them:
+    // [Desktop Entry] first, then one [Desktop Action] group per action.
+    pub fn to_groups(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut keys: Vec<(String, String)> = Vec::new();
+        let mut push = |k: &str, v: String| keys.push((k.to_string(), v));
+        push("Type", escape(&self.type_field));
+        push("Name", escape(&self.name));
         for (lang, val) in &self.name_localized {
-            let _ = writeln!(&mut s, "Name[{}]={}", lang, escape(val));
+            push(&format!("Name[{}]", lang), escape(val));
         }
         if let Some(v) = &self.generic_name {
-            let _ = writeln!(&mut s, "GenericName={}", escape(v));
+            push("GenericName", escape(v));
         }
         for (lang, val) in &self.generic_name_localized {
-            let _ = writeln!(&mut s, "GenericName[{}]={}", lang, escape(val));
+            push(&format!("GenericName[{}]", lang), escape(val));
         }
         if let Some(v) = &self.comment {
-            let _ = writeln!(&mut s, "Comment={}", escape(v));
+            push("Comment", escape(v));
         }
         for (lang, val) in &self.comment_localized {
-            let _ = writeln!(&mut s, "Comment[{}]={}", lang, escape(val));
+            push(&format!("Comment[{}]", lang), escape(val));
         }
         if !self.exec.is_empty() {
-            let _ = writeln!(&mut s, "Exec={}", self.exec.trim());
+            push("Exec", escape(self.exec.trim()));
         }
         if let Some(v) = &self.try_exec {
-            let _ = writeln!(&mut s, "TryExec={}", v.trim());
+            push("TryExec", escape(v.trim()));
         }
         if let Some(v) = &self.icon {
-            let _ = writeln!(&mut s, "Icon={}", v.trim());
+            push("Icon", escape(v.trim()));
         }
         if let Some(v) = &self.path {
-            let _ = writeln!(&mut s, "Path={}", v.trim());
+            push("Path", escape(v.trim()));
         }
         if let Some(v) = &self.url {
-            let _ = writeln!(&mut s, "URL={}", v.trim());
-        }
-        let _ = writeln!(&mut s, "Terminal={}", if self.terminal { "true" } else { "false" });
-        let _ = writeln!(&mut s, "NoDisplay={}", if self.no_display { "true" } else { "false" });
-        let _ = writeln!(
-            &mut s,
-            "StartupNotify={}",
-            if self.startup_notify { "true" } else { "false" }
-        );
+            push("URL", escape(v.trim()));
+        }
+        push("Terminal", bool_str(self.terminal));
+        push("NoDisplay", bool_str(self.no_display));
+        push("StartupNotify", bool_str(self.startup_notify));
         if !self.categories.is_empty() {
-            let _ = writeln!(&mut s, "Categories={};", self.categories.join(";"));
+            push("Categories", join_list(&self.categories));
         }
         if !self.mime_type.is_empty() {
-            let _ = writeln!(&mut s, "MimeType={};", self.mime_type.join(";"));
+            push("MimeType", join_list(&self.mime_type));
         }
         if !self.keywords.is_empty() {
-            let _ = writeln!(&mut s, "Keywords={};", self.keywords.join(";"));
+            push("Keywords", join_list(&self.keywords));
         }
         if !self.only_show_in.is_empty() {
-            let _ = writeln!(&mut s, "OnlyShowIn={};", self.only_show_in.join(";"));
+            push("OnlyShowIn", join_list(&self.only_show_in));
         }
         if !self.not_show_in.is_empty() {
-            let _ = writeln!(&mut s, "NotShowIn={};", self.not_show_in.join(";"));
+            push("NotShowIn", join_list(&self.not_show_in));
         }
         if !self.actions.is_empty() {
-            let _ = writeln!(&mut s, "Actions={};", self.actions.join(";"));
+            push("Actions", join_list(&self.actions));
         }
+        // Extra values are kept as written (already escaped): their type is unknown
         for (k, v) in &self.extra {
             if !k.trim().is_empty() {
-                let _ = writeln!(&mut s, "{}={}", k.trim(), v.trim());
+                push(k.trim(), v.trim().to_string());
             }
         }
-        s
+
+        let mut groups = vec![("Desktop Entry".to_string(), keys)];
+        for action in &self.desktop_actions {
+            let mut keys = vec![("Name".to_string(), escape(&action.name))];
+            for (lang, val) in &action.name_localized {
+                keys.push((format!("Name[{}]", lang), escape(val)));
+            }
+            if let Some(v) = &action.icon {
+                keys.push(("Icon".into(), escape(v.trim())));
+            }
+            if let Some(v) = &action.exec {
+                keys.push(("Exec".into(), escape(v.trim())));
+            }
+            for (k, v) in &action.extra {
+                if !k.trim().is_empty() {
+                    keys.push((k.trim().to_string(), v.trim().to_string()));
+                }
+            }
+            groups.push((action.group_name(), keys));
+        }
+        groups
     }
 }
 
-fn escape(input: &str) -> String {
-    // Per .desktop, use plain strings; escape newlines as \n
-    input.replace('\n', "\\n")
+fn bool_str(b: bool) -> String {
+    if b { "true".into() } else { "false".into() }
 }
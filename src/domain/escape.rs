@@ -0,0 +1,147 @@
+// Value escaping from the Desktop Entry Specification ("Possible value types"):
+// string and localestring values may contain \s, \n, \t, \r and \\, and items of
+// string lists are separated by ';' with a literal semicolon written as \;.
+
+pub fn escape_string(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let last = input.chars().count().saturating_sub(1);
+    for (i, c) in input.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            // Readers trim whitespace around '=' and at line end, so edge spaces must be escaped
+            ' ' if i == 0 || i == last => out.push_str("\\s"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn unescape_string(input: &str) -> String {
+    unescape(input, false)
+}
+
+// Serialize a string list: every item escaped, ';' inside items as \;, trailing ';'
+pub fn join_list(items: &[String]) -> String {
+    let mut out = String::new();
+    for item in items {
+        out.push_str(&escape_string(item).replace(';', "\\;"));
+        out.push(';');
+    }
+    out
+}
+
+// Split a string list on unescaped ';' and unescape each item; empty items are dropped
+pub fn split_list(input: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push('\\');
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ';' => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+    items
+        .iter()
+        .map(|raw| unescape(raw.trim(), true))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unescape(input: &str, in_list: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some(';') if in_list => out.push(';'),
+            // Unknown sequences are kept verbatim rather than dropping data
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Every awkward character the escaping rules deal with, plus ordinary text
+    const ALPHABET: &[char] = &['a', 'Z', ' ', ';', '\\', '\n', '\t', '\r', 's', ';', '=', '#', 'é', '\\'];
+
+    // Deterministic pseudo-random strings (xorshift), so failures are reproducible
+    pub(crate) fn samples(count: usize, max_len: usize) -> Vec<String> {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        (0..count).map(|_| (0..next() % (max_len + 1)).map(|_| ALPHABET[next() % ALPHABET.len()]).collect()).collect()
+    }
+
+    #[test]
+    fn strings_round_trip() {
+        for s in samples(2000, 12) {
+            let escaped = escape_string(&s);
+            assert!(!escaped.contains(['\n', '\r', '\t']), "{:?} -> {:?}", s, escaped);
+            assert_eq!(escaped.trim(), escaped, "edge whitespace must be escaped: {:?}", s);
+            assert_eq!(unescape_string(&escaped), s, "{:?}", escaped);
+        }
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let items = samples(3000, 8);
+        for chunk in items.chunks(5) {
+            // Empty items cannot be written: "a;;b" is the list a, b
+            let list: Vec<String> = chunk.iter().filter(|s| !s.is_empty()).cloned().collect();
+            let joined = join_list(&list);
+            assert_eq!(split_list(&joined), list, "{:?}", joined);
+        }
+    }
+
+    #[test]
+    fn escape_sequences() {
+        assert_eq!(escape_string(" a b "), r"\sa b\s");
+        assert_eq!(escape_string("a\nb\tc\rd\\e"), r"a\nb\tc\rd\\e");
+        assert_eq!(unescape_string(r"\sa\nb\tc\rd\\e\s"), " a\nb\tc\rd\\e ");
+        // \; only means ';' inside lists; unknown sequences are kept
+        assert_eq!(unescape_string(r"a\;b\x"), r"a\;b\x");
+    }
+
+    #[test]
+    fn list_separators() {
+        assert_eq!(split_list(r"a\;b;c;"), vec!["a;b", "c"]);
+        assert_eq!(split_list(r"a\\;b"), vec!["a\\", "b"]);
+        assert_eq!(split_list(";a;;  ;b"), vec!["a", "b"]);
+        assert_eq!(split_list(r"\sa;b\s;"), vec![" a", "b "]);
+        assert_eq!(split_list(r"\n;\t;\r"), vec!["\n", "\t", "\r"]);
+        assert!(split_list("").is_empty());
+        assert_eq!(join_list(&["a;b".into(), " c".into(), "d\\".into()]), r"a\;b;\sc;d\\;");
+        assert_eq!(join_list(&[]), "");
+    }
+}
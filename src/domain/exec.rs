@@ -0,0 +1,462 @@
+// Exec command lines ("The Exec key" in the Desktop Entry Specification): the program and
+// its arguments, quoting rules and field codes. Works on the value after string unescaping.
+use crate::domain::validation::Severity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldCode {
+    File,        // %f
+    Files,       // %F
+    Url,         // %u
+    Urls,        // %U
+    Icon,        // %i
+    Name,        // %c
+    Location,    // %k
+    Deprecated(char), // %d %D %n %N %v %m, expand to nothing
+}
+
+impl FieldCode {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'f' => Some(FieldCode::File),
+            'F' => Some(FieldCode::Files),
+            'u' => Some(FieldCode::Url),
+            'U' => Some(FieldCode::Urls),
+            'i' => Some(FieldCode::Icon),
+            'c' => Some(FieldCode::Name),
+            'k' => Some(FieldCode::Location),
+            'd' | 'D' | 'n' | 'N' | 'v' | 'm' => Some(FieldCode::Deprecated(c)),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> char {
+        match self {
+            FieldCode::File => 'f',
+            FieldCode::Files => 'F',
+            FieldCode::Url => 'u',
+            FieldCode::Urls => 'U',
+            FieldCode::Icon => 'i',
+            FieldCode::Name => 'c',
+            FieldCode::Location => 'k',
+            FieldCode::Deprecated(c) => *c,
+        }
+    }
+
+    // Codes that receive the files or URLs the launcher is opened with
+    pub fn takes_targets(&self) -> bool {
+        matches!(self, FieldCode::File | FieldCode::Files | FieldCode::Url | FieldCode::Urls)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Text(String),
+    Code(FieldCode),
+}
+
+// One argument: literal text and field codes, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Arg {
+    pub parts: Vec<Part>,
+    pub quoted: bool,
+}
+
+impl Arg {
+    // The argument for display: quotes and escapes removed, field codes shown as %x
+    pub fn text(&self) -> String {
+        let mut s = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(t) => s.push_str(t),
+                Part::Code(c) => {
+                    s.push('%');
+                    s.push(c.code());
+                }
+            }
+        }
+        s
+    }
+
+    pub fn codes(&self) -> impl Iterator<Item = FieldCode> + '_ {
+        self.parts.iter().filter_map(|p| match p {
+            Part::Code(c) => Some(*c),
+            Part::Text(_) => None,
+        })
+    }
+
+    fn push_char(&mut self, c: char) {
+        if let Some(Part::Text(t)) = self.parts.last_mut() {
+            t.push(c);
+        } else {
+            self.parts.push(Part::Text(c.to_string()));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandLine {
+    pub args: Vec<Arg>, // args[0] is the program
+}
+
+impl CommandLine {
+    pub fn field_codes(&self) -> impl Iterator<Item = FieldCode> + '_ {
+        self.args.iter().flat_map(Arg::codes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+// Characters that must be inside a quoted argument to be taken literally
+const RESERVED: &[char] = &['\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`'];
+
+// Tokenize a command line; the result is best effort when issues are reported
+pub fn parse(exec: &str) -> (CommandLine, Vec<ExecIssue>) {
+    let mut issues = Vec::new();
+    let mut args = Vec::new();
+    let mut current: Option<Arg> = None;
+    let mut in_quotes = false;
+    let mut chars = exec.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !in_quotes && (c == ' ' || c == '\t' || c == '\n') {
+            if let Some(arg) = current.take() {
+                args.push(arg);
+            }
+            continue;
+        }
+        let arg = current.get_or_insert_with(Arg::default);
+        match c {
+            '"' if in_quotes => {
+                in_quotes = false;
+                if chars.peek().is_some_and(|n| *n != ' ' && *n != '\t') {
+                    issues.push(error("a closing quote must end the argument"));
+                }
+            }
+            '"' => {
+                if !arg.parts.is_empty() {
+                    issues.push(error("quotes must enclose the whole argument"));
+                }
+                in_quotes = true;
+                arg.quoted = true;
+            }
+            '\\' if in_quotes => match chars.next() {
+                Some(e @ ('"' | '`' | '$' | '\\')) => arg.push_char(e),
+                Some(other) => {
+                    issues.push(error(&format!("\"\\{}\" is not a valid escape inside quotes, only \\\", \\`, \\$ and \\\\ are", other)));
+                    arg.push_char('\\');
+                    arg.push_char(other);
+                }
+                None => arg.push_char('\\'),
+            },
+            '`' | '$' if in_quotes => {
+                issues.push(error(&format!("'{}' must be escaped with a backslash inside quotes", c)));
+                arg.push_char(c);
+            }
+            '%' => match chars.next() {
+                Some('%') => arg.push_char('%'),
+                Some(code) => match FieldCode::from_char(code) {
+                    Some(field) => {
+                        if in_quotes {
+                            issues.push(error(&format!("field code %{} must not be used inside quotes", code)));
+                        }
+                        if let FieldCode::Deprecated(_) = field {
+                            issues.push(warning(&format!("field code %{} is deprecated and expands to nothing", code)));
+                        }
+                        arg.parts.push(Part::Code(field));
+                    }
+                    None => {
+                        issues.push(error(&format!("%{} is not a valid field code, write %% for a literal '%'", code)));
+                        arg.push_char('%');
+                        arg.push_char(code);
+                    }
+                },
+                None => {
+                    issues.push(error("trailing '%', write %% for a literal '%'"));
+                    arg.push_char('%');
+                }
+            },
+            _ if !in_quotes && RESERVED.contains(&c) => {
+                issues.push(error(&format!("reserved character '{}' must be inside a quoted argument", c)));
+                arg.push_char(c);
+            }
+            _ => arg.push_char(c),
+        }
+    }
+    if let Some(arg) = current.take() {
+        args.push(arg);
+    }
+    if in_quotes {
+        issues.push(error("unterminated double quote"));
+    }
+
+    let line = CommandLine { args };
+    check_codes(&line, &mut issues);
+    (line, issues)
+}
+
+fn check_codes(line: &CommandLine, issues: &mut Vec<ExecIssue>) {
+    match line.args.first() {
+        None => issues.push(error("no program to run")),
+        Some(program) if program.codes().next().is_some() => issues.push(error("the program itself must not be a field code")),
+        Some(_) => {}
+    }
+    let targets: Vec<FieldCode> = line.field_codes().filter(FieldCode::takes_targets).collect();
+    if targets.len() > 1 {
+        let used: Vec<String> = targets.iter().map(|c| format!("%{}", c.code())).collect();
+        issues.push(error(&format!("only one of %f, %F, %u and %U may be used, found {}", used.join(" "))));
+    }
+    for arg in &line.args {
+        for code in arg.codes() {
+            let alone = arg.parts.len() == 1 && !arg.quoted;
+            if matches!(code, FieldCode::Files | FieldCode::Urls | FieldCode::Icon) && !alone {
+                issues.push(error(&format!("%{} must be an argument on its own", code.code())));
+            }
+        }
+    }
+}
+
+fn error(message: &str) -> ExecIssue {
+    ExecIssue { severity: Severity::Error, message: message.to_string() }
+}
+
+fn warning(message: &str) -> ExecIssue {
+    ExecIssue { severity: Severity::Warning, message: message.to_string() }
+}
+
+// Quote one argument when Exec would otherwise split or reinterpret it. `%` is left
+// alone so field codes inside the argument keep working: escape a literal % as %% first.
+pub fn quote(arg: &str) -> String {
+    let needs_quotes = arg.is_empty() || arg.chars().any(|c| c.is_whitespace() || c == '"' || RESERVED.contains(&c));
+    if !needs_quotes {
+        return arg.to_string();
+    }
+    let mut s = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('"');
+    s
+}
+
+// Replace the program (first argument) of a command line, keeping the other arguments
+// and their field codes exactly as written
+pub fn replace_program(exec: &str, program: &str) -> String {
+    let program = quote(&program.replace('%', "%%"));
+    let trimmed = exec.trim_start();
+    let first_len = raw_args(trimmed).first().map_or(0, |a| a.len());
+    format!("{}{}", program, &trimmed[first_len..])
+}
+
+// Split a command line into arguments as written (quotes and escapes kept), for
+// rearranging a command without re-encoding its arguments
+pub fn raw_args(exec: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in exec.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if in_quotes && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && c.is_whitespace() {
+            if let Some(s) = start.take() {
+                args.push(&exec[s..i]);
+            }
+            continue;
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        args.push(&exec[s..]);
+    }
+    args
+}
+
+// What field codes expand to when the entry is launched
+#[derive(Debug, Clone, Default)]
+pub struct Expansion<'a> {
+    pub targets: &'a [String], // files or URLs the launcher is opened with
+    pub icon: Option<&'a str>,
+    pub name: &'a str,
+    pub location: Option<&'a str>, // path of the .desktop file
+}
+
+// Expand field codes into the final argv. %f and %u take the first target only (a real
+// launcher would start one process per target); arguments that expand to nothing are dropped.
+pub fn expand(line: &CommandLine, ctx: &Expansion<'_>) -> Vec<String> {
+    let mut argv = Vec::new();
+    for arg in &line.args {
+        match arg.parts.as_slice() {
+            [Part::Code(FieldCode::Files)] => argv.extend(ctx.targets.iter().map(|t| file_path(t))),
+            [Part::Code(FieldCode::Urls)] => argv.extend(ctx.targets.iter().cloned()),
+            [Part::Code(FieldCode::Icon)] => {
+                if let Some(icon) = ctx.icon.filter(|i| !i.is_empty()) {
+                    argv.push("--icon".into());
+                    argv.push(icon.to_string());
+                }
+            }
+            parts => {
+                let mut s = String::new();
+                for part in parts {
+                    match part {
+                        Part::Text(t) => s.push_str(t),
+                        Part::Code(code) => s.push_str(&expand_code(*code, ctx)),
+                    }
+                }
+                if !s.is_empty() || arg.quoted {
+                    argv.push(s);
+                }
+            }
+        }
+    }
+    argv
+}
+
+fn expand_code(code: FieldCode, ctx: &Expansion<'_>) -> String {
+    match code {
+        FieldCode::File => ctx.targets.first().map(|t| file_path(t)).unwrap_or_default(),
+        FieldCode::Url => ctx.targets.first().cloned().unwrap_or_default(),
+        FieldCode::Files | FieldCode::Urls => ctx.targets.join(" "),
+        FieldCode::Icon => ctx.icon.unwrap_or_default().to_string(),
+        FieldCode::Name => ctx.name.to_string(),
+        FieldCode::Location => ctx.location.unwrap_or_default().to_string(),
+        FieldCode::Deprecated(_) => String::new(),
+    }
+}
+
+// %f/%F want local paths; file:// URLs are turned back into one
+fn file_path(target: &str) -> String {
+    match target.strip_prefix("file://") {
+        Some(rest) => percent_decode(rest),
+        None => target.to_string(),
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = s.get(i + 1..i + 3)
+            && let Ok(b) = u8::from_str_radix(hex, 16)
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(exec: &str) -> Vec<String> {
+        parse(exec).1.into_iter().filter(|i| i.severity == Severity::Error).map(|i| i.message).collect()
+    }
+
+    fn warnings(exec: &str) -> Vec<String> {
+        parse(exec).1.into_iter().filter(|i| i.severity == Severity::Warning).map(|i| i.message).collect()
+    }
+
+    fn argv(exec: &str, targets: &[&str]) -> Vec<String> {
+        let targets: Vec<String> = targets.iter().map(|t| t.to_string()).collect();
+        let ctx = Expansion { targets: &targets, icon: Some("app-icon"), name: "App", location: Some("/apps/app.desktop") };
+        expand(&parse(exec).0, &ctx)
+    }
+
+    #[test]
+    fn reserved_characters_must_be_quoted() {
+        for c in RESERVED {
+            let exec = format!("app a{}b", c);
+            assert_eq!(errors(&exec).len(), 1, "{}", exec);
+        }
+        assert_eq!(errors(r#"app "a>b" "x;y|z" "*?#()~&<'""#), Vec::<String>::new());
+        assert_eq!(argv(r#"app "a>b" "it's""#, &[]), ["app", "a>b", "it's"]);
+    }
+
+    #[test]
+    fn escapes_inside_quotes() {
+        assert_eq!(argv(r#"app "a\"b" "c\`d" "e\$f" "g\\h""#, &[]), ["app", "a\"b", "c`d", "e$f", "g\\h"]);
+        assert_eq!(errors(r#"app "a\nb""#), [r#""\n" is not a valid escape inside quotes, only \", \`, \$ and \\ are"#]);
+        assert_eq!(errors(r#"app "$HOME""#), ["'$' must be escaped with a backslash inside quotes"]);
+        assert_eq!(errors(r#"app "`id`""#).len(), 2);
+        assert_eq!(errors(r#"app "open"#), ["unterminated double quote"]);
+        assert_eq!(errors(r#"app a"b""#), ["quotes must enclose the whole argument"]);
+    }
+
+    #[test]
+    fn double_percent_is_a_literal_percent() {
+        assert!(parse("printf %%d%%").0.field_codes().next().is_none());
+        assert_eq!(errors("printf %%d%%"), Vec::<String>::new());
+        assert_eq!(argv("printf %%d%%", &["x"]), ["printf", "%d%"]);
+        assert_eq!(errors("printf 50%"), ["trailing '%', write %% for a literal '%'"]);
+        assert_eq!(errors("printf %z"), ["%z is not a valid field code, write %% for a literal '%'"]);
+    }
+
+    #[test]
+    fn only_one_target_code() {
+        assert_eq!(errors("app %f %u"), ["only one of %f, %F, %u and %U may be used, found %f %u"]);
+        assert_eq!(errors("app %F --url=%U").len(), 2, "%U is not alone and both list codes are used");
+        assert_eq!(errors("app %F"), Vec::<String>::new());
+        assert_eq!(errors("%f"), ["the program itself must not be a field code"]);
+        assert_eq!(errors(r#"app "%f""#), ["field code %f must not be used inside quotes"]);
+        assert_eq!(errors(""), ["no program to run"]);
+    }
+
+    #[test]
+    fn deprecated_codes_warn_and_expand_to_nothing() {
+        assert_eq!(errors("app %d %D %n %N %v %m %f"), Vec::<String>::new());
+        assert_eq!(warnings("app %d %m").len(), 2);
+        assert_eq!(warnings("app %d")[0], "field code %d is deprecated and expands to nothing");
+        assert_eq!(argv("app %d %m %f", &["/tmp/a"]), ["app", "/tmp/a"]);
+    }
+
+    #[test]
+    fn quoted_arguments_tokenize_back() {
+        let words = ["", " ", "plain", "two words", "tab\there", "line\nbreak", "\"quoted\"", "back\\slash", "$HOME", "`cmd`", "50%", "%f", "a;b", "it's", "~/x", "é ü"];
+        for a in words {
+            for b in words {
+                let args = vec!["/opt/my app/bin".to_string(), a.to_string(), b.to_string()];
+                // A literal % is escaped first, as quote() documents
+                let exec: Vec<String> = args.iter().map(|arg| quote(&arg.replace('%', "%%"))).collect();
+                let exec = exec.join(" ");
+                assert_eq!(errors(&exec), Vec::<String>::new(), "{}", exec);
+                assert_eq!(argv(&exec, &["/should/not/appear"]), args, "{}", exec);
+            }
+        }
+    }
+
+    #[test]
+    fn expand_with_no_one_or_many_targets() {
+        let many = ["/tmp/a b.txt", "file:///tmp/c%20d.txt", "/tmp/e"];
+        assert_eq!(argv("viewer --new %F", &[]), ["viewer", "--new"]);
+        assert_eq!(argv("viewer --new %F", &many[..1]), ["viewer", "--new", "/tmp/a b.txt"]);
+        assert_eq!(argv("viewer --new %F", &many), ["viewer", "--new", "/tmp/a b.txt", "/tmp/c d.txt", "/tmp/e"]);
+        assert_eq!(argv("viewer %U", &many), ["viewer", "/tmp/a b.txt", "file:///tmp/c%20d.txt", "/tmp/e"]);
+        assert_eq!(argv("viewer %f", &[]), ["viewer"]);
+        assert_eq!(argv("viewer --open=%f", &many[1..]), ["viewer", "--open=/tmp/c d.txt"]);
+        assert_eq!(argv("viewer %u", &many), ["viewer", "/tmp/a b.txt"]);
+        assert_eq!(argv("viewer %i --title=%c %k", &[]), ["viewer", "--icon", "app-icon", "--title=App", "/apps/app.desktop"]);
+    }
+
+    #[test]
+    fn replacing_the_program_keeps_the_arguments() {
+        assert_eq!(replace_program(r#"  /old/app --flag "a b" %U"#, "/new dir/app"), r#""/new dir/app" --flag "a b" %U"#);
+        assert_eq!(replace_program(r#""/old dir/app" %f"#, "run%s"), "run%%s %f");
+        assert_eq!(raw_args(r#"app "a \" b" c"#), ["app", r#""a \" b""#, "c"]);
+    }
+}
@@ -0,0 +1,87 @@
+// Flatpak launch commands. An app is deployed to
+// <installation>/app/<id>/<arch>/<branch>/<commit>/files, a directory that is replaced on
+// every update, so launchers have to go through `flatpak run` instead of pointing into it.
+use crate::domain::exec;
+
+// One installed branch of an app, as in the ref app/<id>/<arch>/<branch>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppRef {
+    pub id: String,
+    pub arch: String,
+    pub branch: String,
+}
+
+// The app a path belongs to when it lies inside a deployment ("…/app/<id>/<arch>/<branch>/
+// <commit>/files/…"). `active` is a symlink to the current commit and changes just the same.
+pub fn deploy_ref(path: &str) -> Option<AppRef> {
+    let parts: Vec<&str> = path.split('/').collect();
+    (0..parts.len().saturating_sub(5)).find_map(|i| {
+        let [app, id, arch, branch, commit, files] = parts[i..i + 6] else { return None };
+        let plausible = app == "app" && files == "files" && id.contains('.') && !arch.is_empty() && !branch.is_empty() && !commit.is_empty();
+        plausible.then(|| AppRef { id: id.into(), arch: arch.into(), branch: branch.into() })
+    })
+}
+
+// The deployment an Exec line runs from, if its program lies inside one
+pub fn exec_deploy_ref(exec_line: &str) -> Option<AppRef> {
+    let (line, _) = exec::parse(exec_line);
+    deploy_ref(&line.args.first()?.text())
+}
+
+// `flatpak run` command line for an app. `args` are Exec arguments as written; a bare file
+// or URL field code among them is wrapped in @@ (files) or @@u (URLs) markers so Flatpak
+// hands the files to the sandbox through the document portal.
+pub fn run_exec(app: &AppRef, command: Option<&str>, args: &[&str]) -> String {
+    let mut parts = vec![
+        "flatpak".to_string(),
+        "run".to_string(),
+        exec::quote(&format!("--branch={}", app.branch)),
+        exec::quote(&format!("--arch={}", app.arch)),
+    ];
+    if let Some(command) = command.filter(|c| !c.is_empty()) {
+        parts.push(exec::quote(&format!("--command={}", command.replace('%', "%%"))));
+    }
+    let forwards = args.iter().any(|a| matches!(*a, "%f" | "%F" | "%u" | "%U"));
+    if forwards {
+        parts.push("--file-forwarding".into());
+    }
+    parts.push(exec::quote(&app.id));
+    for arg in args {
+        match *arg {
+            "%f" | "%F" => parts.extend(["@@".to_string(), arg.to_string(), "@@".to_string()]),
+            "%u" | "%U" => parts.extend(["@@u".to_string(), arg.to_string(), "@@".to_string()]),
+            _ => parts.push(arg.to_string()),
+        }
+    }
+    parts.join(" ")
+}
+
+// Rebuild an Exec line around `app`, keeping the arguments meant for the app itself. The
+// line may already be a `flatpak run` command (as in exported launchers: its --command wins
+// over `command`, its other options and forwarding markers are dropped) or run a program
+// directly.
+pub fn rebase_exec(exec_line: &str, app: &AppRef, command: Option<&str>) -> String {
+    let args = exec::raw_args(exec_line);
+    let text = |raw: &str| exec::parse(raw).0.args.first().map(|a| a.text()).unwrap_or_default();
+    let mut command = command.map(str::to_string);
+    let mut rest: Vec<&str> = args.iter().skip(1).copied().collect();
+    if args.first().is_some_and(|p| text(p).rsplit('/').next() == Some("flatpak")) && args.get(1) == Some(&"run") {
+        let options: Vec<&str> = args[2..].iter().copied().take_while(|a| a.starts_with('-')).collect();
+        if let Some(c) = options.iter().find_map(|o| text(o).strip_prefix("--command=").map(str::to_string)) {
+            command = Some(c);
+        }
+        rest = args[2 + options.len()..].iter().skip(1).copied().collect();
+    }
+    rest.retain(|a| !matches!(*a, "@@" | "@@u"));
+    run_exec(app, command.as_deref(), &rest)
+}
+
+// Exec for a hand-made launcher that runs a file from a deployment: the same program by
+// its path inside the sandbox (/app), through `flatpak run`
+pub fn exec_for_deploy_path(exec_line: &str) -> Option<String> {
+    let app = exec_deploy_ref(exec_line)?;
+    let (line, _) = exec::parse(exec_line);
+    let program = line.args.first()?.text();
+    let inner = program.split_once("/files/").map(|(_, rest)| format!("/app/{}", rest))?;
+    Some(rebase_exec(exec_line, &app, Some(&inner)))
+}
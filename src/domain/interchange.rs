@@ -0,0 +1,306 @@
+// Structured (JSON/TOML) form of a DesktopEntry, for tools that generate launchers from
+// data instead of writing key files. The layout is versioned by `format` and documented
+// in the README ("Launcher documents") and data/launcher-document.schema.json; fields
+// are only ever added, never renamed.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::domain::desktop_action::DesktopAction;
+use crate::domain::desktop_entry::DesktopEntry;
+
+pub const FORMAT_VERSION: u32 = 1;
+
+// Keys that have their own field; `extra` may not repeat them
+const MODELED_KEYS: &[&str] = &[
+    "Type", "Name", "GenericName", "Comment", "Exec", "TryExec", "Icon", "Path", "URL", "Terminal", "NoDisplay",
+    "StartupNotify", "Categories", "MimeType", "Keywords", "OnlyShowIn", "NotShowIn", "Actions",
+];
+const MODELED_ACTION_KEYS: &[&str] = &["Name", "Exec", "Icon"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LauncherDocument {
+    #[serde(default = "format_version")]
+    pub format: u32,
+    #[serde(rename = "type", default = "application")]
+    pub type_field: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub name_localized: BTreeMap<String, String>, // locale -> value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generic_name: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub generic_name_localized: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub comment_localized: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub exec: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub try_exec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub terminal: bool,
+    #[serde(default)]
+    pub no_display: bool,
+    #[serde(default)]
+    pub startup_notify: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mime_type: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only_show_in: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_show_in: Vec<String>,
+    // In Actions= order; Actions= itself is written from this list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<ActionDocument>,
+    // Any other [Desktop Entry] key, raw as in the file (X-*, Version, Keywords[fr], ...)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionDocument {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub name_localized: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    // Any other key of the group, raw as in the file
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+}
+
+fn format_version() -> u32 {
+    FORMAT_VERSION
+}
+
+fn application() -> String {
+    "Application".into()
+}
+
+impl From<&DesktopEntry> for LauncherDocument {
+    fn from(e: &DesktopEntry) -> Self {
+        // Actions= decides the order; groups it does not list follow
+        let mut actions: Vec<&DesktopAction> = e.actions.iter().filter_map(|id| e.desktop_actions.iter().find(|a| &a.id == id)).collect();
+        actions.extend(e.desktop_actions.iter().filter(|a| !e.actions.contains(&a.id)));
+        LauncherDocument {
+            format: FORMAT_VERSION,
+            type_field: e.type_field.clone(),
+            name: e.name.clone(),
+            name_localized: e.name_localized.iter().cloned().collect(),
+            generic_name: e.generic_name.clone(),
+            generic_name_localized: e.generic_name_localized.iter().cloned().collect(),
+            comment: e.comment.clone(),
+            comment_localized: e.comment_localized.iter().cloned().collect(),
+            exec: e.exec.clone(),
+            try_exec: e.try_exec.clone(),
+            icon: e.icon.clone(),
+            path: e.path.clone(),
+            url: e.url.clone(),
+            terminal: e.terminal,
+            no_display: e.no_display,
+            startup_notify: e.startup_notify,
+            categories: e.categories.clone(),
+            mime_type: e.mime_type.clone(),
+            keywords: e.keywords.clone(),
+            only_show_in: e.only_show_in.clone(),
+            not_show_in: e.not_show_in.clone(),
+            actions: actions
+                .into_iter()
+                .map(|a| ActionDocument {
+                    id: a.id.clone(),
+                    name: a.name.clone(),
+                    name_localized: a.name_localized.iter().cloned().collect(),
+                    exec: a.exec.clone(),
+                    icon: a.icon.clone(),
+                    extra: a.extra.iter().map(|(k, v)| (k.trim().to_string(), v.trim().to_string())).collect(),
+                })
+                .collect(),
+            extra: e.extra.iter().map(|(k, v)| (k.trim().to_string(), v.trim().to_string())).collect(),
+        }
+    }
+}
+
+impl TryFrom<LauncherDocument> for DesktopEntry {
+    type Error = String;
+
+    fn try_from(d: LauncherDocument) -> Result<Self, String> {
+        if d.format > FORMAT_VERSION {
+            return Err(format!("format {} is newer than the supported format {}", d.format, FORMAT_VERSION));
+        }
+        if let Some(key) = d.extra.keys().find(|k| MODELED_KEYS.contains(&k.as_str())) {
+            return Err(format!("extra: {} has its own field", key));
+        }
+        let mut ids: Vec<&str> = Vec::new();
+        for a in &d.actions {
+            if a.id.trim().is_empty() {
+                return Err("actions: an action has an empty id".into());
+            }
+            if ids.contains(&a.id.as_str()) {
+                return Err(format!("actions: duplicate id \"{}\"", a.id));
+            }
+            if let Some(key) = a.extra.keys().find(|k| MODELED_ACTION_KEYS.contains(&k.as_str())) {
+                return Err(format!("actions: {}: extra: {} has its own field", a.id, key));
+            }
+            ids.push(&a.id);
+        }
+        Ok(DesktopEntry {
+            type_field: d.type_field,
+            name: d.name,
+            generic_name: d.generic_name,
+            comment: d.comment,
+            exec: d.exec,
+            icon: d.icon,
+            terminal: d.terminal,
+            categories: d.categories,
+            mime_type: d.mime_type,
+            keywords: d.keywords,
+            only_show_in: d.only_show_in,
+            not_show_in: d.not_show_in,
+            no_display: d.no_display,
+            startup_notify: d.startup_notify,
+            try_exec: d.try_exec,
+            path: d.path,
+            url: d.url,
+            actions: d.actions.iter().map(|a| a.id.clone()).collect(),
+            extra: d.extra.into_iter().collect(),
+            desktop_actions: d
+                .actions
+                .into_iter()
+                .map(|a| DesktopAction {
+                    id: a.id,
+                    name: a.name,
+                    name_localized: a.name_localized.into_iter().collect(),
+                    icon: a.icon,
+                    exec: a.exec,
+                    extra: a.extra.into_iter().collect(),
+                })
+                .collect(),
+            name_localized: d.name_localized.into_iter().collect(),
+            generic_name_localized: d.generic_name_localized.into_iter().collect(),
+            comment_localized: d.comment_localized.into_iter().collect(),
+        })
+    }
+}
+
+pub fn to_json(entry: &DesktopEntry) -> String {
+    serde_json::to_string_pretty(&LauncherDocument::from(entry)).expect("a launcher document always serializes") + "\n"
+}
+
+pub fn to_toml(entry: &DesktopEntry) -> String {
+    toml::to_string_pretty(&LauncherDocument::from(entry)).expect("a launcher document always serializes")
+}
+
+pub fn from_json(text: &str) -> Result<DesktopEntry, String> {
+    let doc: LauncherDocument = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    DesktopEntry::try_from(doc)
+}
+
+pub fn from_toml(text: &str) -> Result<DesktopEntry, String> {
+    let doc: LauncherDocument = toml::from_str(text).map_err(|e| e.to_string())?;
+    DesktopEntry::try_from(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    // Localized values and extra keys in the order documents keep them: sorted by key
+    fn entry() -> DesktopEntry {
+        DesktopEntry {
+            type_field: "Application".into(),
+            name: "Notes".into(),
+            name_localized: pairs(&[("de", "Notizen"), ("fr", "Bloc-notes"), ("pt_BR", "Anotações")]),
+            generic_name: Some("Text Editor".into()),
+            generic_name_localized: pairs(&[("fr", "Éditeur de texte")]),
+            comment: Some("Take notes; quickly".into()),
+            comment_localized: pairs(&[("es", "Tomar notas")]),
+            exec: "notes --new-window %U".into(),
+            try_exec: Some("notes".into()),
+            icon: Some("accessories-text-editor".into()),
+            terminal: true,
+            startup_notify: true,
+            categories: vec!["Utility".into(), "TextEditor".into()],
+            mime_type: vec!["text/plain".into()],
+            keywords: vec!["memo".into(), "a;b".into()],
+            only_show_in: vec!["GNOME".into()],
+            actions: vec!["new-note".into(), "open".into()],
+            desktop_actions: vec![
+                DesktopAction {
+                    id: "new-note".into(),
+                    name: "New Note".into(),
+                    name_localized: pairs(&[("fr", "Nouvelle note")]),
+                    exec: Some("notes --new".into()),
+                    icon: None,
+                    extra: pairs(&[("X-Shortcut", "Ctrl+N")]),
+                },
+                DesktopAction { id: "open".into(), name: "Open".into(), exec: Some("notes --open".into()), ..Default::default() },
+            ],
+            extra: pairs(&[("Keywords[fr]", "mémo;"), ("Version", "1.5"), ("X-GNOME-UsesNotifications", "true")]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let e = entry();
+        assert_eq!(from_json(&to_json(&e)), Ok(e));
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let e = entry();
+        assert_eq!(from_toml(&to_toml(&e)), Ok(e));
+    }
+
+    #[test]
+    fn maps_come_back_sorted_by_key() {
+        let mut e = entry();
+        e.extra.reverse();
+        e.name_localized.reverse();
+        let back = from_json(&to_json(&e)).unwrap();
+        assert_eq!(back.extra, entry().extra);
+        assert_eq!(back.name_localized, entry().name_localized);
+        // Actions keep Actions= order, not id order
+        e.actions.reverse();
+        let ids: Vec<String> = from_toml(&to_toml(&e)).unwrap().desktop_actions.into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, ["open", "new-note"]);
+    }
+
+    #[test]
+    fn newer_formats_are_rejected() {
+        assert_eq!(from_json(r#"{"format": 2, "name": "x"}"#), Err("format 2 is newer than the supported format 1".to_string()));
+        assert!(from_toml("format = 2\nname = \"x\"\n").is_err());
+        let current = from_json(r#"{"format": 1, "name": "x"}"#).unwrap();
+        assert_eq!((current.type_field.as_str(), current.name.as_str()), ("Application", "x"));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let unknown = from_json(r#"{"name": "x", "colour": "red"}"#).unwrap_err();
+        assert!(unknown.contains("unknown field `colour`"), "{}", unknown);
+        let in_action = from_toml("name = \"x\"\n[[actions]]\nid = \"a\"\nname = \"A\"\nshortcut = \"Ctrl+A\"\n").unwrap_err();
+        assert!(in_action.contains("unknown field `shortcut`"), "{}", in_action);
+        assert_eq!(from_json(r#"{"name": "x", "extra": {"Exec": "y"}}"#), Err("extra: Exec has its own field".to_string()));
+        assert!(from_json(r#"{"exec": "x"}"#).unwrap_err().contains("missing field `name`"));
+    }
+}
@@ -0,0 +1,235 @@
+// The shared-mime-info database: types described in packages/*.xml, plus the globs2,
+// aliases and subclasses files update-mime-database generates from them. Loaded by
+// services::mime; validation checks MimeType= against the one it is given.
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MimeType {
+    pub name: String,
+    pub comment: Option<String>, // untranslated description
+    pub globs: Vec<String>,      // "*.md"
+    pub aliases: Vec<String>,
+    pub parents: Vec<String>, // sub-class-of
+}
+
+impl MimeType {
+    // Whether the type, its description or one of its file extensions matches `query`;
+    // "md", ".md" and "*.md" all find text/markdown
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let extension = query.trim_start_matches('*').trim_start_matches('.');
+        self.name.contains(&query)
+            || self.aliases.iter().any(|a| a.contains(&query))
+            || self.comment.as_deref().is_some_and(|c| c.to_lowercase().contains(&query))
+            || self.globs.iter().any(|g| g.to_lowercase().strip_prefix("*.").is_some_and(|e| e == extension) || g.to_lowercase() == query)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MimeDatabase {
+    types: BTreeMap<String, MimeType>,
+    aliases: HashMap<String, String>, // alias -> type
+}
+
+// Pseudo types for URL schemes (x-scheme-handler/https) are valid in MimeType= but are
+// not part of the database
+pub fn is_scheme_handler(name: &str) -> bool {
+    name.starts_with("x-scheme-handler/")
+}
+
+impl MimeDatabase {
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    pub fn types(&self) -> impl Iterator<Item = &MimeType> {
+        self.types.values()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MimeType> {
+        self.types.get(self.canonical(name)?)
+    }
+
+    // The type `name` stands for: itself, or the type it is an alias of. None if unknown.
+    pub fn canonical(&self, name: &str) -> Option<&str> {
+        let name = name.trim().to_lowercase();
+        if let Some(target) = self.aliases.get(&name) {
+            return Some(target);
+        }
+        self.types.get_key_value(&name).map(|(k, _)| k.as_str())
+    }
+
+    // The known type closest to an unknown one, for "did you mean" hints: one typo away,
+    // or two for long subtypes
+    pub fn closest(&self, name: &str) -> Option<&str> {
+        let name = name.trim().to_lowercase();
+        self.types
+            .keys()
+            .map(|t| (edit_distance(&name, t), t))
+            .filter(|(d, t)| *d > 0 && *d <= if t.split('/').nth(1).unwrap_or("").len() >= 8 { 2 } else { 1 })
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, t)| t.as_str())
+    }
+
+    // One packages/*.xml file. Only the elements used here are read: comment (without
+    // xml:lang), glob, alias and sub-class-of of each mime-type.
+    pub fn add_package(&mut self, xml: &str) {
+        let mut current: Option<String> = None;
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start..];
+            if let Some(after) = rest.strip_prefix("<!--") {
+                rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+                continue;
+            }
+            let Some(end) = tag_end(rest) else { break };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+            let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+            match name {
+                "mime-type" => {
+                    current = attribute(tag, "type").map(|t| t.to_lowercase());
+                    if let Some(t) = &current {
+                        self.types.entry(t.clone()).or_insert_with(|| MimeType { name: t.clone(), ..Default::default() });
+                    }
+                }
+                "/mime-type" => current = None,
+                "comment" if attribute(tag, "xml:lang").is_none() && !tag.ends_with('/') => {
+                    let text = rest.find('<').map_or(rest, |e| &rest[..e]);
+                    if let Some(t) = current.as_ref().and_then(|t| self.types.get_mut(t)) {
+                        t.comment = Some(unescape(text.trim()));
+                    }
+                }
+                "glob" | "alias" | "sub-class-of" => {
+                    let key = if name == "glob" { "pattern" } else { "type" };
+                    if let (Some(t), Some(value)) = (current.clone(), attribute(tag, key)) {
+                        match name {
+                            "glob" => self.add_glob(&t, &value),
+                            "alias" => self.add_alias(&value, &t),
+                            _ => self.add_parent(&t, &value),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // globs2: "weight:type:pattern[:flags]" per line
+    pub fn add_globs2(&mut self, text: &str) {
+        for line in data_lines(text) {
+            let mut fields = line.splitn(4, ':');
+            if let (Some(_), Some(t), Some(pattern)) = (fields.next(), fields.next(), fields.next()) {
+                self.add_glob(&t.to_lowercase(), pattern);
+            }
+        }
+    }
+
+    // aliases: "alias type" per line
+    pub fn add_aliases(&mut self, text: &str) {
+        for line in data_lines(text) {
+            if let Some((alias, target)) = line.split_once(' ') {
+                self.add_alias(alias, &target.trim().to_lowercase());
+            }
+        }
+    }
+
+    // subclasses: "type parent" per line
+    pub fn add_subclasses(&mut self, text: &str) {
+        for line in data_lines(text) {
+            if let Some((t, parent)) = line.split_once(' ') {
+                self.add_parent(&t.to_lowercase(), parent.trim());
+            }
+        }
+    }
+
+    fn entry(&mut self, name: &str) -> &mut MimeType {
+        self.types.entry(name.to_string()).or_insert_with(|| MimeType { name: name.to_string(), ..Default::default() })
+    }
+
+    fn add_glob(&mut self, name: &str, pattern: &str) {
+        let t = self.entry(name);
+        if !t.globs.iter().any(|g| g == pattern) {
+            t.globs.push(pattern.to_string());
+        }
+    }
+
+    fn add_alias(&mut self, alias: &str, name: &str) {
+        let alias = alias.trim().to_lowercase();
+        let t = self.entry(name);
+        if !t.aliases.contains(&alias) {
+            t.aliases.push(alias.clone());
+        }
+        self.aliases.insert(alias, name.to_string());
+    }
+
+    fn add_parent(&mut self, name: &str, parent: &str) {
+        let parent = parent.trim().to_lowercase();
+        let t = self.entry(name);
+        if !t.parents.contains(&parent) {
+            t.parents.push(parent);
+        }
+    }
+}
+
+fn data_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#'))
+}
+
+// Index of the '>' closing the tag at the start of `text`, skipping quoted attribute values
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('>', None) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(name) {
+        let before = rest[..at].chars().last();
+        let after = rest[at + name.len()..].trim_start();
+        rest = &rest[at + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=').map(str::trim_start) else { continue };
+        let quote = value.chars().next().filter(|q| *q == '"' || *q == '\'')?;
+        let value = &value[1..];
+        return value.find(quote).map(|end| unescape(&value[..end]));
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+// Edits (insert, delete, replace, swap two neighbours) that turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -1 +1,10 @@
 pub mod desktop_entry;
+pub mod desktop_action;
+pub mod escape;
+pub mod exec;
+pub mod validation;
+pub mod interchange;
+pub mod template;
+pub mod flatpak;
+pub mod webapp;
+pub mod mime;
@@ -0,0 +1,196 @@
+// Launcher templates: a launcher document (see domain::interchange) whose strings may hold
+// `{{variable}}` placeholders, plus the variables the user is asked to fill in. Templates
+// are TOML files; the built-in ones live in data/templates.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::exec;
+use crate::domain::interchange::LauncherDocument;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableKind {
+    #[default]
+    Text,
+    File,   // picked with a file chooser
+    Folder, // picked with a folder chooser
+    Url,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Variable {
+    pub id: String, // used as {{id}}
+    pub label: String,
+    #[serde(default)]
+    pub kind: VariableKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<Variable>,
+    pub entry: LauncherDocument,
+}
+
+impl Template {
+    pub fn from_toml(text: &str) -> Result<Template, String> {
+        let template: Template = toml::from_str(text).map_err(|e| e.to_string())?;
+        // Catch placeholders without a variable when the template is loaded, not when used
+        let declared: Vec<&str> = template.variables.iter().map(|v| v.id.as_str()).collect();
+        let mut unknown = Vec::new();
+        for_each_string(&mut DesktopEntry::try_from(template.entry.clone())?, &mut |s, _| {
+            unknown.extend(placeholders(s).into_iter().filter(|p| !declared.contains(&p.as_str())));
+        });
+        match unknown.first() {
+            Some(name) => Err(format!("{{{{{}}}}} is not a declared variable", name)),
+            None => Ok(template),
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("a template always serializes")
+    }
+
+    // A template that reproduces `entry` as is, without variables
+    pub fn from_entry(entry: &DesktopEntry, title: &str, description: &str) -> Template {
+        Template { title: title.to_string(), description: description.to_string(), variables: Vec::new(), entry: LauncherDocument::from(entry) }
+    }
+
+    // Fill in the placeholders. Missing values fall back to the variable's default; a
+    // variable with neither is an error. Values land in Exec as whole, quoted arguments.
+    pub fn instantiate(&self, values: &HashMap<String, String>) -> Result<DesktopEntry, String> {
+        let mut resolved: HashMap<&str, String> = HashMap::new();
+        for v in &self.variables {
+            let value = values.get(&v.id).map(|s| s.trim()).filter(|s| !s.is_empty()).map(str::to_string).or_else(|| v.default.clone());
+            match value {
+                Some(value) => resolved.insert(v.id.as_str(), value),
+                None => return Err(format!("{} is required", v.label)),
+            };
+        }
+        let mut entry = DesktopEntry::try_from(self.entry.clone())?;
+        for_each_string(&mut entry, &mut |s, is_exec| {
+            if !s.contains("{{") {
+                return;
+            }
+            *s = if is_exec { substitute_exec(s, &resolved) } else { substitute(s, &resolved, |v| v.to_string()) };
+        });
+        Ok(entry)
+    }
+}
+
+// Every string of the entry, with whether it is an Exec command line
+fn for_each_string(e: &mut DesktopEntry, f: &mut impl FnMut(&mut String, bool)) {
+    f(&mut e.exec, true);
+    for s in [&mut e.name, &mut e.type_field] {
+        f(s, false);
+    }
+    for s in [&mut e.generic_name, &mut e.comment, &mut e.icon, &mut e.try_exec, &mut e.path, &mut e.url].into_iter().flatten() {
+        f(s, false);
+    }
+    for list in [&mut e.categories, &mut e.mime_type, &mut e.keywords, &mut e.only_show_in, &mut e.not_show_in] {
+        list.iter_mut().for_each(|s| f(s, false));
+    }
+    for (_, s) in e.name_localized.iter_mut().chain(e.generic_name_localized.iter_mut()).chain(e.comment_localized.iter_mut()).chain(e.extra.iter_mut()) {
+        f(s, false);
+    }
+    for a in &mut e.desktop_actions {
+        f(&mut a.name, false);
+        a.name_localized.iter_mut().chain(a.extra.iter_mut()).for_each(|(_, s)| f(s, false));
+        if let Some(s) = &mut a.icon {
+            f(s, false);
+        }
+        if let Some(s) = &mut a.exec {
+            f(s, true);
+        }
+    }
+}
+
+fn placeholders(s: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        out.push(rest[start + 2..start + 2 + len].trim().to_string());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out
+}
+
+fn substitute(s: &str, values: &HashMap<&str, String>, render: impl Fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        out.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&values.get(name).map(|v| render(v)).unwrap_or_default());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// Placeholders are replaced inside their argument, which is then quoted as a whole when
+// the value needs it; arguments without placeholders are kept verbatim
+fn substitute_exec(line: &str, values: &HashMap<&str, String>) -> String {
+    let mut out = String::new();
+    let mut token = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for c in line.chars().chain(std::iter::once(' ')) {
+        if !in_quotes && c.is_whitespace() {
+            if !token.is_empty() {
+                out.push_str(&exec_token(&token, values));
+                token.clear();
+            }
+            out.push(c);
+            continue;
+        }
+        if in_quotes && !escaped && c == '\\' {
+            escaped = true;
+        } else {
+            if c == '"' && !escaped {
+                in_quotes = !in_quotes;
+            }
+            escaped = false;
+        }
+        token.push(c);
+    }
+    out.pop();
+    out
+}
+
+fn exec_token(token: &str, values: &HashMap<&str, String>) -> String {
+    if !token.contains("{{") {
+        return token.to_string();
+    }
+    // A token written quoted in the template is unquoted first, then requoted as a whole
+    let inner = match token.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        Some(quoted) => unquote(quoted),
+        None => token.to_string(),
+    };
+    exec::quote(&substitute(&inner, values, |v| v.replace('%', "%%")))
+}
+
+fn unquote(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
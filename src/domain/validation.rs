@@ -0,0 +1,579 @@
+// Checks modelled on desktop-file-validate (desktop-file-utils). Every finding carries the
+// section of the Desktop Entry or Desktop Menu specification it comes from.
+use std::fmt;
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::exec;
+use crate::domain::flatpak;
+use crate::domain::mime::{self, MimeDatabase};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub group: String,
+    pub key: Option<String>,
+    pub message: String,
+    pub spec: &'static str,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity { Severity::Error => "error", Severity::Warning => "warning" };
+        match &self.key {
+            Some(key) => write!(f, "{}: [{}] {}: {} ({})", level, self.group, key, self.message, self.spec),
+            None => write!(f, "{}: [{}] {} ({})", level, self.group, self.message, self.spec),
+        }
+    }
+}
+
+pub const SPEC_KEYS: &str = "Desktop Entry Spec, Recognized desktop entry keys";
+pub const SPEC_FORMAT: &str = "Desktop Entry Spec, Basic format of the file";
+pub const SPEC_LOCALE: &str = "Desktop Entry Spec, Localized values for keys";
+pub const SPEC_EXTENSIONS: &str = "Desktop Entry Spec, Extending the format";
+pub const SPEC_DEPRECATED: &str = "Desktop Entry Spec, Deprecated Items";
+pub const SPEC_EXEC: &str = "Desktop Entry Spec, The Exec key";
+pub const SPEC_ACTIONS: &str = "Desktop Entry Spec, Additional applications actions";
+pub const SPEC_MIME: &str = "Shared MIME-info Database Spec";
+pub const SPEC_ICON: &str = "Icon Theme Spec, Icon Lookup";
+pub const SPEC_CATEGORIES: &str = "Desktop Menu Spec, Registered Categories";
+pub const SPEC_ENVIRONMENTS: &str = "Desktop Menu Spec, Registered OnlyShowIn Environments";
+
+const KNOWN_KEYS: &[&str] = &[
+    "Type", "Version", "Name", "GenericName", "NoDisplay", "Comment", "Icon", "Hidden",
+    "OnlyShowIn", "NotShowIn", "DBusActivatable", "TryExec", "Exec", "Path", "Terminal",
+    "Actions", "MimeType", "Categories", "Implements", "Keywords", "StartupNotify",
+    "StartupWMClass", "URL", "PrefersNonDefaultGPU", "SingleMainWindow",
+];
+
+// Not in the spec but used by session managers; desktop-file-validate accepts them
+const SESSION_KEYS: &[&str] = &["AutostartCondition"];
+
+const LOCALIZABLE_KEYS: &[&str] = &["Name", "GenericName", "Comment", "Icon", "Keywords"];
+
+const BOOLEAN_KEYS: &[&str] = &["Hidden", "DBusActivatable", "PrefersNonDefaultGPU", "SingleMainWindow"];
+
+// Deprecated keys that desktop-file-validate still accepts (with a warning)
+const DEPRECATED_KEYS: &[&str] = &[
+    "Encoding", "MiniIcon", "TerminalOptions", "Protocols", "Extensions", "BinaryPattern",
+    "MapNotify", "SwallowTitle", "SwallowExec", "SortOrder", "FilePattern", "Patterns",
+    "DefaultApp", "DocPath", "ServiceTypes", "InitialPreference", "Dev", "FSType",
+    "MountPoint", "ReadOnly", "UnmountIcon",
+];
+
+const VERSIONS: &[&str] = &["1.0", "1.1", "1.2", "1.3", "1.4", "1.5"];
+
+pub const MAIN_CATEGORIES: &[&str] = &[
+    "AudioVideo", "Audio", "Video", "Development", "Education", "Game", "Graphics", "Network",
+    "Office", "Science", "Settings", "System", "Utility",
+];
+
+// Additional categories with the categories they should appear with: each inner slice is
+// one acceptable combination, an empty list means no requirement.
+pub const ADDITIONAL_CATEGORIES: &[(&str, &[&[&str]])] = &[
+    ("Building", &[&["Development"]]),
+    ("Debugger", &[&["Development"]]),
+    ("IDE", &[&["Development"]]),
+    ("GUIDesigner", &[&["Development"]]),
+    ("Profiling", &[&["Development"]]),
+    ("RevisionControl", &[&["Development"]]),
+    ("Translation", &[&["Development"]]),
+    ("Calendar", &[&["Office"]]),
+    ("ContactManagement", &[&["Office"]]),
+    ("Database", &[&["Office"], &["Development"], &["AudioVideo"]]),
+    ("Dictionary", &[&["Office", "TextTools"]]),
+    ("Chart", &[&["Office"]]),
+    ("Email", &[&["Office", "Network"]]),
+    ("Finance", &[&["Office"]]),
+    ("FlowChart", &[&["Office"]]),
+    ("PDA", &[&["Office"]]),
+    ("ProjectManagement", &[&["Office", "Development"]]),
+    ("Presentation", &[&["Office"]]),
+    ("Spreadsheet", &[&["Office"]]),
+    ("WordProcessor", &[&["Office"]]),
+    ("2DGraphics", &[&["Graphics"]]),
+    ("VectorGraphics", &[&["Graphics", "2DGraphics"]]),
+    ("RasterGraphics", &[&["Graphics", "2DGraphics"]]),
+    ("3DGraphics", &[&["Graphics"]]),
+    ("Scanning", &[&["Graphics"]]),
+    ("OCR", &[&["Graphics", "Scanning"]]),
+    ("Photography", &[&["Graphics"], &["Office"]]),
+    ("Publishing", &[&["Graphics"], &["Office"]]),
+    ("Viewer", &[&["Graphics"], &["Office"]]),
+    ("TextTools", &[&["Utility"]]),
+    ("DesktopSettings", &[&["Settings"]]),
+    ("HardwareSettings", &[&["Settings"]]),
+    ("Printing", &[&["HardwareSettings", "Settings"]]),
+    ("PackageManager", &[&["Settings"]]),
+    ("Dialup", &[&["Network"]]),
+    ("InstantMessaging", &[&["Network"]]),
+    ("Chat", &[&["Network"]]),
+    ("IRCClient", &[&["Network"]]),
+    ("Feed", &[&["Network"]]),
+    ("FileTransfer", &[&["Network"]]),
+    ("HamRadio", &[&["Network"], &["Audio"]]),
+    ("News", &[&["Network"]]),
+    ("P2P", &[&["Network"]]),
+    ("RemoteAccess", &[&["Network"]]),
+    ("Telephony", &[&["Network"]]),
+    ("TelephonyTools", &[&["Utility"]]),
+    ("VideoConference", &[&["Network"]]),
+    ("WebBrowser", &[&["Network"]]),
+    ("WebDevelopment", &[&["Network"], &["Development"]]),
+    ("Midi", &[&["AudioVideo", "Audio"]]),
+    ("Mixer", &[&["AudioVideo", "Audio"]]),
+    ("Sequencer", &[&["AudioVideo", "Audio"]]),
+    ("Tuner", &[&["AudioVideo", "Audio"]]),
+    ("TV", &[&["AudioVideo", "Video"]]),
+    ("AudioVideoEditing", &[&["Audio"], &["Video"], &["AudioVideo"]]),
+    ("Player", &[&["Audio"], &["Video"], &["AudioVideo"]]),
+    ("Recorder", &[&["Audio"], &["Video"], &["AudioVideo"]]),
+    ("DiscBurning", &[&["AudioVideo"]]),
+    ("ActionGame", &[&["Game"]]),
+    ("AdventureGame", &[&["Game"]]),
+    ("ArcadeGame", &[&["Game"]]),
+    ("BoardGame", &[&["Game"]]),
+    ("BlocksGame", &[&["Game"]]),
+    ("CardGame", &[&["Game"]]),
+    ("KidsGame", &[&["Game"]]),
+    ("LogicGame", &[&["Game"]]),
+    ("RolePlaying", &[&["Game"]]),
+    ("Shooter", &[&["Game"]]),
+    ("Simulation", &[&["Game"]]),
+    ("SportsGame", &[&["Game"]]),
+    ("StrategyGame", &[&["Game"]]),
+    ("Art", &[&["Education"], &["Science"]]),
+    ("Construction", &[&["Education"], &["Science"]]),
+    ("Music", &[&["AudioVideo"], &["Education"]]),
+    ("Languages", &[&["Education"], &["Science"]]),
+    ("ArtificialIntelligence", &[&["Education"], &["Science"]]),
+    ("Astronomy", &[&["Education"], &["Science"]]),
+    ("Biology", &[&["Education"], &["Science"]]),
+    ("Chemistry", &[&["Education"], &["Science"]]),
+    ("ComputerScience", &[&["Education"], &["Science"]]),
+    ("DataVisualization", &[&["Education"], &["Science"]]),
+    ("Economy", &[&["Education"], &["Science"]]),
+    ("Electricity", &[&["Education"], &["Science"]]),
+    ("Geography", &[&["Education"], &["Science"]]),
+    ("Geology", &[&["Education"], &["Science"]]),
+    ("Geoscience", &[&["Education"], &["Science"]]),
+    ("History", &[&["Education"], &["Science"]]),
+    ("Humanities", &[&["Education"], &["Science"]]),
+    ("ImageProcessing", &[&["Education"], &["Science"]]),
+    ("Literature", &[&["Education"], &["Science"]]),
+    ("Maps", &[&["Education"], &["Science"], &["Utility"]]),
+    ("Math", &[&["Education"], &["Science"]]),
+    ("NumericalAnalysis", &[&["Education", "Math"], &["Science", "Math"]]),
+    ("MedicalSoftware", &[&["Education"], &["Science"]]),
+    ("Physics", &[&["Education"], &["Science"]]),
+    ("Robotics", &[&["Education"], &["Science"]]),
+    ("Spirituality", &[&["Education"], &["Science"], &["Utility"]]),
+    ("Sports", &[&["Education"], &["Science"]]),
+    ("ParallelComputing", &[&["Education", "ComputerScience"], &["Science", "ComputerScience"]]),
+    ("Amusement", &[]),
+    ("Archiving", &[&["Utility"]]),
+    ("Compression", &[&["Utility"]]),
+    ("Electronics", &[]),
+    ("Emulator", &[&["System"], &["Game"]]),
+    ("Engineering", &[]),
+    ("FileTools", &[&["Utility"], &["System"]]),
+    ("FileManager", &[&["System", "FileTools"]]),
+    ("TerminalEmulator", &[&["System"]]),
+    ("Filesystem", &[&["System"]]),
+    ("Monitor", &[&["System"], &["Network"]]),
+    ("Security", &[&["Settings"], &["System"]]),
+    ("Accessibility", &[&["Settings"], &["Utility"]]),
+    ("Calculator", &[&["Utility"]]),
+    ("Clock", &[&["Utility"]]),
+    ("TextEditor", &[&["Utility"]]),
+    ("Documentation", &[]),
+    ("Adult", &[]),
+    ("Core", &[]),
+    ("KDE", &[&["Qt"]]),
+    ("GNOME", &[&["GTK"]]),
+    ("XFCE", &[&["GTK"]]),
+    ("DDE", &[&["Qt"]]),
+    ("GTK", &[]),
+    ("Qt", &[]),
+    ("Motif", &[]),
+    ("Java", &[]),
+    ("ConsoleOnly", &[]),
+];
+
+// Only valid together with OnlyShowIn
+const RESERVED_CATEGORIES: &[&str] = &["Screensaver", "TrayIcon", "Applet", "Shell"];
+
+const DEPRECATED_CATEGORIES: &[&str] = &["Application", "Applications"];
+
+pub const ENVIRONMENTS: &[&str] = &[
+    "GNOME", "GNOME-Classic", "GNOME-Flashback", "KDE", "LXDE", "LXQt", "MATE", "Razor", "ROX",
+    "TDE", "Unity", "XFCE", "EDE", "Cinnamon", "Pantheon", "Budgie", "Enlightenment", "DDE",
+    "Endless", "Old",
+];
+
+// With a MIME database, MimeType= values it does not know are reported too
+pub fn validate_entry(e: &DesktopEntry, mime_db: Option<&MimeDatabase>) -> Vec<Finding> {
+    let mut v = Validator { findings: Vec::new(), group: "Desktop Entry".into(), mime_db };
+    v.entry(e);
+    for action in &e.desktop_actions {
+        v.group = action.group_name();
+        if action.id.trim().is_empty() {
+            v.error(None, "Desktop Action groups need a name", SPEC_ACTIONS);
+        }
+        if action.name.trim().is_empty() {
+            v.error(Some("Name"), "required key is missing", SPEC_ACTIONS);
+        }
+        for (lang, _) in &action.name_localized {
+            v.locale("Name", lang);
+        }
+        if action.exec.as_deref().unwrap_or("").trim().is_empty() && !is_true(e.extra_value("DBusActivatable")) {
+            v.error(Some("Exec"), "required unless the application is DBusActivatable", SPEC_ACTIONS);
+        }
+        if let Some(exec) = &action.exec {
+            v.exec(exec);
+        }
+        for (key, _) in &action.extra {
+            v.action_key(key);
+        }
+        if !e.actions.contains(&action.id) {
+            v.warning(None, "group is not listed in the Actions key of [Desktop Entry]", SPEC_ACTIONS);
+        }
+    }
+    v.findings
+}
+
+struct Validator<'a> {
+    findings: Vec<Finding>,
+    group: String,
+    mime_db: Option<&'a MimeDatabase>,
+}
+
+impl Validator<'_> {
+    fn push(&mut self, severity: Severity, key: Option<&str>, message: &str, spec: &'static str) {
+        self.findings.push(Finding {
+            severity,
+            group: self.group.clone(),
+            key: key.map(str::to_string),
+            message: message.to_string(),
+            spec,
+        });
+    }
+
+    fn error(&mut self, key: Option<&str>, message: &str, spec: &'static str) {
+        self.push(Severity::Error, key, message, spec);
+    }
+
+    fn warning(&mut self, key: Option<&str>, message: &str, spec: &'static str) {
+        self.push(Severity::Warning, key, message, spec);
+    }
+
+    fn entry(&mut self, e: &DesktopEntry) {
+        match e.type_field.as_str() {
+            "Application" | "Link" | "Directory" => {}
+            "" => self.error(Some("Type"), "required key is missing", SPEC_KEYS),
+            "MimeType" | "ServiceType" | "Service" | "FSDevice" => self.error(
+                Some("Type"),
+                &format!("\"{}\" is a deprecated type, use Application, Link or Directory", e.type_field),
+                SPEC_DEPRECATED,
+            ),
+            other => self.error(Some("Type"), &format!("\"{}\" is not Application, Link or Directory", other), SPEC_KEYS),
+        }
+        if e.name.trim().is_empty() {
+            self.error(Some("Name"), "required key is missing", SPEC_KEYS);
+        }
+        if e.type_field == "Application" {
+            if e.exec.trim().is_empty() && !is_true(e.extra_value("DBusActivatable")) {
+                self.error(Some("Exec"), "required for Type=Application unless DBusActivatable=true", SPEC_KEYS);
+            }
+        } else if !e.exec.trim().is_empty() {
+            self.warning(Some("Exec"), &format!("only used for Type=Application, not {}", e.type_field), SPEC_KEYS);
+        }
+        if e.type_field == "Link" && e.url.as_deref().unwrap_or("").trim().is_empty() {
+            self.error(Some("URL"), "required for Type=Link", SPEC_KEYS);
+        }
+        if !e.exec.trim().is_empty() {
+            self.exec(&e.exec);
+        }
+
+        if e.comment.as_deref().is_some_and(|c| c == e.name) {
+            self.warning(Some("Comment"), "should not repeat the Name", SPEC_KEYS);
+        }
+        if e.generic_name.as_deref().is_some_and(|g| g == e.name) {
+            self.warning(Some("GenericName"), "should not repeat the Name", SPEC_KEYS);
+        }
+
+        if let Some(icon) = e.icon.as_deref().map(str::trim).filter(|i| !i.is_empty()) {
+            let has_ext = [".png", ".svg", ".svgz", ".xpm"].iter().any(|x| icon.to_ascii_lowercase().ends_with(x));
+            if has_ext && !icon.starts_with('/') {
+                self.warning(Some("Icon"), "icon names should not include an extension unless the value is an absolute path", SPEC_ICON);
+            }
+        }
+
+        for (lang, _) in &e.name_localized { self.locale("Name", lang); }
+        for (lang, _) in &e.generic_name_localized { self.locale("GenericName", lang); }
+        for (lang, _) in &e.comment_localized { self.locale("Comment", lang); }
+
+        self.categories(e);
+        self.environments(e);
+
+        for mime in &e.mime_type {
+            let valid = mime.split_once('/').is_some_and(|(t, s)| !t.is_empty() && !s.is_empty() && !s.contains('/'));
+            if !valid {
+                self.error(Some("MimeType"), &format!("\"{}\" is not a media type of the form type/subtype", mime), SPEC_KEYS);
+            } else if let Some(db) = self.mime_db.filter(|db| !db.is_empty())
+                && !mime::is_scheme_handler(mime)
+                && db.canonical(mime).is_none()
+            {
+                let hint = db.closest(mime).map(|t| format!(", did you mean {}?", t)).unwrap_or_default();
+                self.warning(Some("MimeType"), &format!("\"{}\" is not in the shared MIME database{}", mime, hint), SPEC_MIME);
+            }
+        }
+
+        for name in &e.actions {
+            if !e.desktop_actions.iter().any(|a| &a.id == name) {
+                self.error(Some("Actions"), &format!("action \"{}\" has no [Desktop Action {}] group", name, name), SPEC_ACTIONS);
+            }
+        }
+
+        for (key, value) in &e.extra {
+            self.extra_key(key, value);
+        }
+    }
+
+    fn extra_key(&mut self, key: &str, value: &str) {
+        let (base, locale) = split_locale(key);
+        if base.is_empty() || !base.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            self.error(Some(key), "key names may only contain A-Z, a-z, 0-9 and '-'", SPEC_FORMAT);
+            return;
+        }
+        if let Some(lang) = locale {
+            if !base.starts_with("X-") && !LOCALIZABLE_KEYS.contains(&base) && KNOWN_KEYS.contains(&base) {
+                self.error(Some(key), "this key is not localizable", SPEC_LOCALE);
+            }
+            self.locale(base, lang);
+        }
+        if base.starts_with("X-") || SESSION_KEYS.contains(&base) {
+            return;
+        }
+        if DEPRECATED_KEYS.contains(&base) {
+            if base == "Encoding" && value != "UTF-8" {
+                self.error(Some(key), "the only supported encoding is UTF-8", SPEC_DEPRECATED);
+            } else {
+                self.warning(Some(key), "deprecated key", SPEC_DEPRECATED);
+            }
+            return;
+        }
+        if !KNOWN_KEYS.contains(&base) {
+            self.error(Some(key), "unknown key, keys extending the format must start with X-", SPEC_EXTENSIONS);
+            return;
+        }
+        if BOOLEAN_KEYS.contains(&base) && value != "true" && value != "false" {
+            self.error(Some(key), &format!("\"{}\" is not a boolean (true or false)", value), SPEC_KEYS);
+        }
+        if base == "Version" && !VERSIONS.contains(&value) {
+            if value.starts_with("0.9") {
+                self.warning(Some(key), &format!("version {} is outdated, the current one is 1.5", value), SPEC_KEYS);
+            } else {
+                self.error(Some(key), &format!("\"{}\" is not a known version of the specification", value), SPEC_KEYS);
+            }
+        }
+    }
+
+    // Keys of a [Desktop Action] group other than Name, Icon and Exec
+    fn action_key(&mut self, key: &str) {
+        let (base, locale) = split_locale(key);
+        if base.is_empty() || !base.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            self.error(Some(key), "key names may only contain A-Z, a-z, 0-9 and '-'", SPEC_FORMAT);
+            return;
+        }
+        if let Some(lang) = locale {
+            self.locale(base, lang);
+        }
+        if !base.starts_with("X-") && base != "Icon" {
+            self.error(Some(key), "unknown key in an action group, keys extending the format must start with X-", SPEC_EXTENSIONS);
+        }
+    }
+
+    fn locale(&mut self, base: &str, lang: &str) {
+        if !is_valid_locale(lang) {
+            let key = format!("{}[{}]", base, lang);
+            self.error(Some(&key), &format!("\"{}\" is not a valid locale (lang_COUNTRY.ENCODING@MODIFIER)", lang), SPEC_LOCALE);
+        }
+    }
+
+    fn categories(&mut self, e: &DesktopEntry) {
+        let cats = &e.categories;
+        let mut seen: Vec<&str> = Vec::new();
+        for cat in cats {
+            let cat = cat.as_str();
+            if seen.contains(&cat) {
+                self.warning(Some("Categories"), &format!("\"{}\" is listed more than once", cat), SPEC_CATEGORIES);
+            }
+            seen.push(cat);
+            if cat.starts_with("X-") || MAIN_CATEGORIES.contains(&cat) {
+                continue;
+            }
+            if DEPRECATED_CATEGORIES.contains(&cat) {
+                self.warning(Some("Categories"), &format!("\"{}\" is deprecated", cat), SPEC_CATEGORIES);
+            } else if RESERVED_CATEGORIES.contains(&cat) {
+                if e.only_show_in.is_empty() {
+                    self.error(Some("Categories"), &format!("reserved category \"{}\" requires OnlyShowIn", cat), SPEC_CATEGORIES);
+                }
+            } else if let Some((_, related)) = ADDITIONAL_CATEGORIES.iter().find(|(name, _)| *name == cat) {
+                let satisfied = related.is_empty() || related.iter().any(|combo| combo.iter().all(|r| cats.iter().any(|c| c == r)));
+                if !satisfied {
+                    let options: Vec<String> = related.iter().map(|combo| combo.join(";")).collect();
+                    self.warning(Some("Categories"), &format!("\"{}\" should be used together with {}", cat, options.join(" or ")), SPEC_CATEGORIES);
+                }
+            } else {
+                self.error(Some("Categories"), &format!("\"{}\" is not a registered category, custom ones must start with X-", cat), SPEC_CATEGORIES);
+            }
+        }
+        let mains = cats.iter().filter(|c| MAIN_CATEGORIES.contains(&c.as_str())).count();
+        if !cats.is_empty() && mains == 0 && !cats.iter().any(|c| RESERVED_CATEGORIES.contains(&c.as_str())) {
+            self.warning(Some("Categories"), "no main category, the launcher may not appear in menus", SPEC_CATEGORIES);
+        }
+        if mains > 1 {
+            self.warning(Some("Categories"), "more than one main category, the launcher may appear several times in menus", SPEC_CATEGORIES);
+        }
+    }
+
+    fn environments(&mut self, e: &DesktopEntry) {
+        if !e.only_show_in.is_empty() && !e.not_show_in.is_empty() {
+            self.error(Some("OnlyShowIn"), "OnlyShowIn and NotShowIn must not both be set", SPEC_KEYS);
+        }
+        for (key, list) in [("OnlyShowIn", &e.only_show_in), ("NotShowIn", &e.not_show_in)] {
+            for env in list {
+                if !env.starts_with("X-") && !ENVIRONMENTS.contains(&env.as_str()) {
+                    self.error(Some(key), &format!("\"{}\" is not a registered desktop environment, custom ones must start with X-", env), SPEC_ENVIRONMENTS);
+                }
+            }
+        }
+    }
+
+    fn exec(&mut self, exec: &str) {
+        let (_, issues) = exec::parse(exec);
+        for issue in issues {
+            self.push(issue.severity, Some("Exec"), &issue.message, SPEC_EXEC);
+        }
+        if let (Some(app), Some(fixed)) = (flatpak::exec_deploy_ref(exec), flatpak::exec_for_deploy_path(exec)) {
+            self.warning(
+                Some("Exec"),
+                &format!("runs a file inside the Flatpak deployment of {}, which is replaced on every update; use \"{}\"", app.id, fixed),
+                SPEC_EXEC,
+            );
+        }
+    }
+}
+
+// "Name[fr_FR]" -> ("Name", Some("fr_FR"))
+fn split_locale(key: &str) -> (&str, Option<&str>) {
+    match key.split_once('[') {
+        Some((base, rest)) => (base, Some(rest.strip_suffix(']').unwrap_or(rest))),
+        None => (key, None),
+    }
+}
+
+// lang_COUNTRY.ENCODING@MODIFIER, where only lang is required
+pub fn is_valid_locale(locale: &str) -> bool {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((r, m)) => (r, Some(m)),
+        None => (locale, None),
+    };
+    let (rest, encoding) = match rest.split_once('.') {
+        Some((r, e)) => (r, Some(e)),
+        None => (rest, None),
+    };
+    let (lang, country) = match rest.split_once('_') {
+        Some((l, c)) => (l, Some(c)),
+        None => (rest, None),
+    };
+    let lang_ok = (2..=3).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_lowercase());
+    let country_ok = country.is_none_or(|c| (2..=3).contains(&c.len()) && c.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+    let encoding_ok = encoding.is_none_or(|e| !e.is_empty() && e.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    let modifier_ok = modifier.is_none_or(|m| !m.is_empty() && m.chars().all(|c| c.is_ascii_alphanumeric()));
+    lang_ok && country_ok && encoding_ok && modifier_ok
+}
+
+fn is_true(value: Option<&str>) -> bool {
+    value == Some("true")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::domain::desktop_entry::DesktopEntry;
+    use crate::domain::mime::MimeDatabase;
+    use crate::services::desktop_file;
+
+    // tests/fixtures/validation: every good-*.desktop validates cleanly, every bad-*.desktop
+    // yields exactly the findings of the .expected file next to it (one per line, any order)
+    #[test]
+    fn fixture_corpus() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/validation");
+        let mut paths: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|x| x == "desktop")).collect();
+        paths.sort();
+        assert!(!paths.is_empty(), "no fixtures in {}", dir.display());
+        for path in paths {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let (entry, diagnostics) = desktop_file::parse_with_diagnostics(&fs::read_to_string(&path).unwrap());
+            assert!(diagnostics.is_empty(), "{}: {:?}", name, diagnostics);
+            let mut found: Vec<String> = entry.validate(None).iter().map(|f| f.to_string()).collect();
+            let mut expected: Vec<String> = if name.starts_with("bad-") {
+                let text = fs::read_to_string(path.with_extension("expected")).unwrap();
+                text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect()
+            } else {
+                Vec::new()
+            };
+            found.sort();
+            expected.sort();
+            assert_eq!(found, expected, "{}", name);
+        }
+    }
+
+    // Only an entry that cannot work as a launcher is refused; other spec errors are reported
+    #[test]
+    fn only_structural_errors_block_saving() {
+        let entry = DesktopEntry {
+            type_field: "Application".into(),
+            name: "Files".into(),
+            exec: "files %U".into(),
+            only_show_in: vec!["COSMIC".into()],
+            extra: vec![("Unknown".into(), "1".into())],
+            ..Default::default()
+        };
+        assert_eq!(entry.check(), Ok(()));
+        assert_eq!(entry.conformance_errors().len(), 2);
+        let unnamed = DesktopEntry { name: String::new(), ..entry.clone() };
+        assert_eq!(unnamed.check(), Err("Name: required key is missing".to_string()));
+        let unquoted = DesktopEntry { exec: "files >out".into(), ..entry };
+        assert!(unquoted.check().unwrap_err().starts_with("Exec: "));
+    }
+
+    // MimeType= is only checked against a database when the caller passes one
+    #[test]
+    fn unknown_mime_types_need_a_database() {
+        let mut db = MimeDatabase::default();
+        db.add_globs2("50:text/markdown:*.md\n50:text/plain:*.txt\n");
+        let entry = DesktopEntry {
+            type_field: "Application".into(),
+            name: "Notes".into(),
+            exec: "notes %f".into(),
+            mime_type: vec!["text/markdown".into(), "text/markdwn".into(), "x-scheme-handler/notes".into()],
+            ..Default::default()
+        };
+        assert!(entry.validate(None).is_empty());
+        let found: Vec<String> = entry.validate(Some(&db)).iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            found,
+            ["warning: [Desktop Entry] MimeType: \"text/markdwn\" is not in the shared MIME database, did you mean text/markdown? (Shared MIME-info Database Spec)"]
+        );
+    }
+}
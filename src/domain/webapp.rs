@@ -0,0 +1,138 @@
+// Browser app windows: a site in its own window without tabs or toolbar, opened by a
+// Chromium-based browser with --app=<url>. --class names the window so the desktop can
+// match it to the launcher through StartupWMClass. The launchers Chrome and Chromium write
+// for web apps installed from the browser (chrome-<app id>-Default.desktop) use --app-id.
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::exec;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebApp {
+    pub browser: String,         // browser command line, without field codes
+    pub url: String,             // --app=
+    pub app_id: Option<String>,  // --app-id= of a web app installed from the browser
+    pub profile: Option<String>, // --user-data-dir=, a profile of its own
+    pub class: Option<String>,   // --class= and StartupWMClass; see default_class()
+    pub args: Vec<String>,       // other browser arguments, as written in Exec
+}
+
+// Program names and Flatpak IDs of browsers that understand --app
+const CHROMIUM_NAMES: &[&str] = &["chrom", "brave", "vivaldi", "edge", "opera", "thorium", "yandex"];
+
+const FILE_ARGS: &[&str] = &["%f", "%F", "%u", "%U", "@@", "@@u"];
+
+impl WebApp {
+    // A web app launcher: Type=Application running a browser with --app or --app-id
+    pub fn from_entry(entry: &DesktopEntry) -> Option<WebApp> {
+        if entry.type_field != "Application" {
+            return None;
+        }
+        let mut app = WebApp::from_exec(&entry.exec)?;
+        if app.class.is_none() {
+            app.class = entry.extra_value("StartupWMClass").filter(|c| !c.is_empty()).map(str::to_string);
+        }
+        Some(app)
+    }
+
+    pub fn from_exec(exec_line: &str) -> Option<WebApp> {
+        let raw = exec::raw_args(exec_line);
+        let browser_len = browser_len(&raw);
+        let mut app = WebApp { browser: raw[..browser_len].join(" "), ..Default::default() };
+        let mut found = false;
+        for arg in &raw[browser_len..] {
+            if FILE_ARGS.contains(arg) {
+                continue;
+            }
+            let value = arg_text(arg);
+            if let Some(url) = value.strip_prefix("--app=") {
+                app.url = url.to_string();
+                found = true;
+            } else if let Some(id) = value.strip_prefix("--app-id=") {
+                app.app_id = Some(id.to_string());
+                found = true;
+            } else if let Some(dir) = value.strip_prefix("--user-data-dir=") {
+                app.profile = Some(dir.to_string());
+            } else if let Some(class) = value.strip_prefix("--class=") {
+                app.class = Some(class.to_string());
+            } else {
+                app.args.push(arg.to_string());
+            }
+        }
+        found.then_some(app)
+    }
+
+    // Chromium names installed web apps' windows crx_<app id>; for sites the class is made
+    // from the address ("webapp-dashboard.example.com-status")
+    pub fn default_class(&self) -> String {
+        if let Some(id) = &self.app_id {
+            return format!("crx_{}", id);
+        }
+        let rest = self.url.split_once("://").map_or(self.url.as_str(), |(_, r)| r);
+        let rest = rest.split(['?', '#']).next().unwrap_or("");
+        let slug: String = rest
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        if slug.is_empty() { "webapp".into() } else { format!("webapp-{}", slug) }
+    }
+
+    pub fn class_or_default(&self) -> String {
+        self.class.clone().filter(|c| !c.trim().is_empty()).unwrap_or_else(|| self.default_class())
+    }
+
+    pub fn exec(&self) -> String {
+        let flag = |name: &str, value: &str| exec::quote(&format!("--{}={}", name, value.replace('%', "%%")));
+        let mut parts: Vec<String> = vec![self.browser.clone()];
+        parts.extend(self.args.iter().cloned());
+        if let Some(profile) = self.profile.as_deref().filter(|p| !p.is_empty()) {
+            parts.push(flag("user-data-dir", profile));
+        }
+        match self.app_id.as_deref().filter(|id| !id.is_empty()) {
+            // Chromium picks the class of installed apps itself
+            Some(id) => parts.push(flag("app-id", id)),
+            None => {
+                parts.push(flag("app", &self.url));
+                parts.push(flag("class", &self.class_or_default()));
+            }
+        }
+        parts.retain(|p| !p.is_empty());
+        parts.join(" ")
+    }
+
+    // Exec and the matching StartupWMClass
+    pub fn apply(&self, entry: &mut DesktopEntry) {
+        entry.exec = self.exec();
+        entry.set_extra("StartupWMClass", Some(&self.class_or_default()));
+    }
+}
+
+// Whether a browser's Exec runs a Chromium-based browser (natively or as a Flatpak)
+pub fn is_chromium_based(exec_line: &str) -> bool {
+    let raw = exec::raw_args(exec_line);
+    raw[..browser_len(&raw)].iter().any(|a| {
+        let text = arg_text(a).to_lowercase();
+        let name = text.rsplit('/').next().unwrap_or("");
+        !name.starts_with('-') && name != "flatpak" && CHROMIUM_NAMES.iter().any(|n| name.contains(n))
+    })
+}
+
+// A browser's Exec without its file and URL arguments, ready for more options
+pub fn browser_command(exec_line: &str) -> String {
+    exec::raw_args(exec_line).into_iter().filter(|a| !FILE_ARGS.contains(a)).collect::<Vec<_>>().join(" ")
+}
+
+// Leading arguments that start the browser: the program, or for `flatpak run` everything
+// up to the app ID
+fn browser_len(raw: &[&str]) -> usize {
+    let Some(first) = raw.first() else { return 0 };
+    if arg_text(first).rsplit('/').next() == Some("flatpak") && raw.get(1) == Some(&"run") {
+        let options = raw[2..].iter().take_while(|a| a.starts_with('-')).count();
+        (2 + options + 1).min(raw.len())
+    } else {
+        1
+    }
+}
+
+fn arg_text(raw: &str) -> String {
+    exec::parse(raw).0.args.first().map(|a| a.text()).unwrap_or_default()
+}
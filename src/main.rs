@@ -1,8 +1,14 @@
 mod app;
+mod cli;
 mod domain;
 mod services;
 mod ui;
 
 fn main() {
+    // A subcommand runs headless and never touches GTK
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
     app::run();
 }
\ No newline at end of file
@@ -0,0 +1,157 @@
+// AppImage import: a type-2 AppImage is an ELF runtime followed by a SquashFS image whose
+// root holds the app's .desktop file and .DirIcon. The launcher is rebuilt around the
+// AppImage itself and the icon is installed into the user's hicolor theme.
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::exec;
+use crate::services::desktop_file;
+use crate::services::icons::{self, Icons};
+use crate::services::squashfs::{Kind, SquashFs};
+
+pub struct Imported {
+    pub entry: DesktopEntry,
+    pub icons: Vec<PathBuf>, // installed icon files
+    pub notes: Vec<String>,    // things the user should know about the result
+}
+
+pub struct AppImage;
+
+impl AppImage {
+    pub fn import(path: &Path) -> Result<Imported> {
+        let path = fs::canonicalize(path).with_context(|| format!("Reading {}", path.display()))?;
+        let mut notes = Vec::new();
+        let mut fs = SquashFs::open(&path, squashfs_offset(&path)?)?;
+
+        let mut desktops: Vec<String> = fs
+            .read_dir("/")?
+            .into_iter()
+            .filter(|e| e.name.ends_with(".desktop") && matches!(e.kind, Kind::File | Kind::Symlink))
+            .map(|e| e.name)
+            .collect();
+        desktops.sort();
+        let Some(desktop_name) = desktops.first().cloned() else { bail!("The AppImage has no .desktop file at its root") };
+        if desktops.len() > 1 {
+            notes.push(format!("Several .desktop files found, used {}", desktop_name));
+        }
+        let content = fs.read_file(&desktop_name)?;
+        let (mut entry, diagnostics) = desktop_file::parse_with_diagnostics(&String::from_utf8_lossy(&content));
+        if let Some(d) = diagnostics.iter().find(|d| d.severity == desktop_file::Severity::Error) {
+            notes.push(format!("{} has problems ({}), check the result", desktop_name, d));
+        }
+
+        // Run the AppImage instead of the program inside it, with the same arguments
+        let appimage = path.to_string_lossy().into_owned();
+        entry.exec = if entry.exec.trim().is_empty() { exec::quote(&appimage.replace('%', "%%")) } else { exec::replace_program(&entry.exec, &appimage) };
+        for action in &mut entry.desktop_actions {
+            if let Some(e) = action.exec.as_mut().filter(|e| !e.trim().is_empty()) {
+                *e = exec::replace_program(e, &appimage);
+            }
+        }
+        entry.try_exec = Some(appimage.clone());
+        entry.type_field = "Application".into();
+
+        let stem = desktop_name.trim_end_matches(".desktop").to_string();
+        let icon_name = entry.icon.clone().filter(|i| !i.trim().is_empty() && !i.contains('/')).unwrap_or(stem);
+        let icons = match find_icon(&mut fs, &icon_name) {
+            Some(data) => match Icons::install_data(&icon_name, &data) {
+                Ok(installed) => {
+                    entry.set_extra(icons::KEY_ICON, Some(&icon_name));
+                    entry.icon = Some(icon_name);
+                    installed
+                }
+                Err(e) => {
+                    notes.push(format!("Icon not installed: {:#}", e));
+                    Vec::new()
+                }
+            },
+            None => {
+                notes.push("No icon found in the AppImage".into());
+                Vec::new()
+            }
+        };
+
+        if make_executable(&path)? {
+            notes.push(format!("{} was made executable", path.display()));
+        }
+        Ok(Imported { entry, icons, notes })
+    }
+}
+
+// Where the SquashFS image starts: right after the ELF runtime, i.e. at the end of its
+// section header table
+pub fn squashfs_offset(path: &Path) -> Result<u64> {
+    let mut header = [0u8; 64];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .with_context(|| format!("Reading {}", path.display()))?;
+    if &header[..4] != b"\x7fELF" {
+        bail!("{} is not an AppImage (no ELF header)", path.display());
+    }
+    if &header[8..11] == b"AI\x01" {
+        bail!("Type 1 AppImages (ISO 9660) are not supported, only type 2");
+    }
+    let little = header[5] == 1;
+    let u16_at = |i: usize| {
+        let b = [header[i], header[i + 1]];
+        if little { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) }
+    };
+    let u64_at = |i: usize, len: usize| {
+        let mut b = [0u8; 8];
+        let src = &header[i..i + len];
+        if little {
+            b[..len].copy_from_slice(src);
+            u64::from_le_bytes(b)
+        } else {
+            b[8 - len..].copy_from_slice(src);
+            u64::from_be_bytes(b)
+        }
+    };
+    // e_shoff, e_shentsize and e_shnum sit at different places in 32- and 64-bit ELF
+    let (shoff, shentsize, shnum) = match header[4] {
+        1 => (u64_at(32, 4), u16_at(46), u16_at(48)),
+        2 => (u64_at(40, 8), u16_at(58), u16_at(60)),
+        other => bail!("Unknown ELF class {}", other),
+    };
+    // The section headers end where the filesystem starts; a crafted header can overflow
+    let offset = shoff
+        .checked_add(shentsize as u64 * shnum as u64)
+        .ok_or_else(|| anyhow!("{} has an invalid ELF section header table", path.display()))?;
+    if offset == 0 {
+        return Err(anyhow!("{} has no embedded filesystem", path.display()));
+    }
+    Ok(offset)
+}
+
+// .DirIcon first (what file managers show), then <icon>.png/.svg/.xpm at the root, then
+// the hicolor theme inside the image
+fn find_icon(fs: &mut SquashFs, name: &str) -> Option<Vec<u8>> {
+    let mut candidates = vec![".DirIcon".to_string()];
+    for ext in ["png", "svg", "xpm"] {
+        candidates.push(format!("{}.{}", name, ext));
+    }
+    candidates.push(format!("usr/share/icons/hicolor/scalable/apps/{}.svg", name));
+    for size in icons::HICOLOR_SIZES.iter().rev() {
+        candidates.push(format!("usr/share/icons/hicolor/{0}x{0}/apps/{1}.png", size, name));
+    }
+    candidates
+        .iter()
+        .filter_map(|c| fs.read_file(c).ok())
+        .find(|data| icons::detect_format(data).is_some())
+}
+
+// Returns whether the mode had to change
+fn make_executable(path: &Path) -> Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = fs::metadata(path)?.permissions();
+    if perms.mode() & 0o111 != 0 {
+        return Ok(false);
+    }
+    // Executable for whoever can read it, like chmod +x under the default umask
+    perms.set_mode(perms.mode() | (perms.mode() & 0o444) >> 2);
+    fs::set_permissions(path, perms).with_context(|| format!("Making {} executable", path.display()))?;
+    Ok(true)
+}
@@ -0,0 +1,203 @@
+// Login-time autostart (Desktop Application Autostart Specification): .desktop files in
+// $XDG_CONFIG_HOME/autostart and $XDG_CONFIG_DIRS/*/autostart. A user file hides the
+// system file with the same name, which is how a system entry gets disabled.
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::desktop_writer::DesktopWriter;
+use crate::services::discovery::{config_dirs, config_home, Source};
+use crate::services::launcher::find_program;
+
+pub const KEY_HIDDEN: &str = "Hidden";
+pub const KEY_GNOME_ENABLED: &str = "X-GNOME-Autostart-enabled";
+pub const KEY_GNOME_DELAY: &str = "X-GNOME-Autostart-Delay";
+pub const KEY_GNOME_PHASE: &str = "X-GNOME-Autostart-Phase";
+pub const KEY_CONDITION: &str = "AutostartCondition";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State {
+    Enabled,
+    Disabled(String), // why the session will not start it
+}
+
+#[derive(Debug, Clone)]
+pub struct AutostartItem {
+    pub file_name: String,
+    pub path: PathBuf,
+    pub source: Source, // User or System
+    pub entry: Option<DesktopEntry>, // None when the file does not parse
+    pub overridden_by: Option<PathBuf>,
+    pub state: State,
+}
+
+pub fn user_autostart_dir() -> Option<PathBuf> {
+    config_home().map(|d| d.join("autostart"))
+}
+
+pub struct Autostart;
+
+impl Autostart {
+    // User entries first, then system ones; system entries hidden by a user file are kept
+    // with `overridden_by` set so the view can explain them
+    pub fn list() -> Vec<AutostartItem> {
+        let mut dirs: Vec<(PathBuf, Source)> = user_autostart_dir().map(|d| (d, Source::User)).into_iter().collect();
+        dirs.extend(config_dirs().into_iter().map(|d| (d.join("autostart"), Source::System)));
+
+        let mut items: Vec<AutostartItem> = Vec::new();
+        let mut seen_dirs = HashSet::new();
+        for (dir, source) in dirs {
+            if !seen_dirs.insert(dir.clone()) {
+                continue;
+            }
+            let Ok(read) = fs::read_dir(&dir) else { continue };
+            let mut paths: Vec<PathBuf> = read
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|e| e == "desktop").unwrap_or(false))
+                .collect();
+            paths.sort();
+            for path in paths {
+                let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                let overridden_by = items.iter().find(|i| i.file_name == file_name).map(|i| i.path.clone());
+                let entry = DesktopReader::read_from_path(&path).ok();
+                let state = match (&overridden_by, &entry) {
+                    (Some(winner), _) => State::Disabled(format!("replaced by {}", winner.display())),
+                    (None, Some(e)) => effective_state(e),
+                    (None, None) => State::Disabled("file does not parse".into()),
+                };
+                items.push(AutostartItem { file_name, path, source, entry, overridden_by, state });
+            }
+        }
+        items
+    }
+
+    // Start a launcher at login: a copy of it in the user autostart dir
+    pub fn add(launcher: &Path) -> Result<PathBuf> {
+        let entry = DesktopReader::read_from_path(launcher)?;
+        let stem = launcher.file_stem().map(|s| s.to_string_lossy().into_owned()).ok_or_else(|| anyhow!("Invalid file name: {}", launcher.display()))?;
+        let dir = user_autostart_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        if dir.join(format!("{}.desktop", stem)).exists() {
+            bail!("{} already starts at login", stem);
+        }
+        DesktopWriter::write_in(&entry, &dir, &stem, false)
+    }
+
+    // Change an autostart entry. A system entry is first copied to the user dir, and the
+    // copy is what gets edited, so the system file is never touched. The copy is only made
+    // once the change is known to be savable, and removed again if writing it fails: a
+    // stray copy would hide the system entry.
+    pub fn update(path: &Path, change: impl FnOnce(&mut DesktopEntry)) -> Result<PathBuf> {
+        let target = Self::user_path(path)?;
+        let copied = !target.exists();
+        let mut entry = DesktopReader::read_from_path(if copied { path } else { &target })?;
+        change(&mut entry);
+        entry.check().map_err(|e| anyhow!(e))?;
+        if copied {
+            Self::copy_to_user_dir(path, &target)?;
+        }
+        DesktopWriter::write_to_path(&entry, &target).inspect_err(|_| {
+            if copied {
+                let _ = fs::remove_file(&target);
+            }
+        })
+    }
+
+    pub fn set_hidden(path: &Path, hidden: bool) -> Result<PathBuf> {
+        Self::update(path, |e| e.set_extra(KEY_HIDDEN, hidden.then_some("true")))
+    }
+
+    pub fn set_gnome_enabled(path: &Path, enabled: bool) -> Result<PathBuf> {
+        Self::update(path, |e| e.set_extra(KEY_GNOME_ENABLED, Some(if enabled { "true" } else { "false" })))
+    }
+
+    // Delete a user entry; for an override this brings the system entry back
+    pub fn remove(path: &Path) -> Result<()> {
+        let user_dir = user_autostart_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        if !path.starts_with(&user_dir) {
+            bail!("{} is a system entry, disable it instead", path.display());
+        }
+        fs::remove_file(path).with_context(|| format!("Deleting {}", path.display()))
+    }
+
+    // Where the user's version of the entry at `path` lives: the file itself for user
+    // entries, a file of the same name in the user dir for system ones
+    fn user_path(path: &Path) -> Result<PathBuf> {
+        let user_dir = user_autostart_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        if path.starts_with(&user_dir) {
+            return Ok(path.to_path_buf());
+        }
+        let name = path.file_name().ok_or_else(|| anyhow!("Invalid file name: {}", path.display()))?;
+        Ok(user_dir.join(name))
+    }
+
+    // Copied as is first, so the save keeps the system file's layout
+    fn copy_to_user_dir(path: &Path, dest: &Path) -> Result<()> {
+        if let Some(dir) = dest.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Creating directory {}", dir.display()))?;
+        }
+        fs::copy(path, dest).with_context(|| format!("Copying {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(dest, fs::Permissions::from_mode(0o644))?;
+        }
+        Ok(())
+    }
+}
+
+// Whether the session would start this entry, following the checks GNOME and KDE apply
+pub fn effective_state(entry: &DesktopEntry) -> State {
+    if entry.extra_value(KEY_HIDDEN) == Some("true") {
+        return State::Disabled("Hidden=true".into());
+    }
+    if entry.extra_value(KEY_GNOME_ENABLED) == Some("false") {
+        return State::Disabled("disabled (X-GNOME-Autostart-enabled=false)".into());
+    }
+    let desktops: Vec<String> = env::var("XDG_CURRENT_DESKTOP")
+        .map(|v| v.split(':').filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    if !entry.only_show_in.is_empty() && !desktops.iter().any(|d| entry.only_show_in.contains(d)) {
+        return State::Disabled(format!("only for {}", entry.only_show_in.join(", ")));
+    }
+    if let Some(d) = desktops.iter().find(|d| entry.not_show_in.contains(d)) {
+        return State::Disabled(format!("not for {}", d));
+    }
+    if let Some(try_exec) = entry.try_exec.as_deref().map(str::trim).filter(|t| !t.is_empty())
+        && find_program(try_exec).is_none()
+    {
+        return State::Disabled(format!("{} is not installed", try_exec));
+    }
+    State::Enabled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_env;
+
+    const ENTRY: &str = "[Desktop Entry]\n# shipped by the distribution\nType=Application\nName=Agent\nExec=agent\n";
+
+    #[test]
+    fn system_entries_are_overridden_by_a_user_copy() {
+        let env = test_env::isolated("autostart-copy");
+        let system = env.write("system-config/autostart/agent.desktop", ENTRY);
+        let copy = Autostart::set_hidden(&system, true).unwrap();
+        assert_eq!(copy, env.config_home().join("autostart/agent.desktop"));
+        let text = fs::read_to_string(&copy).unwrap();
+        assert!(text.contains("# shipped by the distribution\n") && text.contains("Hidden=true\n"), "{}", text);
+        assert_eq!(fs::read_to_string(&system).unwrap(), ENTRY);
+    }
+
+    #[test]
+    fn a_failed_change_leaves_no_user_copy() {
+        let env = test_env::isolated("autostart-failed");
+        let system = env.write("system-config/autostart/agent.desktop", ENTRY);
+        assert!(Autostart::update(&system, |e| e.name.clear()).is_err());
+        assert!(!env.config_home().join("autostart/agent.desktop").exists());
+    }
+}
@@ -0,0 +1,38 @@
+// Browsers that can open web app windows: installed launchers that handle https links
+// (MimeType=x-scheme-handler/https) and run a Chromium-based browser.
+use std::collections::HashSet;
+
+use crate::domain::webapp::{self, WebApp};
+use crate::services::desktop_reader::DesktopReader;
+
+#[derive(Debug, Clone)]
+pub struct Browser {
+    pub name: String,
+    pub command: String, // Exec without field codes
+}
+
+pub struct Browsers;
+
+impl Browsers {
+    // One per command, in launcher precedence order; hidden launchers and web apps that
+    // happen to handle https are skipped
+    pub fn detect() -> Vec<Browser> {
+        let mut seen = HashSet::new();
+        let mut browsers = Vec::new();
+        for d in DesktopReader::list_desktop_files().into_iter().filter(|d| d.shadowed_by.is_none()) {
+            let Ok(entry) = DesktopReader::read_from_path(&d.path) else { continue };
+            let handles_https = entry.mime_type.iter().any(|m| m == "x-scheme-handler/https");
+            if !handles_https || entry.extra_value("Hidden") == Some("true") || WebApp::from_exec(&entry.exec).is_some() {
+                continue;
+            }
+            if !webapp::is_chromium_based(&entry.exec) {
+                continue;
+            }
+            let command = webapp::browser_command(&entry.exec);
+            if seen.insert(command.clone()) {
+                browsers.push(Browser { name: entry.name, command });
+            }
+        }
+        browsers
+    }
+}
@@ -0,0 +1,98 @@
+// Launchers placed on the desktop. File managers only run a .desktop file from the desktop
+// when it is executable and, on GNOME, marked trusted through GIO metadata. Each copy names
+// its original in X-LauncherStudio-Source so saving the original can refresh it.
+use anyhow::{anyhow, bail, Context, Result};
+use gio::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::desktop_writer::DesktopWriter;
+use crate::services::discovery::config_home;
+
+pub const KEY_SOURCE: &str = "X-LauncherStudio-Source";
+
+pub struct Placed {
+    pub path: PathBuf,
+    pub trusted: Result<(), String>, // metadata::trusted is not supported everywhere
+}
+
+// XDG_DESKTOP_DIR from user-dirs.dirs (xdg-user-dirs), falling back to ~/Desktop
+pub fn desktop_dir() -> Option<PathBuf> {
+    let home = directories::BaseDirs::new()?.home_dir().to_path_buf();
+    let configured = config_home()
+        .and_then(|c| fs::read_to_string(c.join("user-dirs.dirs")).ok())
+        .and_then(|content| user_dir(&content, "XDG_DESKTOP_DIR", &home));
+    Some(configured.unwrap_or_else(|| home.join("Desktop")))
+}
+
+// Lines look like XDG_DESKTOP_DIR="$HOME/Desktop"; the value is either relative to
+// $HOME or absolute
+fn user_dir(content: &str, key: &str, home: &Path) -> Option<PathBuf> {
+    content.lines().map(str::trim).filter(|l| !l.starts_with('#')).find_map(|line| {
+        let value = line.strip_prefix(key)?.trim_start().strip_prefix('=')?.trim();
+        let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value).replace("\\\"", "\"");
+        if let Some(rest) = value.strip_prefix("$HOME") {
+            Some(home.join(rest.trim_start_matches('/')))
+        } else if value.starts_with('/') {
+            Some(PathBuf::from(value))
+        } else {
+            None
+        }
+    })
+}
+
+pub struct DesktopCopies;
+
+impl DesktopCopies {
+    // Write an executable, trusted copy of `entry` (saved at `source`) on the desktop
+    pub fn place(entry: &DesktopEntry, source: &Path) -> Result<Placed> {
+        let dir = desktop_dir().ok_or_else(|| anyhow!("Failed to resolve the desktop directory"))?;
+        let name = source.file_name().ok_or_else(|| anyhow!("Invalid file name: {}", source.display()))?;
+        let dest = dir.join(name);
+        if dest.exists() && !Self::copies_of(source).contains(&dest) {
+            bail!("{} already exists and is not a copy of this launcher", dest.display());
+        }
+        Self::write_copy(entry, source, &dest)
+    }
+
+    // Desktop files whose X-LauncherStudio-Source is `source`
+    pub fn copies_of(source: &Path) -> Vec<PathBuf> {
+        let Some(dir) = desktop_dir() else { return Vec::new() };
+        let Ok(read) = fs::read_dir(&dir) else { return Vec::new() };
+        let source = source.to_string_lossy();
+        let mut copies: Vec<PathBuf> = read
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|e| e == "desktop").unwrap_or(false))
+            .filter(|p| DesktopReader::read_from_path(p).is_ok_and(|de| de.extra_value(KEY_SOURCE) == Some(source.as_ref())))
+            .collect();
+        copies.sort();
+        copies
+    }
+
+    // Rewrite every desktop copy of `source` after the original was saved; returns how many
+    pub fn sync(entry: &DesktopEntry, source: &Path) -> Result<usize> {
+        let copies = Self::copies_of(source);
+        for dest in &copies {
+            Self::write_copy(entry, source, dest)?;
+        }
+        Ok(copies.len())
+    }
+
+    fn write_copy(entry: &DesktopEntry, source: &Path, dest: &Path) -> Result<Placed> {
+        let mut copy = entry.clone();
+        copy.set_extra(KEY_SOURCE, Some(&source.to_string_lossy()));
+        DesktopWriter::write_to_path_with_mode(&copy, dest, 0o755).with_context(|| format!("Placing {}", dest.display()))?;
+        let trusted = trust(dest);
+        Ok(Placed { path: dest.to_path_buf(), trusted })
+    }
+}
+
+// GNOME (Nautilus, desktop icons extensions) reads this GVfs attribute
+fn trust(path: &Path) -> Result<(), String> {
+    gio::File::for_path(path)
+        .set_attribute_string("metadata::trusted", "true", gio::FileQueryInfoFlags::NONE, gio::Cancellable::NONE)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,456 @@
+// The single parser/serializer for .desktop content. DesktopReader, DesktopWriter and the
+// editor's Source tab all go through here so a file reads the same everywhere.
+use std::fmt;
+
+use crate::domain::desktop_action::DesktopAction;
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::escape::{split_list, unescape_string};
+use crate::domain::mime::MimeDatabase;
+pub use crate::domain::validation::Severity;
+use crate::domain::validation::Finding;
+use crate::services::keyfile::{KeyFile, LineView};
+
+// A problem found while reading, located in the source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,   // 1-based
+    pub column: usize, // 1-based, in characters
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity { Severity::Error => "error", Severity::Warning => "warning" };
+        match &self.key {
+            Some(key) => write!(f, "{}:{}: {}: {}: {}", self.line, self.column, level, key, self.message),
+            None => write!(f, "{}:{}: {}: {}", self.line, self.column, level, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,         // 1-based
+    pub key: Option<String>, // offending key, if the problem is tied to one
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "line {}: {}: {}", self.line, key, self.reason),
+            None => write!(f, "line {}: {}", self.line, self.reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Strict parse: fails on the first error-level diagnostic. For checking content on purpose
+// (the CLI, checking a changed file); reading launchers goes through
+// parse_with_diagnostics so a flawed file still opens.
+pub fn parse(content: &str) -> Result<DesktopEntry, ParseError> {
+    let (entry, diagnostics) = parse_with_diagnostics(content);
+    match diagnostics.into_iter().find(|d| d.severity == Severity::Error) {
+        Some(d) => Err(ParseError { line: d.line, key: d.key, reason: d.message }),
+        None => Ok(entry),
+    }
+}
+
+// Lenient parse: everything readable, plus every problem found along the way
+pub fn parse_with_diagnostics(content: &str) -> (DesktopEntry, Vec<Diagnostic>) {
+    let doc = KeyFile::parse(content);
+    let mut diagnostics = structure_diagnostics(&doc);
+    let (entry, mut value_diagnostics) = build(&doc);
+    diagnostics.append(&mut value_diagnostics);
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    (entry, diagnostics)
+}
+
+// Findings of DesktopEntry::validate, placed on the line of the key they are about (or the
+// group header). Kept apart from parse diagnostics: an invalid file still opens.
+pub fn validation_diagnostics(content: &str, entry: &DesktopEntry, mime_db: Option<&MimeDatabase>) -> Vec<Diagnostic> {
+    finding_diagnostics(content, entry.validate(mime_db))
+}
+
+// Place findings of other checks (e.g. icon_theme::check_icons) the same way
+pub fn finding_diagnostics(content: &str, findings: Vec<Finding>) -> Vec<Diagnostic> {
+    let doc = KeyFile::parse(content);
+    let views = doc.line_views();
+    findings
+        .into_iter()
+        .map(|f| {
+            let (line, column) = locate(&views, &f.group, f.key.as_deref());
+            Diagnostic { severity: f.severity, line, column, key: f.key, message: format!("{} ({})", f.message, f.spec) }
+        })
+        .collect()
+}
+
+// Line and column of `key` in `group`, falling back to the group header, then line 1
+fn locate(views: &[(usize, LineView<'_>)], group: &str, key: Option<&str>) -> (usize, usize) {
+    let mut current: Option<&str> = None;
+    let mut header = None;
+    for (line, view) in views {
+        match view {
+            LineView::Group(name) => {
+                current = Some(name);
+                if *name == group && header.is_none() {
+                    header = Some((*line, 1));
+                }
+            }
+            LineView::Entry { key: k, column, .. } if current == Some(group) && Some(*k) == key => {
+                return (*line, *column);
+            }
+            _ => {}
+        }
+    }
+    header.unwrap_or((1, 1))
+}
+
+// Serialize `entry`. With the previous content of the file, that content is used as the
+// template so its comments, key order and unknown groups survive; only keys whose value
+// changed are rewritten. Lines of it no reader can use are dropped, otherwise they would
+// survive the fix.
+pub fn serialize(entry: &DesktopEntry, existing: Option<&str>) -> String {
+    match existing {
+        Some(content) => {
+            let mut doc = KeyFile::parse(content);
+            drop_unreadable_lines(&mut doc);
+            merge_entry(&mut doc, entry);
+            doc.to_text()
+        }
+        None => entry.to_ini_string(),
+    }
+}
+
+// Remove what structure_diagnostics reports as errors and readers skip: stray text, keys
+// before the first group or with a malformed locale, and repeated groups with their keys.
+// Returns the 1-based numbers of the removed lines.
+pub fn drop_unreadable_lines(doc: &mut KeyFile) -> Vec<usize> {
+    let mut dropped = Vec::new();
+    let mut groups_seen: Vec<&str> = Vec::new();
+    let mut in_group = false;
+    let mut in_repeated_group = false;
+    for (line, view) in doc.line_views() {
+        let unreadable = match view {
+            LineView::Blank | LineView::Comment => in_repeated_group,
+            LineView::Group(name) => {
+                in_group = true;
+                in_repeated_group = groups_seen.contains(&name);
+                groups_seen.push(name);
+                in_repeated_group
+            }
+            LineView::Invalid(_) => true,
+            LineView::Entry { key, .. } => !in_group || in_repeated_group || malformed_locale(key).is_some(),
+        };
+        if unreadable {
+            dropped.push(line);
+        }
+    }
+    doc.remove_lines(&dropped);
+    dropped
+}
+
+pub fn merge_entry(doc: &mut KeyFile, entry: &DesktopEntry) {
+    // A broken file still yields its readable keys, which is all merging needs
+    let before = build(doc).0.to_groups();
+    let after = entry.to_groups();
+    for (group, keys) in &after {
+        let old_keys: &[(String, String)] = before.iter().find(|(g, _)| g == group).map(|(_, k)| k.as_slice()).unwrap_or(&[]);
+        for (k, v) in keys {
+            // Same value as what the file already means: keep the original line (and its spelling)
+            if !old_keys.iter().any(|(ok, ov)| ok == k && ov == v) {
+                doc.set(group, k, v);
+            }
+        }
+        for (k, _) in old_keys {
+            if !keys.iter().any(|(nk, _)| nk == k) {
+                doc.remove(group, k);
+            }
+        }
+    }
+    for (group, _) in &before {
+        if group.starts_with("Desktop Action ") && !after.iter().any(|(g, _)| g == group) {
+            doc.remove_group(group);
+        }
+    }
+}
+
+// Problems in the file layout itself, independent of what the keys mean
+fn structure_diagnostics(doc: &KeyFile) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let mut group: Option<&str> = None;
+    let mut groups_seen: Vec<&str> = Vec::new();
+    let mut keys_seen: Vec<&str> = Vec::new();
+    for (line, view) in doc.line_views() {
+        match view {
+            LineView::Blank | LineView::Comment => {}
+            LineView::Group(name) => {
+                if groups_seen.contains(&name) {
+                    out.push(error(line, 1, None, format!("duplicate group [{}]", name)));
+                }
+                groups_seen.push(name);
+                group = Some(name);
+                keys_seen.clear();
+            }
+            LineView::Invalid(text) => {
+                let column = text.chars().take_while(|c| c.is_whitespace()).count() + 1;
+                let message = if text.trim_start().starts_with('[') {
+                    "malformed group header".to_string()
+                } else {
+                    "line is not a group header, comment or key=value pair".to_string()
+                };
+                out.push(error(line, column, None, message));
+            }
+            LineView::Entry { key, column, .. } => {
+                if group.is_none() {
+                    out.push(error(line, column, Some(key), "key before the first group header".into()));
+                }
+                if keys_seen.contains(&key) {
+                    out.push(Diagnostic {
+                        severity: Severity::Warning,
+                        line,
+                        column,
+                        key: Some(key.to_string()),
+                        message: "duplicate key, only the last value is used".into(),
+                    });
+                }
+                keys_seen.push(key);
+                if let Some(offset) = malformed_locale(key) {
+                    out.push(error(line, column + offset, Some(key), "malformed locale suffix, expected Key[locale]".into()));
+                }
+            }
+        }
+    }
+    out
+}
+
+// Character offset of the problem in a `Key[locale]` key, if the suffix is broken
+fn malformed_locale(key: &str) -> Option<usize> {
+    let open = key.find('[');
+    let close = key.find(']');
+    match (open, close) {
+        (None, None) => None,
+        (Some(o), Some(c)) if c == key.len() - 1 && c > o + 1 && !key[o + 1..c].contains('[') => None,
+        (Some(o), _) => Some(key[..o].chars().count()),
+        (None, Some(c)) => Some(key[..c].chars().count()),
+    }
+}
+
+fn error(line: usize, column: usize, key: Option<&str>, message: String) -> Diagnostic {
+    Diagnostic { severity: Severity::Error, line, column, key: key.map(str::to_string), message }
+}
+
+// Build the entry from [Desktop Entry] and [Desktop Action *]; other groups stay in the
+// document only. Returns the entry read so far together with the problems in key values.
+fn build(doc: &KeyFile) -> (DesktopEntry, Vec<Diagnostic>) {
+    let mut entry = DesktopEntry::default();
+    let mut diagnostics = Vec::new();
+    let mut seen: Vec<&str> = Vec::new();
+    for group in doc.groups() {
+        if seen.contains(&group) { continue; }
+        seen.push(group);
+        if group == "Desktop Entry" {
+            for (line, k, v) in doc.numbered_entries(group) {
+                if let Some(message) = apply_entry_key(&mut entry, k, v) {
+                    let column = k.chars().count() + 2;
+                    diagnostics.push(Diagnostic { severity: Severity::Warning, line, column, key: Some(k.to_string()), message });
+                }
+            }
+        } else if let Some(id) = group.strip_prefix("Desktop Action ") {
+            let mut action = DesktopAction { id: id.to_string(), ..Default::default() };
+            for (_, k, v) in doc.numbered_entries(group) {
+                apply_action_key(&mut action, k, v);
+            }
+            entry.desktop_actions.push(action);
+        }
+    }
+    if !seen.contains(&"Desktop Entry") {
+        diagnostics.push(error(1, 1, None, "missing [Desktop Entry] group".into()));
+    }
+    if entry.type_field.is_empty() { entry.type_field = "Application".into(); }
+    (entry, diagnostics)
+}
+
+// Returns a warning when the value had to be guessed
+fn apply_entry_key(entry: &mut DesktopEntry, key: &str, raw: &str) -> Option<String> {
+    let val = unescape_string(raw);
+    match key {
+        "Type" => entry.type_field = val,
+        "Name" => entry.name = val,
+        _ if key.starts_with("Name[") && key.ends_with("]") => {
+            let lang = key.trim_start_matches("Name[").trim_end_matches("]").to_string();
+            put(&mut entry.name_localized, lang, val);
+        }
+        "GenericName" => entry.generic_name = Some(val),
+        _ if key.starts_with("GenericName[") && key.ends_with("]") => {
+            let lang = key.trim_start_matches("GenericName[").trim_end_matches("]").to_string();
+            put(&mut entry.generic_name_localized, lang, val);
+        }
+        "Comment" => entry.comment = Some(val),
+        _ if key.starts_with("Comment[") && key.ends_with("]") => {
+            let lang = key.trim_start_matches("Comment[").trim_end_matches("]").to_string();
+            put(&mut entry.comment_localized, lang, val);
+        }
+        "Exec" => entry.exec = val,
+        "TryExec" => entry.try_exec = Some(val),
+        "Icon" => entry.icon = Some(val),
+        "Path" => entry.path = Some(val),
+        "URL" => entry.url = Some(val),
+        "Terminal" => return parse_bool(raw, &mut entry.terminal),
+        "NoDisplay" => return parse_bool(raw, &mut entry.no_display),
+        "StartupNotify" => return parse_bool(raw, &mut entry.startup_notify),
+        "Categories" => entry.categories = split_list(raw),
+        "MimeType" => entry.mime_type = split_list(raw),
+        "Keywords" => entry.keywords = split_list(raw),
+        "OnlyShowIn" => entry.only_show_in = split_list(raw),
+        "NotShowIn" => entry.not_show_in = split_list(raw),
+        "Actions" => entry.actions = split_list(raw),
+        _ => put(&mut entry.extra, key.to_string(), raw.to_string()),
+    }
+    None
+}
+
+fn apply_action_key(action: &mut DesktopAction, key: &str, raw: &str) {
+    let val = unescape_string(raw);
+    match key {
+        "Name" => action.name = val,
+        _ if key.starts_with("Name[") && key.ends_with("]") => {
+            let lang = key.trim_start_matches("Name[").trim_end_matches("]").to_string();
+            put(&mut action.name_localized, lang, val);
+        }
+        "Icon" => action.icon = Some(val),
+        "Exec" => action.exec = Some(val),
+        _ => put(&mut action.extra, key.to_string(), raw.to_string()),
+    }
+}
+
+// Of a key given more than once, the last value counts (as for the modeled keys)
+fn put(pairs: &mut Vec<(String, String)>, key: String, value: String) {
+    match pairs.iter_mut().find(|(k, _)| *k == key) {
+        Some(pair) => pair.1 = value,
+        None => pairs.push((key, value)),
+    }
+}
+
+fn parse_bool(raw: &str, value: &mut bool) -> Option<String> {
+    // The spec only allows true/false; GLib also accepts 1/0, so we do too. Anything else
+    // is read the way older versions did (case-insensitive "true") and reported.
+    match raw {
+        "true" | "1" => *value = true,
+        "false" | "0" => *value = false,
+        _ => {
+            *value = raw.eq_ignore_ascii_case("true");
+            return Some(format!("expected true or false, found \"{}\"; read as {}", raw, value));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::escape::tests::samples;
+
+    // Entries whose strings and list items are full of characters that need escaping
+    fn entries() -> Vec<DesktopEntry> {
+        let strings = samples(600, 10);
+        let items: Vec<String> = strings.iter().filter(|s| !s.is_empty()).cloned().collect();
+        // Exec, Icon and other non-text values are written trimmed
+        let trimmed: Vec<String> = items.iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        (0..100)
+            .map(|i| {
+                let s = |k: usize| strings[(i * 7 + k) % strings.len()].clone();
+                let list = |k: usize| items.iter().cycle().skip(i * 5 + k).take(i % 4).cloned().collect::<Vec<_>>();
+                let t = |k: usize| trimmed[(i * 3 + k) % trimmed.len()].clone();
+                DesktopEntry {
+                    type_field: "Application".into(),
+                    name: s(0),
+                    generic_name: Some(s(1)),
+                    comment: (i % 2 == 0).then(|| s(2)),
+                    exec: t(0),
+                    icon: Some(t(1)),
+                    terminal: i % 3 == 0,
+                    categories: list(0),
+                    mime_type: list(1),
+                    keywords: list(2),
+                    only_show_in: list(3),
+                    not_show_in: list(4),
+                    no_display: i % 5 == 0,
+                    startup_notify: i % 2 == 1,
+                    try_exec: (i % 4 == 0).then(|| t(2)),
+                    path: Some(t(3)),
+                    url: None,
+                    actions: vec!["one".into()],
+                    extra: vec![("X-Number".into(), i.to_string()), ("Version".into(), "1.5".into())],
+                    desktop_actions: vec![DesktopAction {
+                        id: "one".into(),
+                        name: s(3),
+                        name_localized: vec![("de".into(), s(4))],
+                        icon: Some(t(4)),
+                        exec: Some(t(5)),
+                        extra: vec![("X-Extra".into(), "yes".into())],
+                    }],
+                    name_localized: vec![("fr".into(), s(5)), ("pt_BR".into(), s(6))],
+                    generic_name_localized: vec![("de".into(), s(7))],
+                    comment_localized: vec![("sr@latin".into(), s(8))],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entries_round_trip_through_the_file_format() {
+        for entry in entries() {
+            let text = entry.to_ini_string();
+            assert_eq!(parse(&text).as_ref(), Ok(&entry), "{}", text);
+        }
+    }
+
+    #[test]
+    fn odd_booleans_are_read_with_a_warning() {
+        let content = "[Desktop Entry]\nType=Application\nName=A\nExec=a\nTerminal=True\nNoDisplay=yes\n";
+        let (entry, diagnostics) = parse_with_diagnostics(content);
+        assert!(entry.terminal && !entry.no_display);
+        assert_eq!(diagnostics.iter().map(|d| (d.severity, d.line)).collect::<Vec<_>>(), vec![(Severity::Warning, 5), (Severity::Warning, 6)]);
+        assert!(parse(content).is_ok());
+    }
+
+    #[test]
+    fn merged_entries_round_trip() {
+        let base = "# keep me\n[Desktop Entry]\nType=Application\nName=Base\nExec=base\nX-Kept=1\n\n[Other]\nKey=value\n";
+        for entry in entries() {
+            let text = serialize(&entry, Some(base));
+            assert_eq!(parse(&text).as_ref(), Ok(&entry), "{}", text);
+            // New groups are appended, so the unknown one stays where it was
+            assert!(text.starts_with("# keep me\n") && text.contains("\n[Other]\nKey=value\n"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn a_broken_file_keeps_its_layout_without_the_broken_lines() {
+        let content = "\
+Stray=before any group
+# keep me
+[Desktop Entry]
+Type=Application
+Name=Old
+this line is garbage
+Exec=old
+
+[Other]
+Key=value
+
+[Desktop Entry]
+Name=Repeated
+";
+        let (mut entry, diagnostics) = parse_with_diagnostics(content);
+        assert_eq!(diagnostics.iter().filter(|d| d.severity == Severity::Error).count(), 3);
+        entry.name = "New".into();
+        let text = serialize(&entry, Some(content));
+        assert_eq!(text, "# keep me\n[Desktop Entry]\nType=Application\nName=New\nExec=old\n\n[Other]\nKey=value\n\n");
+        assert_eq!(parse(&text).map(|e| e.name), Ok("New".to_string()));
+    }
+}
@@ -1,95 +1,38 @@
-use anyhow::{Context, Result};
-use directories::BaseDirs;
+use anyhow::{anyhow, bail, Context, Result};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::interchange;
+use crate::services::desktop_file;
+use crate::services::discovery::{self, DiscoveredEntry};
+use crate::services::keyfile::KeyFile;
 
 pub struct DesktopReader;
 
 impl DesktopReader {
-    pub fn user_applications_dir() -> Option<PathBuf> {
-        BaseDirs::new().map(|b| b.home_dir().join(".local/share/applications"))
-    }
-
-    pub fn list_desktop_files() -> Result<Vec<PathBuf>> {
-        let dir = match Self::user_applications_dir() {
-            Some(p) => p,
-            None => return Ok(Vec::new()),
-        };
-        let mut files = Vec::new();
-        if dir.exists() {
-            for entry in fs::read_dir(&dir).with_context(|| format!("Reading {}", dir.display()))? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.extension().map(|e| e == "desktop").unwrap_or(false) {
-                    files.push(path);
-                }
-            }
-        }
-        Ok(files)
+    // Every launcher in the XDG data dirs, in precedence order (see services::discovery)
+    pub fn list_desktop_files() -> Vec<DiscoveredEntry> {
+        discovery::discover()
     }
 
+    // Lenient: whatever the file defines, even when it has problems (see
+    // desktop_file::parse_with_diagnostics). Only a file without [Desktop Entry] fails.
     pub fn read_from_path(path: &Path) -> Result<DesktopEntry> {
         let content = fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
-        Ok(parse_desktop_content(&content))
-    }
-}
-
-fn parse_desktop_content(content: &str) -> DesktopEntry {
-    // very simple key=value parser; ignores sections other than [Desktop Entry]
-    let mut entry = DesktopEntry::default();
-    let mut in_desktop = false;
-    for line in content.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') || line.starts_with(';') { continue; }
-        if line.starts_with('[') && line.ends_with(']') {
-            in_desktop = line == "[Desktop Entry]";
-            continue;
-        }
-        if !in_desktop { continue; }
-        if let Some((k, v)) = line.split_once('=') {
-            let key = k.trim();
-            let val = v.trim().to_string();
-            match key {
-                "Type" => entry.type_field = val,
-                "Name" => entry.name = val,
-                _ if key.starts_with("Name[") && key.ends_with("]") => {
-                    let lang = key.trim_start_matches("Name[").trim_end_matches("]").to_string();
-                    entry.name_localized.push((lang, val));
-                }
-                "GenericName" => entry.generic_name = Some(val),
-                _ if key.starts_with("GenericName[") && key.ends_with("]") => {
-                    let lang = key.trim_start_matches("GenericName[").trim_end_matches("]").to_string();
-                    entry.generic_name_localized.push((lang, val));
-                }
-                "Comment" => entry.comment = Some(val),
-                _ if key.starts_with("Comment[") && key.ends_with("]") => {
-                    let lang = key.trim_start_matches("Comment[").trim_end_matches("]").to_string();
-                    entry.comment_localized.push((lang, val));
-                }
-                "Exec" => entry.exec = val,
-                "TryExec" => entry.try_exec = Some(val),
-                "Icon" => entry.icon = Some(val),
-                "Path" => entry.path = Some(val),
-                "URL" => entry.url = Some(val),
-                "Terminal" => entry.terminal = val.eq_ignore_ascii_case("true"),
-                "NoDisplay" => entry.no_display = val.eq_ignore_ascii_case("true"),
-                "StartupNotify" => entry.startup_notify = val.eq_ignore_ascii_case("true"),
-                "Categories" => entry.categories = split_semicolon(&val),
-                "MimeType" => entry.mime_type = split_semicolon(&val),
-                "Keywords" => entry.keywords = split_semicolon(&val),
-                "OnlyShowIn" => entry.only_show_in = split_semicolon(&val),
-                "NotShowIn" => entry.not_show_in = split_semicolon(&val),
-                "Actions" => entry.actions = split_semicolon(&val),
-                _ => entry.extra.push((key.to_string(), val)),
-            }
+        if !KeyFile::parse(&content).groups().contains(&"Desktop Entry") {
+            bail!("{} has no [Desktop Entry] group", path.display());
         }
+        Ok(desktop_file::parse_with_diagnostics(&content).0)
     }
-    if entry.type_field.is_empty() { entry.type_field = "Application".into(); }
-    entry
-}
 
-fn split_semicolon(s: &str) -> Vec<String> {
-    s.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect()
+    // A launcher document (domain::interchange); the extension picks JSON or TOML
+    pub fn read_document(path: &Path) -> Result<DesktopEntry> {
+        let content = fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+        let parsed = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => interchange::from_toml(&content),
+            _ => interchange::from_json(&content),
+        };
+        parsed.map_err(|e| anyhow!(e)).with_context(|| format!("Parsing {}", path.display()))
+    }
 }
@@ -1,32 +1,40 @@
 use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::interchange;
+use crate::services::desktop_file;
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::discovery;
+use crate::services::icons::Icons;
+use crate::services::mime_cache::MimeCache;
 use anyhow::{anyhow, Context, Result};
-use directories::BaseDirs;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+// Marks launchers created from a provisioning manifest; the value is the owner ID their
+// manifest directory declares (see services::provisioning)
+pub const KEY_MANAGED: &str = "X-LauncherStudio-Managed";
+
 pub struct DesktopWriter;
 
 impl DesktopWriter {
     pub fn user_applications_dir() -> Result<PathBuf> {
-        if let Some(base) = BaseDirs::new() {
-            let dir = base.home_dir().join(".local/share/applications");
-            Ok(dir)
-        } else {
-            Err(anyhow!("Failed to resolve XDG base directories"))
-        }
+        discovery::user_applications_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))
     }
 
     pub fn write(entry: &DesktopEntry, file_name: &str, overwrite: bool) -> Result<PathBuf> {
-        entry.validate().map_err(|e| anyhow!(e))?;
-        let dir = Self::user_applications_dir()?;
-        fs::create_dir_all(&dir).context("Creating applications directory")?;
+        Self::write_in(entry, &Self::user_applications_dir()?, file_name, overwrite)
+    }
+
+    // Same as `write`, into another directory (e.g. ~/.config/autostart)
+    pub fn write_in(entry: &DesktopEntry, dir: &Path, file_name: &str, overwrite: bool) -> Result<PathBuf> {
+        entry.check().map_err(|e| anyhow!(e))?;
+        fs::create_dir_all(dir).with_context(|| format!("Creating directory {}", dir.display()))?;
 
         let sanitized = sanitize_file_name(file_name);
         let path = dir.join(format!("{}.desktop", sanitized));
         if path.exists() && !overwrite {
             return Err(anyhow!("File already exists: {}", path.display()));
         }
-        let content = entry.to_ini_string();
+        let content = render(entry, &path);
         fs::write(&path, content).with_context(|| format!("Writing {}", path.display()))?;
 
         // Try to set sane permissions (not strictly required)
@@ -37,26 +45,73 @@ impl DesktopWriter {
             perms.set_mode(0o644);
             fs::set_permissions(&path, perms)?;
         }
+        // The launcher is saved even if its MimeType= associations could not be indexed
+        MimeCache::refresh_for(&path).ok();
 
         Ok(path)
     }
 
+    // Delete a launcher, then the icon installed for it unless something else shows it.
+    // Returns the icon files removed; the launcher is gone even if they could not be.
+    pub fn delete(path: &Path) -> Result<Vec<PathBuf>> {
+        let entry = DesktopReader::read_from_path(path).ok();
+        fs::remove_file(path).with_context(|| format!("Deleting {}", path.display()))?;
+        MimeCache::refresh_for(path).ok();
+        Ok(entry.and_then(|e| Icons::release(&e, path).ok()).unwrap_or_default())
+    }
+
     pub fn write_to_path(entry: &DesktopEntry, path: &Path) -> Result<PathBuf> {
-        entry.validate().map_err(|e| anyhow!(e))?;
+        Self::write_to_path_with_mode(entry, path, 0o644)
+    }
+
+    // Launchers outside the applications dirs (e.g. on the desktop) must be executable
+    // for file managers to run them
+    pub fn write_to_path_with_mode(entry: &DesktopEntry, path: &Path, mode: u32) -> Result<PathBuf> {
+        entry.check().map_err(|e| anyhow!(e))?;
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).with_context(|| format!("Creating directory {}", parent.display()))?;
         }
-        let content = entry.to_ini_string();
+        let content = render(entry, path);
         fs::write(path, content).with_context(|| format!("Writing {}", path.display()))?;
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
             let mut perms = fs::metadata(path)?.permissions();
-            perms.set_mode(0o644);
+            perms.set_mode(mode);
             fs::set_permissions(path, perms)?;
         }
+        MimeCache::refresh_for(path).ok();
         Ok(path.to_path_buf())
     }
+
+    // Write `entry` at `path` as owned by `owner`, so a later apply can update or prune it
+    pub fn write_managed(entry: &DesktopEntry, path: &Path, owner: &str) -> Result<PathBuf> {
+        Self::write_to_path(&managed(entry, owner), path)
+    }
+
+    // Export as a launcher document (domain::interchange), TOML for .toml paths and JSON
+    // otherwise. Not validated: a document may be a draft another tool completes.
+    pub fn write_document(entry: &DesktopEntry, path: &Path) -> Result<PathBuf> {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => interchange::to_toml(entry),
+            _ => interchange::to_json(entry),
+        };
+        fs::write(path, content).with_context(|| format!("Writing {}", path.display()))?;
+        Ok(path.to_path_buf())
+    }
+}
+
+// `entry` with the ownership marker set
+pub fn managed(entry: &DesktopEntry, owner: &str) -> DesktopEntry {
+    let mut entry = entry.clone();
+    entry.set_extra(KEY_MANAGED, Some(owner));
+    entry
+}
+
+// Serialize the entry for `path`, keeping the layout of the file it replaces
+fn render(entry: &DesktopEntry, path: &Path) -> String {
+    let existing = fs::read_to_string(path).ok();
+    desktop_file::serialize(entry, existing.as_deref())
 }
 
 fn sanitize_file_name(input: &str) -> String {
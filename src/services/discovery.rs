@@ -0,0 +1,239 @@
+// Application discovery per the XDG Base Directory and Desktop Entry specifications:
+// every $XDG_DATA_HOME/applications and $XDG_DATA_DIRS/*/applications tree is walked,
+// files get their desktop-file ID, and the first directory in precedence order wins.
+use directories::BaseDirs;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Source {
+    User,
+    System,
+    Flatpak,
+    Snap,
+}
+
+impl Source {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Source::User => "User",
+            Source::System => "System",
+            Source::Flatpak => "Flatpak",
+            Source::Snap => "Snap",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AppDir {
+    pub path: PathBuf, // the applications directory itself
+    pub source: Source,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscoveredEntry {
+    pub id: String, // desktop-file ID, e.g. "kde4-konsole.desktop"
+    pub path: PathBuf,
+    pub source: Source,
+    pub shadowed_by: Option<PathBuf>, // file with the same ID in a higher-precedence dir
+    pub shadows: Vec<PathBuf>,        // files this one hides
+}
+
+impl DiscoveredEntry {
+    // A user file hiding a launcher installed elsewhere
+    pub fn is_override(&self) -> bool {
+        self.source == Source::User && !self.shadows.is_empty()
+    }
+}
+
+// $XDG_DATA_HOME, defaulting to ~/.local/share
+pub fn data_home() -> Option<PathBuf> {
+    match env::var_os("XDG_DATA_HOME").map(PathBuf::from) {
+        Some(p) if p.is_absolute() => Some(p),
+        _ => BaseDirs::new().map(|b| b.home_dir().join(".local/share")),
+    }
+}
+
+// $XDG_DATA_DIRS, defaulting to /usr/local/share:/usr/share
+pub fn data_dirs() -> Vec<PathBuf> {
+    let dirs: Vec<PathBuf> = env::var_os("XDG_DATA_DIRS")
+        .map(|v| env::split_paths(&v).filter(|p| p.is_absolute()).collect())
+        .unwrap_or_default();
+    if dirs.is_empty() {
+        vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]
+    } else {
+        dirs
+    }
+}
+
+// $XDG_CONFIG_HOME, defaulting to ~/.config
+pub fn config_home() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(p) if p.is_absolute() => Some(p),
+        _ => BaseDirs::new().map(|b| b.home_dir().join(".config")),
+    }
+}
+
+// $XDG_CONFIG_DIRS, defaulting to /etc/xdg
+pub fn config_dirs() -> Vec<PathBuf> {
+    let dirs: Vec<PathBuf> = env::var_os("XDG_CONFIG_DIRS")
+        .map(|v| env::split_paths(&v).filter(|p| p.is_absolute()).collect())
+        .unwrap_or_default();
+    if dirs.is_empty() { vec![PathBuf::from("/etc/xdg")] } else { dirs }
+}
+
+pub fn user_applications_dir() -> Option<PathBuf> {
+    data_home().map(|d| d.join("applications"))
+}
+
+// Every applications directory, most important first, without duplicates
+pub fn application_dirs() -> Vec<AppDir> {
+    let home = data_home();
+    let mut dirs = Vec::new();
+    let mut seen = HashSet::new();
+    for base in home.iter().cloned().chain(data_dirs()) {
+        let path = base.join("applications");
+        if seen.insert(path.clone()) {
+            let source = classify(&path, home.as_deref());
+            dirs.push(AppDir { path, source });
+        }
+    }
+    dirs
+}
+
+fn classify(dir: &Path, home: Option<&Path>) -> Source {
+    let s = dir.to_string_lossy();
+    if s.contains("/flatpak/exports/") {
+        Source::Flatpak
+    } else if s.contains("/snapd/desktop") || s.starts_with("/snap/") {
+        Source::Snap
+    } else if home.is_some_and(|h| dir.starts_with(h)) {
+        Source::User
+    } else {
+        Source::System
+    }
+}
+
+// Desktop-file ID: path below the applications dir with '/' replaced by '-'
+pub fn desktop_file_id(apps_dir: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(apps_dir).ok()?;
+    let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+    if parts.is_empty() { None } else { Some(parts.join("-")) }
+}
+
+// All .desktop files in every applications dir, in precedence order
+pub fn discover() -> Vec<DiscoveredEntry> {
+    discover_in(&application_dirs())
+}
+
+pub fn discover_in(dirs: &[AppDir]) -> Vec<DiscoveredEntry> {
+    let mut entries: Vec<DiscoveredEntry> = Vec::new();
+    let mut winners: HashMap<String, usize> = HashMap::new();
+    for dir in dirs {
+        let mut files = Vec::new();
+        let mut visited = HashSet::new();
+        walk(&dir.path, &mut files, &mut visited);
+        files.sort();
+        for path in files {
+            let Some(id) = desktop_file_id(&dir.path, &path) else { continue };
+            let shadowed_by = winners.get(&id).map(|&i| entries[i].path.clone());
+            match winners.get(&id) {
+                Some(&i) => entries[i].shadows.push(path.clone()),
+                None => {
+                    winners.insert(id.clone(), entries.len());
+                }
+            }
+            entries.push(DiscoveredEntry { id, path, source: dir.source, shadowed_by, shadows: Vec::new() });
+        }
+    }
+    entries
+}
+
+fn walk(dir: &Path, out: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) {
+    // Symlinked directories are followed once
+    let Ok(real) = fs::canonicalize(dir) else { return };
+    if !visited.insert(real) {
+        return;
+    }
+    let Ok(read) = fs::read_dir(dir) else { return };
+    for entry in read.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, out, visited);
+        } else if path.extension().map(|e| e == "desktop").unwrap_or(false) {
+            out.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_env;
+
+    const LAUNCHER: &str = "[Desktop Entry]\nType=Application\nName=App\nExec=app\n";
+
+    #[test]
+    fn desktop_file_ids_join_subdirectories_with_dashes() {
+        let apps = Path::new("/usr/share/applications");
+        assert_eq!(desktop_file_id(apps, &apps.join("app.desktop")).as_deref(), Some("app.desktop"));
+        assert_eq!(desktop_file_id(apps, &apps.join("kde4/konsole.desktop")).as_deref(), Some("kde4-konsole.desktop"));
+        assert_eq!(desktop_file_id(apps, &apps.join("a/b/c.desktop")).as_deref(), Some("a-b-c.desktop"));
+        assert_eq!(desktop_file_id(apps, apps), None);
+        assert_eq!(desktop_file_id(apps, Path::new("/opt/app.desktop")), None);
+    }
+
+    #[test]
+    fn user_launchers_shadow_system_ones_with_the_same_id() {
+        let env = test_env::isolated("discovery-shadowing");
+        let user = env.write("data/applications/kde4-konsole.desktop", LAUNCHER);
+        let system = env.write("system-data/applications/kde4/konsole.desktop", LAUNCHER);
+        let only_system = env.write("system-data/applications/viewer.desktop", LAUNCHER);
+        env.write("system-data/applications/notes.txt", "not a launcher");
+
+        let found = discover();
+        let summary: Vec<(&str, &Path, Source)> = found.iter().map(|d| (d.id.as_str(), d.path.as_path(), d.source)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("kde4-konsole.desktop", user.as_path(), Source::User),
+                ("kde4-konsole.desktop", system.as_path(), Source::System),
+                ("viewer.desktop", only_system.as_path(), Source::System),
+            ]
+        );
+        assert_eq!(found[0].shadows, std::slice::from_ref(&system));
+        assert!(found[0].is_override() && found[0].shadowed_by.is_none());
+        assert_eq!(found[1].shadowed_by.as_deref(), Some(user.as_path()));
+        assert!(found[2].shadowed_by.is_none() && !found[2].is_override());
+    }
+
+    #[test]
+    fn earlier_data_dirs_win_and_relative_ones_are_ignored() {
+        let env = test_env::isolated("discovery-data-dirs");
+        let first = env.root.join("first");
+        let dirs = env::join_paths([first.clone(), PathBuf::from("relative"), env.root.join("system-data"), first.clone()]).unwrap();
+        // Only ever changed while the test environment's lock is held
+        unsafe { env::set_var("XDG_DATA_DIRS", dirs) };
+        let winner = env.write("first/applications/app.desktop", LAUNCHER);
+        env.write("system-data/applications/app.desktop", LAUNCHER);
+
+        let paths: Vec<PathBuf> = application_dirs().into_iter().map(|d| d.path).collect();
+        assert_eq!(paths, [env.data_home(), first, env.root.join("system-data")].map(|d| d.join("applications")));
+        let found = discover();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].shadowed_by.as_deref(), Some(winner.as_path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_directories_are_walked_once() {
+        let env = test_env::isolated("discovery-symlinks");
+        env.write("data/applications/vendor/app.desktop", LAUNCHER);
+        let apps = env.data_home().join("applications");
+        std::os::unix::fs::symlink(&apps, apps.join("vendor/loop")).unwrap();
+        let ids: Vec<String> = discover().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, ["vendor-app.desktop"]);
+    }
+}
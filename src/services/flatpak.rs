@@ -0,0 +1,170 @@
+// Installed Flatpak apps, read straight from the installations: each app's deployment
+// (app/<id>/current and its metadata file) and the launchers it exports to
+// exports/share/applications. Launchers built from them run the app with `flatpak run`.
+use anyhow::Result;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::flatpak::{self, AppRef};
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::discovery;
+use crate::services::keyfile::KeyFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    User,
+    System,
+}
+
+impl Scope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Scope::User => "user",
+            Scope::System => "system",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Installation {
+    pub path: PathBuf,
+    pub scope: Scope,
+}
+
+#[derive(Debug, Clone)]
+pub struct FlatpakApp {
+    pub app: AppRef,
+    pub scope: Scope,
+    pub name: String,                // Name of the main exported launcher, else the app ID
+    pub command: Option<String>,     // command= from the metadata file
+    pub runtime: Option<String>,     // runtime= from the metadata file
+    pub desktop_files: Vec<PathBuf>, // exported launchers, main one first
+}
+
+// The per-user installation, the default system one and any configured in
+// /etc/flatpak/installations.d, in the order `flatpak run` looks
+pub fn installations() -> Vec<Installation> {
+    let mut list: Vec<Installation> = discovery::data_home()
+        .map(|d| Installation { path: d.join("flatpak"), scope: Scope::User })
+        .into_iter()
+        .collect();
+    let system = env::var_os("FLATPAK_SYSTEM_DIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/var/lib/flatpak"));
+    list.push(Installation { path: system, scope: Scope::System });
+
+    let mut confs: Vec<PathBuf> = fs::read_dir("/etc/flatpak/installations.d")
+        .map(|r| r.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "conf")).collect())
+        .unwrap_or_default();
+    confs.sort();
+    for conf in confs {
+        let Ok(text) = fs::read_to_string(&conf) else { continue };
+        let doc = KeyFile::parse(&text);
+        for group in doc.groups().into_iter().filter(|g| g.starts_with("Installation ")) {
+            if let Some((_, _, path)) = doc.numbered_entries(group).into_iter().find(|(_, k, _)| k.trim() == "Path") {
+                list.push(Installation { path: PathBuf::from(path.trim()), scope: Scope::System });
+            }
+        }
+    }
+    list
+}
+
+pub struct Flatpak;
+
+impl Flatpak {
+    // Apps of every installation, by name
+    pub fn list() -> Vec<FlatpakApp> {
+        let mut apps: Vec<FlatpakApp> = installations().iter().flat_map(apps_in).collect();
+        apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.app.id.cmp(&b.app.id)));
+        apps
+    }
+
+    // A launcher for the app: its main exported launcher with every Exec rebuilt for the
+    // installed branch, or a minimal one when it exports none (command-line tools)
+    pub fn launcher(app: &FlatpakApp) -> Result<DesktopEntry> {
+        let Some(main) = app.desktop_files.first() else {
+            let mut entry = DesktopEntry {
+                type_field: "Application".into(),
+                name: app.name.clone(),
+                exec: flatpak::run_exec(&app.app, app.command.as_deref(), &[]),
+                icon: Some(app.app.id.clone()),
+                ..Default::default()
+            };
+            entry.set_extra("X-Flatpak", Some(&app.app.id));
+            return Ok(entry);
+        };
+        let mut entry = DesktopReader::read_from_path(main)?;
+        entry.exec = flatpak::rebase_exec(&entry.exec, &app.app, app.command.as_deref());
+        for action in &mut entry.desktop_actions {
+            if let Some(exec) = action.exec.as_mut().filter(|e| !e.trim().is_empty()) {
+                *exec = flatpak::rebase_exec(exec, &app.app, app.command.as_deref());
+            }
+        }
+        if entry.extra_value("X-Flatpak").is_none() {
+            entry.set_extra("X-Flatpak", Some(&app.app.id));
+        }
+        Ok(entry)
+    }
+}
+
+fn apps_in(inst: &Installation) -> Vec<FlatpakApp> {
+    let Ok(read) = fs::read_dir(inst.path.join("app")) else { return Vec::new() };
+    let exports = inst.path.join("exports/share/applications");
+    let mut apps = Vec::new();
+    for dir in read.flatten() {
+        let id = dir.file_name().to_string_lossy().into_owned();
+        let Some((arch, branch)) = current_deploy(&dir.path()) else { continue };
+        let app = AppRef { id: id.clone(), arch, branch };
+        let metadata = fs::read_to_string(dir.path().join(&app.arch).join(&app.branch).join("active/metadata")).unwrap_or_default();
+        let doc = KeyFile::parse(&metadata);
+        let value = |key: &str| {
+            doc.numbered_entries("Application").into_iter().find(|(_, k, _)| k.trim() == key).map(|(_, _, v)| v.trim().to_string())
+        };
+        let desktop_files = exported_launchers(&exports, &inst.path.join("app"), &id);
+        let name = desktop_files
+            .first()
+            .and_then(|p| DesktopReader::read_from_path(p).ok())
+            .map(|e| e.name)
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| id.clone());
+        apps.push(FlatpakApp { name, command: value("command"), runtime: value("runtime"), desktop_files, app, scope: inst.scope });
+    }
+    apps
+}
+
+// The deployment `flatpak run` uses without --arch/--branch: the `current` link
+// ("<arch>/<branch>"), or the only one installed when the link is missing
+fn current_deploy(app_dir: &Path) -> Option<(String, String)> {
+    if let Ok(target) = fs::read_link(app_dir.join("current")) {
+        let target = target.to_string_lossy().into_owned();
+        if let Some((arch, branch)) = target.split_once('/') {
+            return Some((arch.to_string(), branch.trim_end_matches('/').to_string()));
+        }
+    }
+    let mut deploys = Vec::new();
+    for arch in fs::read_dir(app_dir).ok()?.flatten().filter(|e| e.path().is_dir()) {
+        for branch in fs::read_dir(arch.path()).into_iter().flatten().flatten() {
+            if branch.path().join("active").exists() {
+                deploys.push((arch.file_name().to_string_lossy().into_owned(), branch.file_name().to_string_lossy().into_owned()));
+            }
+        }
+    }
+    if deploys.len() == 1 { deploys.pop() } else { None }
+}
+
+// Exported launchers must be named after the app ID: "<id>.desktop", then "<id>.*.desktop"
+// unless that name is another installed app's ("org.app" vs "org.app.Devel")
+fn exported_launchers(exports: &Path, app_root: &Path, id: &str) -> Vec<PathBuf> {
+    let main = format!("{}.desktop", id);
+    let prefix = format!("{}.", id);
+    let mut files: Vec<PathBuf> = fs::read_dir(exports)
+        .map(|r| r.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    files.retain(|p| {
+        let name = p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let Some(stem) = name.strip_suffix(".desktop") else { return false };
+        name == main || (stem.starts_with(&prefix) && !app_root.join(stem).exists())
+    });
+    files.sort_by_key(|p| (p.file_name().is_none_or(|n| n.to_string_lossy() != main), p.clone()));
+    files
+}
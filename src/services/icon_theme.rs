@@ -0,0 +1,448 @@
+// Icon lookup as in the Icon Theme Specification: a theme is described by the first
+// index.theme found under ~/.icons, $XDG_DATA_HOME/icons and $XDG_DATA_DIRS/icons, its
+// icons are searched in that theme dir of every base dir, then in the themes it inherits,
+// then in hicolor, and finally unthemed in the base dirs and /usr/share/pixmaps.
+use directories::BaseDirs;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::validation::{Finding, Severity, SPEC_ICON};
+use crate::domain::{exec, flatpak};
+use crate::services::discovery;
+use crate::services::icons::HICOLOR_SIZES;
+use crate::services::keyfile::KeyFile;
+
+// Sizes reported for an icon, the common launcher and panel sizes
+pub const REPORT_SIZES: &[u32] = &[16, 24, 32, 48, 64, 128, 256];
+
+const EXTENSIONS: &[&str] = &["png", "svg", "xpm"];
+
+// What a theme directory's Context= (or its name) says its icons are for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconCategory {
+    Apps,
+    MimeTypes,
+    Places,
+    Other,
+}
+
+// An icon name the resolver can find, with the category of the first directory holding it
+#[derive(Debug, Clone)]
+pub struct ThemeIcon {
+    pub name: String,
+    pub category: IconCategory,
+}
+
+impl ThemeIcon {
+    pub fn is_symbolic(&self) -> bool {
+        self.name.ends_with("-symbolic")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+// One subdirectory of a theme and the sizes it serves
+#[derive(Debug, Clone)]
+struct ThemeDir {
+    name: String,
+    context: Option<String>, // Applications, MimeTypes, Places, ...
+    size: u32,
+    scale: u32,
+    kind: DirType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDir {
+    fn matches(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirType::Fixed => self.size == size,
+            DirType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirType::Threshold => (self.size.saturating_sub(self.threshold)..=self.size + self.threshold).contains(&size),
+        }
+    }
+
+    // How far the directory's icons are from the wanted pixel size
+    fn distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+        let (low, high) = match self.kind {
+            DirType::Fixed => (self.size, self.size),
+            DirType::Scalable => (self.min_size, self.max_size),
+            DirType::Threshold => (self.size.saturating_sub(self.threshold), self.size + self.threshold),
+        };
+        let (low, high) = (low * self.scale, high * self.scale);
+        low.saturating_sub(wanted).max(wanted.saturating_sub(high))
+    }
+}
+
+#[derive(Debug)]
+struct Theme {
+    inherits: Vec<String>,
+    dirs: Vec<ThemeDir>,
+    roots: Vec<PathBuf>, // <base dir>/<theme name> that exist
+}
+
+// ~/.icons, $XDG_DATA_HOME/icons, $XDG_DATA_DIRS/icons, then /usr/share/pixmaps
+pub fn base_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = BaseDirs::new().map(|b| b.home_dir().join(".icons")).into_iter().collect();
+    dirs.extend(discovery::data_home().map(|d| d.join("icons")));
+    dirs.extend(discovery::data_dirs().into_iter().map(|d| d.join("icons")));
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    let mut seen = HashSet::new();
+    dirs.retain(|d| seen.insert(d.clone()));
+    dirs
+}
+
+// The icon theme chosen in the GTK settings files, else GTK's default
+pub fn current_theme_name() -> String {
+    let Some(config) = discovery::config_home() else { return "Adwaita".into() };
+    for version in ["gtk-4.0", "gtk-3.0"] {
+        let Ok(text) = fs::read_to_string(config.join(version).join("settings.ini")) else { continue };
+        let doc = KeyFile::parse(&text);
+        if let Some((_, _, name)) = doc.numbered_entries("Settings").into_iter().find(|(_, k, _)| k.trim() == "gtk-icon-theme-name") {
+            let name = name.trim().trim_matches('"');
+            if !name.is_empty() {
+                return name.to_string();
+            }
+        }
+    }
+    "Adwaita".into()
+}
+
+// Words the icon of `entry` is likely named after, best first: the Flatpak app ID or the
+// program Exec runs, StartupWMClass, then the Name
+pub fn icon_hints(entry: &DesktopEntry) -> Vec<String> {
+    let mut hints = Vec::new();
+    if let Some(app) = flatpak::exec_deploy_ref(&entry.exec) {
+        hints.push(app.id.clone());
+        hints.extend(app.id.rsplit('.').next().map(str::to_string));
+    }
+    let args: Vec<String> = exec::parse(&entry.exec).0.args.iter().map(|a| a.text()).collect();
+    let program = args.iter().find(|a| a.rsplit('/').next() != Some("env") && !a.contains('='));
+    if let Some(program) = program.and_then(|p| p.rsplit('/').next()) {
+        if program == "flatpak" {
+            // flatpak run [options] <app id>
+            if let Some(id) = args.iter().skip(2).find(|a| !a.starts_with('-')) {
+                hints.push(id.clone());
+                hints.extend(id.rsplit('.').next().map(str::to_string));
+            }
+        } else {
+            hints.push(program.to_string());
+            hints.extend(program.split_once('.').map(|(stem, _)| stem.to_string()));
+        }
+    }
+    hints.extend(entry.extra_value("StartupWMClass").map(str::to_string));
+    let name = entry.name.to_lowercase();
+    let words: Vec<&str> = name.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    hints.push(words.join("-"));
+    hints.extend(words.iter().filter(|w| w.len() >= 3).map(|w| w.to_string()));
+    let mut seen = HashSet::new();
+    hints.into_iter().map(|h| h.to_lowercase()).filter(|h| !h.is_empty() && seen.insert(h.clone())).collect()
+}
+
+// How well an icon matches the hints; 0 for no match. Exact names beat the last part of
+// reverse-DNS names (org.gnome.Calculator), which beat partial matches, the longer the
+// better; symbolic icons, which launchers rarely use, rank lower.
+pub fn suggestion_score(icon: &ThemeIcon, hints: &[String]) -> u32 {
+    let name = icon.name.to_lowercase();
+    let plain = name.strip_suffix("-symbolic").unwrap_or(&name);
+    let last = plain.rsplit('.').next().unwrap_or(plain);
+    let score = hints
+        .iter()
+        .map(|hint| {
+            if plain == hint {
+                100
+            } else if last == hint {
+                90
+            } else if hint.len() >= 3 && plain.starts_with(hint.as_str()) {
+                (30 + 2 * hint.len() as u32).min(80)
+            } else if hint.len() >= 3 && plain.contains(hint.as_str()) {
+                (20 + 2 * hint.len() as u32).min(70)
+            } else if last.len() >= 4 && hint.contains(last) {
+                20
+            } else {
+                0
+            }
+        })
+        .max()
+        .unwrap_or(0);
+    match (score, icon.is_symbolic(), icon.category) {
+        (0, _, _) => 0,
+        (s, true, _) => s / 2,
+        (s, false, IconCategory::Apps) => s + 5,
+        (s, _, _) => s,
+    }
+}
+
+// Icon= values of the entry and its actions that the current theme cannot resolve. Looks
+// at the disk, so it is a separate step from DesktopEntry::validate.
+pub fn check_icons(entry: &DesktopEntry) -> Vec<Finding> {
+    let icons = std::iter::once(("Desktop Entry".to_string(), entry.icon.as_deref()))
+        .chain(entry.desktop_actions.iter().map(|a| (a.group_name(), a.icon.as_deref())));
+    let mut resolver = IconResolver::new(&current_theme_name());
+    let mut findings = Vec::new();
+    for (group, icon) in icons {
+        let Some(icon) = icon.map(str::trim).filter(|i| !i.is_empty()) else { continue };
+        if resolver.lookup(icon, 48, 1).is_none() {
+            let message = resolver.missing_message(icon);
+            findings.push(Finding { severity: Severity::Error, group, key: Some("Icon".into()), message, spec: SPEC_ICON });
+        }
+    }
+    findings
+}
+
+pub struct IconResolver {
+    theme: String,
+    base_dirs: Vec<PathBuf>,
+    themes: HashMap<String, Option<Rc<Theme>>>, // parsed on first use
+}
+
+impl IconResolver {
+    pub fn new(theme: &str) -> Self {
+        IconResolver { theme: theme.to_string(), base_dirs: base_dirs(), themes: HashMap::new() }
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    // The file used for an Icon= value at `size` (in logical pixels) and `scale`. Absolute
+    // paths are taken as they are; a name with an image extension is looked up without it.
+    pub fn lookup(&mut self, icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let icon = icon.trim();
+        if icon.is_empty() {
+            return None;
+        }
+        if icon.starts_with('/') {
+            return Path::new(icon).is_file().then(|| PathBuf::from(icon));
+        }
+        if icon.contains('/') {
+            return None;
+        }
+        let name = EXTENSIONS.iter().find_map(|ext| icon.strip_suffix(&format!(".{}", ext))).unwrap_or(icon);
+        let theme = self.theme.clone();
+        let mut visited = HashSet::new();
+        if let Some(found) = self.find_in(&theme, name, size, scale, &mut visited) {
+            return Some(found);
+        }
+        if let Some(found) = self.find_in("hicolor", name, size, scale, &mut visited) {
+            return Some(found);
+        }
+        // Unthemed icons directly in the base dirs
+        self.base_dirs
+            .iter()
+            .flat_map(|dir| EXTENSIONS.iter().map(move |ext| dir.join(format!("{}.{}", name, ext))))
+            .find(|p| p.is_file())
+    }
+
+    // Why lookup() found nothing
+    pub fn missing_message(&self, icon: &str) -> String {
+        let icon = icon.trim();
+        if icon.starts_with('/') {
+            format!("icon file {} does not exist", icon)
+        } else if icon.contains('/') {
+            format!("\"{}\" is neither an icon name nor an absolute path", icon)
+        } else {
+            format!("no icon named \"{}\" in the {} theme, its parents, hicolor or the unthemed icon dirs", icon, self.theme)
+        }
+    }
+
+    // File used at each of REPORT_SIZES (scale 1)
+    pub fn report(&mut self, icon: &str) -> Vec<(u32, Option<PathBuf>)> {
+        REPORT_SIZES.iter().map(|&size| (size, self.lookup(icon, size, 1))).collect()
+    }
+
+    // Every icon name lookup() finds: the theme's, its parents', hicolor's and the unthemed
+    // ones, sorted by name
+    pub fn list(&mut self) -> Vec<ThemeIcon> {
+        let mut themes = Vec::new();
+        let mut visited = HashSet::new();
+        let theme = self.theme.clone();
+        self.chain(&theme, &mut visited, &mut themes);
+        self.chain("hicolor", &mut visited, &mut themes);
+        let mut found: HashMap<String, IconCategory> = HashMap::new();
+        for theme in themes {
+            for dir in &theme.dirs {
+                let category = dir_category(dir);
+                for root in &theme.roots {
+                    for icon in icon_names(&root.join(&dir.name)) {
+                        found.entry(icon).or_insert(category);
+                    }
+                }
+            }
+        }
+        for dir in &self.base_dirs {
+            for icon in icon_names(dir) {
+                found.entry(icon).or_insert(IconCategory::Other);
+            }
+        }
+        let mut icons: Vec<ThemeIcon> = found.into_iter().map(|(name, category)| ThemeIcon { name, category }).collect();
+        icons.sort_by(|a, b| a.name.cmp(&b.name));
+        icons
+    }
+
+    // The theme, then the themes it inherits (depth first, each once)
+    fn find_in(&mut self, theme_name: &str, icon: &str, size: u32, scale: u32, visited: &mut HashSet<String>) -> Option<PathBuf> {
+        if !visited.insert(theme_name.to_string()) {
+            return None;
+        }
+        let theme = self.load(theme_name)?;
+        if let Some(found) = lookup_in(&theme, icon, size, scale) {
+            return Some(found);
+        }
+        for parent in &theme.inherits {
+            if let Some(found) = self.find_in(parent, icon, size, scale, visited) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    // The themes find_in() visits, in the same order
+    fn chain(&mut self, name: &str, visited: &mut HashSet<String>, out: &mut Vec<Rc<Theme>>) {
+        if !visited.insert(name.to_string()) {
+            return;
+        }
+        let Some(theme) = self.load(name) else { return };
+        out.push(theme.clone());
+        for parent in &theme.inherits {
+            self.chain(parent, visited, out);
+        }
+    }
+
+    fn load(&mut self, name: &str) -> Option<Rc<Theme>> {
+        if let Some(cached) = self.themes.get(name) {
+            return cached.clone();
+        }
+        let theme = read_theme(&self.base_dirs, name).map(Rc::new);
+        self.themes.insert(name.to_string(), theme.clone());
+        theme
+    }
+}
+
+fn dir_category(dir: &ThemeDir) -> IconCategory {
+    let context = dir.context.as_deref().unwrap_or("");
+    let last = dir.name.rsplit('/').next().unwrap_or("");
+    if context == "Applications" || last == "apps" {
+        IconCategory::Apps
+    } else if context == "MimeTypes" || last == "mimetypes" {
+        IconCategory::MimeTypes
+    } else if context == "Places" || last == "places" {
+        IconCategory::Places
+    } else {
+        IconCategory::Other
+    }
+}
+
+// Icon names (file names without the image extension) in a directory; GTK's pre-rendered
+// foo-symbolic.symbolic.png is foo-symbolic
+fn icon_names(dir: &Path) -> Vec<String> {
+    let Ok(read) = fs::read_dir(dir) else { return Vec::new() };
+    read.flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let (stem, ext) = name.rsplit_once('.')?;
+            EXTENSIONS.contains(&ext).then(|| stem.strip_suffix(".symbolic").unwrap_or(stem).to_string())
+        })
+        .collect()
+}
+
+// Exact size match first, otherwise the closest size available
+fn lookup_in(theme: &Theme, icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+    let candidates = |dir: &ThemeDir| {
+        theme
+            .roots
+            .iter()
+            .flat_map(move |root| EXTENSIONS.iter().map(move |ext| root.join(&dir.name).join(format!("{}.{}", icon, ext))))
+            .find(|p| p.is_file())
+    };
+    if let Some(found) = theme.dirs.iter().filter(|d| d.matches(size, scale)).find_map(candidates) {
+        return Some(found);
+    }
+    let mut best: Option<(u32, PathBuf)> = None;
+    for dir in &theme.dirs {
+        let distance = dir.distance(size, scale);
+        if best.as_ref().is_some_and(|(d, _)| *d <= distance) {
+            continue;
+        }
+        if let Some(found) = candidates(dir) {
+            best = Some((distance, found));
+        }
+    }
+    best.map(|(_, p)| p)
+}
+
+fn read_theme(base_dirs: &[PathBuf], name: &str) -> Option<Theme> {
+    let roots: Vec<PathBuf> = base_dirs.iter().map(|b| b.join(name)).filter(|p| p.is_dir()).collect();
+    let index = roots.iter().find_map(|r| fs::read_to_string(r.join("index.theme")).ok());
+    let Some(index) = index else {
+        // hicolor is the fallback theme and has a fixed layout, even without an index
+        return (name == "hicolor" && !roots.is_empty()).then(|| Theme { inherits: Vec::new(), dirs: hicolor_dirs(), roots });
+    };
+    let doc = KeyFile::parse(&index);
+    let value = |group: &str, key: &str| {
+        doc.numbered_entries(group).into_iter().find(|(_, k, _)| k.trim() == key).map(|(_, _, v)| v.trim().to_string())
+    };
+    let list = |key: &str| -> Vec<String> {
+        value("Icon Theme", key)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    };
+    let mut dirs = Vec::new();
+    for dir in list("Directories").into_iter().chain(list("ScaledDirectories")) {
+        let number = |key: &str| value(&dir, key).and_then(|v| v.parse::<u32>().ok());
+        let Some(size) = number("Size") else { continue };
+        let kind = match value(&dir, "Type").as_deref() {
+            Some("Fixed") => DirType::Fixed,
+            Some("Scalable") => DirType::Scalable,
+            _ => DirType::Threshold,
+        };
+        dirs.push(ThemeDir {
+            size,
+            scale: number("Scale").unwrap_or(1).max(1),
+            kind,
+            min_size: number("MinSize").unwrap_or(size),
+            max_size: number("MaxSize").unwrap_or(size),
+            threshold: number("Threshold").unwrap_or(2),
+            context: value(&dir, "Context"),
+            name: dir,
+        });
+    }
+    Some(Theme { inherits: list("Inherits"), dirs, roots })
+}
+
+fn hicolor_dirs() -> Vec<ThemeDir> {
+    let fixed = HICOLOR_SIZES.iter().map(|&size| ThemeDir {
+        name: format!("{0}x{0}/apps", size),
+        context: Some("Applications".into()),
+        size,
+        scale: 1,
+        kind: DirType::Threshold,
+        min_size: size,
+        max_size: size,
+        threshold: 2,
+    });
+    let scalable = ThemeDir {
+        name: "scalable/apps".into(),
+        context: Some("Applications".into()),
+        size: 128,
+        scale: 1,
+        kind: DirType::Scalable,
+        min_size: 1,
+        max_size: 512,
+        threshold: 2,
+    };
+    fixed.chain(std::iter::once(scalable)).collect()
+}
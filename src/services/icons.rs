@@ -0,0 +1,326 @@
+// Icons installed into the user's hicolor theme ($XDG_DATA_HOME/icons/hicolor), so an
+// Icon= name keeps working wherever the original file was.
+use anyhow::{anyhow, bail, Context, Result};
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::services::desktop_copies::DesktopCopies;
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::discovery;
+use crate::services::icon_theme::{self, IconResolver};
+
+// Names the icon installed for a launcher (its Icon= value); the icon is removed with the
+// last launcher that uses it (see Icons::release)
+pub const KEY_ICON: &str = "X-LauncherStudio-Icon";
+
+// Fixed-size app directories of the hicolor theme (hicolor/index.theme)
+pub const HICOLOR_SIZES: &[u32] = &[16, 22, 24, 32, 48, 64, 96, 128, 256, 512];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    Png,
+    Svg,
+    Xpm,
+}
+
+impl IconFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            IconFormat::Png => "png",
+            IconFormat::Svg => "svg",
+            IconFormat::Xpm => "xpm",
+        }
+    }
+}
+
+pub fn user_hicolor_dir() -> Option<PathBuf> {
+    discovery::data_home().map(|d| d.join("icons/hicolor"))
+}
+
+// Guess the format from the content, not the file name
+pub fn detect_format(data: &[u8]) -> Option<IconFormat> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some(IconFormat::Png);
+    }
+    let head = String::from_utf8_lossy(&data[..data.len().min(1024)]);
+    if head.contains("/* XPM */") {
+        Some(IconFormat::Xpm)
+    } else if head.contains("<svg") || (head.trim_start().starts_with("<?xml") && String::from_utf8_lossy(data).contains("<svg")) {
+        Some(IconFormat::Svg)
+    } else {
+        None
+    }
+}
+
+// Width and height of a raster icon
+pub fn pixel_size(data: &[u8], format: IconFormat) -> Option<(u32, u32)> {
+    match format {
+        // IHDR is always the first chunk
+        IconFormat::Png if data.len() >= 24 => Some((
+            u32::from_be_bytes(data[16..20].try_into().ok()?),
+            u32::from_be_bytes(data[20..24].try_into().ok()?),
+        )),
+        // First string of the array: "<width> <height> <colors> <chars per pixel>"
+        IconFormat::Xpm => {
+            let text = String::from_utf8_lossy(data);
+            let start = text.find('{')?;
+            let values = text[start..].split('"').nth(1)?;
+            let mut numbers = values.split_whitespace().map(|n| n.parse::<u32>().ok());
+            Some((numbers.next()??, numbers.next()??))
+        }
+        _ => None,
+    }
+}
+
+pub struct Icons;
+
+impl Icons {
+    // Install an image file under `name`; see install_data
+    pub fn install_file(path: &Path, name: &str) -> Result<Vec<PathBuf>> {
+        let data = fs::read(path).with_context(|| format!("Reading {}", path.display()))?;
+        Self::install_data(name, &data)
+    }
+
+    // Install icon data under `name`, replacing an icon installed before with that name.
+    // SVG goes to scalable/apps; raster images are written as PNG at every standard size up
+    // to their own (or the smallest one that holds them), scaled down as needed. Returns the
+    // files written.
+    pub fn install_data(name: &str, data: &[u8]) -> Result<Vec<PathBuf>> {
+        if name.is_empty() || name.contains('/') {
+            bail!("Invalid icon name \"{}\"", name);
+        }
+        let format = detect_format(data).ok_or_else(|| anyhow!("Not a PNG, SVG or XPM image"))?;
+        let hicolor = user_hicolor_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        let image = match format {
+            IconFormat::Png => Some(
+                image::load_from_memory_with_format(data, ImageFormat::Png).context("Could not decode the PNG image")?.to_rgba8(),
+            ),
+            IconFormat::Xpm => decode_xpm(data),
+            IconFormat::Svg => None,
+        };
+        let Some(image) = image else {
+            // SVG, or an XPM using colors decode_xpm does not know: installed unchanged
+            let size_dir = match format {
+                IconFormat::Svg => "scalable".to_string(),
+                _ => {
+                    let (w, h) = pixel_size(data, format).ok_or_else(|| anyhow!("Could not read the image size"))?;
+                    format!("{0}x{0}", fitting_size(w.max(h)))
+                }
+            };
+            let path = hicolor.join(size_dir).join("apps").join(format!("{}.{}", name, format.extension()));
+            write_file(&path, data)?;
+            let written = vec![path];
+            Self::remove_stale(name, &written)?;
+            return Ok(written);
+        };
+
+        let image = squared(image);
+        let side = image.width();
+        let mut sizes: Vec<u32> = HICOLOR_SIZES.iter().copied().filter(|s| *s <= side).collect();
+        if sizes.is_empty() {
+            sizes.push(fitting_size(side));
+        }
+        let mut written = Vec::new();
+        for size in sizes {
+            let dir = hicolor.join(format!("{0}x{0}", size)).join("apps");
+            fs::create_dir_all(&dir).with_context(|| format!("Creating directory {}", dir.display()))?;
+            let path = dir.join(format!("{}.png", name));
+            let scaled = if size == side { image.clone() } else { imageops::resize(&image, size, size, FilterType::Lanczos3) };
+            scaled.save_with_format(&path, ImageFormat::Png).with_context(|| format!("Writing {}", path.display()))?;
+            written.push(path);
+        }
+        Self::remove_stale(name, &written)?;
+        Ok(written)
+    }
+
+    // Files of an icon installed before under `name` that the new one did not replace.
+    // Only removed once the new files are in place, so a failed install keeps the old icon.
+    fn remove_stale(name: &str, written: &[PathBuf]) -> Result<()> {
+        for path in Self::installed(name).iter().filter(|p| !written.contains(p)) {
+            fs::remove_file(path).with_context(|| format!("Deleting {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    // Files of the icon `name` in the user's hicolor theme, at any size
+    pub fn installed(name: &str) -> Vec<PathBuf> {
+        let Some(hicolor) = user_hicolor_dir() else { return Vec::new() };
+        let Ok(read) = fs::read_dir(&hicolor) else { return Vec::new() };
+        let mut files: Vec<PathBuf> = read
+            .flatten()
+            .flat_map(|size_dir| {
+                let apps = size_dir.path().join("apps");
+                [IconFormat::Png, IconFormat::Svg, IconFormat::Xpm].map(|f| apps.join(format!("{}.{}", name, f.extension())))
+            })
+            .filter(|p| p.is_file())
+            .collect();
+        files.sort();
+        files
+    }
+
+    // Delete every installed file of the icon `name`; returns them
+    pub fn remove(name: &str) -> Result<Vec<PathBuf>> {
+        let files = Self::installed(name);
+        for path in &files {
+            fs::remove_file(path).with_context(|| format!("Deleting {}", path.display()))?;
+        }
+        Ok(files)
+    }
+
+    // A name for an image file that no installed or themed icon uses yet:
+    // "My Logo.png" becomes my-logo, then my-logo-2, ...
+    pub fn unused_name(path: &Path) -> String {
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let slug: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '_' { c } else { '-' }).collect();
+        let slug = slug.trim_matches('-');
+        let base = if slug.is_empty() { "launcher-icon" } else { slug };
+        let mut resolver = IconResolver::new(&icon_theme::current_theme_name());
+        (1..)
+            .map(|n| if n == 1 { base.to_string() } else { format!("{}-{}", base, n) })
+            .find(|name| Self::installed(name).is_empty() && resolver.lookup(name, 48, 1).is_none())
+            .unwrap_or_default()
+    }
+
+    // After the launcher at `path` was deleted: remove the icon installed for it (KEY_ICON)
+    // unless a launcher or a desktop copy of it still shows that icon. Returns the files
+    // removed.
+    pub fn release(entry: &DesktopEntry, path: &Path) -> Result<Vec<PathBuf>> {
+        let Some(name) = entry.extra_value(KEY_ICON).filter(|n| !n.is_empty() && entry.icon.as_deref() == Some(n)) else {
+            return Ok(Vec::new());
+        };
+        let uses_icon = |p: &Path| p != path && DesktopReader::read_from_path(p).is_ok_and(|e| e.icon.as_deref() == Some(name));
+        let in_use = DesktopReader::list_desktop_files().iter().any(|d| uses_icon(&d.path))
+            || DesktopCopies::copies_of(path).iter().any(|p| uses_icon(p));
+        if in_use {
+            return Ok(Vec::new());
+        }
+        Self::remove(name)
+    }
+}
+
+// The smallest fixed size that holds `side` pixels
+fn fitting_size(side: u32) -> u32 {
+    HICOLOR_SIZES.iter().copied().find(|s| *s >= side).unwrap_or(512)
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Creating directory {}", dir.display()))?;
+    }
+    fs::write(path, data).with_context(|| format!("Writing {}", path.display()))
+}
+
+// Theme icons are square: center other images on a transparent square. Images larger
+// than the biggest theme size are scaled down first, so a 1×N image does not need N².
+fn squared(image: RgbaImage) -> RgbaImage {
+    let largest = HICOLOR_SIZES[HICOLOR_SIZES.len() - 1];
+    let image = if image.width().max(image.height()) > largest {
+        let (w, h) = (image.width() as u64, image.height() as u64);
+        let scale = |side: u64| ((side * largest as u64 / w.max(h)) as u32).max(1);
+        imageops::resize(&image, scale(w), scale(h), FilterType::Lanczos3)
+    } else {
+        image
+    };
+    let (w, h) = image.dimensions();
+    if w == h {
+        return image;
+    }
+    let side = w.max(h);
+    let mut canvas = RgbaImage::new(side, side);
+    imageops::overlay(&mut canvas, &image, ((side - w) / 2).into(), ((side - h) / 2).into());
+    canvas
+}
+
+// XPM is C source: a string array with "<width> <height> <colors> <chars per pixel>", one
+// string per color ("<chars> c <color>") and then one per row of pixels
+fn decode_xpm(data: &[u8]) -> Option<RgbaImage> {
+    let text = String::from_utf8_lossy(data);
+    let body = &text[text.find('{')?..];
+    let strings: Vec<&str> = body.split('"').skip(1).step_by(2).collect();
+    let mut header = strings.first()?.split_whitespace().map(|n| n.parse::<u32>().ok());
+    let (width, height, colors, cpp) = (header.next()??, header.next()??, header.next()?? as usize, header.next()?? as usize);
+    if width == 0 || height == 0 || cpp == 0 || strings.len() < 1 + colors + height as usize {
+        return None;
+    }
+    // Every row must hold `width` pixels before the image is allocated: the header alone
+    // could ask for gigabytes
+    let row_len = (width as usize).checked_mul(cpp)?;
+    let rows = &strings[1 + colors..1 + colors + height as usize];
+    if rows.iter().any(|row| row.len() < row_len) {
+        return None;
+    }
+    let mut table = HashMap::new();
+    for line in &strings[1..=colors] {
+        let key = line.get(..cpp)?;
+        let words: Vec<&str> = line.get(cpp..)?.split_whitespace().collect();
+        // The color visual ("c") first, then the grayscale and monochrome ones
+        let value = ["c", "g", "g4", "m"].iter().find_map(|visual| {
+            let at = words.iter().position(|w| w == visual)?;
+            let value: Vec<&str> = words[at + 1..].iter().copied().take_while(|w| !XPM_KEYS.contains(w)).collect();
+            (!value.is_empty()).then(|| value.join(" "))
+        })?;
+        table.insert(key, xpm_color(&value)?);
+    }
+    let mut image = RgbaImage::new(width, height);
+    for (y, row) in rows.iter().enumerate() {
+        for x in 0..width as usize {
+            let pixel = table.get(row.get(x * cpp..(x + 1) * cpp)?)?;
+            image.put_pixel(x as u32, y as u32, Rgba(*pixel));
+        }
+    }
+    Some(image)
+}
+
+const XPM_KEYS: &[&str] = &["c", "g", "g4", "m", "s"];
+
+// "None", #RGB to #RRRRGGGGBBBB, and the basic X11 color names
+fn xpm_color(value: &str) -> Option<[u8; 4]> {
+    if value.eq_ignore_ascii_case("none") {
+        return Some([0, 0, 0, 0]);
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = hex.len() / 3;
+        if !(1..=4).contains(&digits) || hex.len() % 3 != 0 {
+            return None;
+        }
+        let max = (1u32 << (4 * digits)) - 1;
+        let channel = |i: usize| u32::from_str_radix(hex.get(i * digits..(i + 1) * digits)?, 16).ok().map(|v| (v * 255 / max) as u8);
+        return Some([channel(0)?, channel(1)?, channel(2)?, 255]);
+    }
+    let [r, g, b] = match value.to_lowercase().replace(' ', "").as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "red" => [255, 0, 0],
+        "green" => [0, 255, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "cyan" => [0, 255, 255],
+        "magenta" => [255, 0, 255],
+        "gray" | "grey" => [190, 190, 190],
+        _ => return None,
+    };
+    Some([r, g, b, 255])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xpm_rows_must_hold_the_declared_width() {
+        let xpm = |header: &str, row: &str| format!("static char *x[] = {{\n\"{}\",\n\". c #FF0000\",\n\"{}\"}};\n", header, row);
+        assert_eq!(decode_xpm(xpm("2 1 1 1", "..").as_bytes()).map(|i| i.dimensions()), Some((2, 1)));
+        assert!(decode_xpm(xpm("3 1 1 1", "..").as_bytes()).is_none());
+        assert!(decode_xpm(xpm("4000000000 1 1 1", "..").as_bytes()).is_none());
+    }
+
+    #[test]
+    fn large_images_are_scaled_down_before_squaring() {
+        assert_eq!(squared(RgbaImage::new(1, 20000)).dimensions(), (512, 512));
+        assert_eq!(squared(RgbaImage::new(100, 50)).dimensions(), (100, 100));
+    }
+}
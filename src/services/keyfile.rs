@@ -0,0 +1,307 @@
+// Lossless key file document (the INI dialect used by .desktop files).
+// Every source line is kept verbatim, including comments, blank lines, key order,
+// duplicate keys, unknown groups and line terminators, so that serializing a document
+// that was not modified reproduces the original bytes. Only lines touched through
+// `set`/`remove` are regenerated.
+
+#[derive(Debug, Clone)]
+enum Line {
+    // Blank line, comment, or anything that is neither a group header nor key=value
+    Other { raw: String },
+    Group { name: String, raw: String },
+    Entry { key: String, value: String, raw: String },
+}
+
+impl Line {
+    fn raw(&self) -> &str {
+        match self {
+            Line::Other { raw } | Line::Group { raw, .. } | Line::Entry { raw, .. } => raw,
+        }
+    }
+}
+
+// Read-only classification of a source line, for diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineView<'a> {
+    Blank,
+    Comment,
+    Group(&'a str),
+    // `column` is the 1-based column where the key starts
+    Entry { key: &'a str, value: &'a str, column: usize },
+    // Neither blank, comment, group header nor key=value
+    Invalid(&'a str),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KeyFile {
+    lines: Vec<Line>,
+}
+
+impl KeyFile {
+    pub fn parse(content: &str) -> Self {
+        let lines = content.split_inclusive('\n').map(parse_line).collect();
+        KeyFile { lines }
+    }
+
+    // Serialize back to text; byte-identical to the input when nothing was changed
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        for line in &self.lines {
+            s.push_str(line.raw());
+        }
+        s
+    }
+
+    // Every line with its 1-based number, trailing line terminator stripped
+    pub fn line_views(&self) -> Vec<(usize, LineView<'_>)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                let view = match l {
+                    Line::Group { name, .. } => LineView::Group(name),
+                    Line::Entry { key, value, raw } => {
+                        let indent = raw.chars().take_while(|c| c.is_whitespace()).count();
+                        LineView::Entry { key, value, column: indent + 1 }
+                    }
+                    Line::Other { raw } => {
+                        let text = raw.trim_end_matches(['\n', '\r']);
+                        let t = text.trim();
+                        if t.is_empty() {
+                            LineView::Blank
+                        } else if t.starts_with('#') || t.starts_with(';') {
+                            LineView::Comment
+                        } else {
+                            LineView::Invalid(text)
+                        }
+                    }
+                };
+                (i + 1, view)
+            })
+            .collect()
+    }
+
+    pub fn groups(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Group { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Key/value pairs of a group in file order (raw, still escaped values), with the
+    // 1-based line number of each pair. Line numbers refer to the parsed text and are
+    // only meaningful until the document is edited.
+    pub fn numbered_entries(&self, group: &str) -> Vec<(usize, &str, &str)> {
+        match self.group_range(group) {
+            Some((start, end)) => self.lines[start..end]
+                .iter()
+                .enumerate()
+                .filter_map(|(i, l)| match l {
+                    Line::Entry { key, value, .. } => Some((start + i + 1, key.as_str(), value.as_str())),
+                    _ => None,
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Set a raw value. An existing line is only rewritten when its value differs; of a
+    // duplicated key, readers use the last copy, so that one is rewritten and the earlier
+    // ones are dropped. A new key is inserted after the last entry of its group, a new
+    // group is appended.
+    pub fn set(&mut self, group: &str, key: &str, value: &str) {
+        let Some((start, end)) = self.group_range(group) else {
+            self.append_group(group);
+            self.lines.push(Line::Entry { key: key.to_string(), value: value.to_string(), raw: format!("{}={}\n", key, value) });
+            return;
+        };
+        let copies: Vec<usize> = (start..end).filter(|&i| matches!(&self.lines[i], Line::Entry { key: k, .. } if k == key)).collect();
+        if let Some((&last, earlier)) = copies.split_last() {
+            if let Line::Entry { value: v, raw, .. } = &mut self.lines[last]
+                && v != value
+            {
+                let eol = line_ending(raw);
+                *raw = format!("{}={}{}", key, value, eol);
+                *v = value.to_string();
+                for &i in earlier.iter().rev() {
+                    self.lines.remove(i);
+                }
+            }
+            return;
+        }
+        let insert_at = self.lines[start..end]
+            .iter()
+            .rposition(|l| matches!(l, Line::Entry { .. } | Line::Group { .. }))
+            .map(|i| start + i + 1)
+            .unwrap_or(end);
+        self.ensure_terminated(insert_at);
+        self.lines.insert(insert_at, Line::Entry { key: key.to_string(), value: value.to_string(), raw: format!("{}={}\n", key, value) });
+    }
+
+    // Remove every occurrence of a key in a group
+    pub fn remove(&mut self, group: &str, key: &str) {
+        if let Some((start, end)) = self.group_range(group) {
+            let mut i = start;
+            let mut end = end;
+            while i < end {
+                if matches!(&self.lines[i], Line::Entry { key: k, .. } if k == key) {
+                    self.lines.remove(i);
+                    end -= 1;
+                } else {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    // Remove a group header and everything up to the next group
+    pub fn remove_group(&mut self, group: &str) {
+        if let Some((start, end)) = self.group_range(group) {
+            let header = start - 1;
+            self.lines.drain(header..end);
+        }
+    }
+
+    // Remove lines by their 1-based number in the parsed text
+    pub fn remove_lines(&mut self, numbers: &[usize]) {
+        let mut i = 0;
+        self.lines.retain(|_| {
+            i += 1;
+            !numbers.contains(&i)
+        });
+    }
+
+    // Range of lines belonging to the first group with this name (header excluded)
+    fn group_range(&self, group: &str) -> Option<(usize, usize)> {
+        let header = self.lines.iter().position(|l| matches!(l, Line::Group { name, .. } if name == group))?;
+        let end = self.lines[header + 1..]
+            .iter()
+            .position(|l| matches!(l, Line::Group { .. }))
+            .map(|i| header + 1 + i)
+            .unwrap_or(self.lines.len());
+        Some((header + 1, end))
+    }
+
+    fn append_group(&mut self, group: &str) {
+        let len = self.lines.len();
+        self.ensure_terminated(len);
+        if self.lines.last().map(|l| !l.raw().trim().is_empty()).unwrap_or(false) {
+            self.lines.push(Line::Other { raw: "\n".into() });
+        }
+        self.lines.push(Line::Group { name: group.to_string(), raw: format!("[{}]\n", group) });
+    }
+
+    // Make sure the line before `index` ends with a newline before inserting after it
+    fn ensure_terminated(&mut self, index: usize) {
+        if index == 0 {
+            return;
+        }
+        let raw = match &mut self.lines[index - 1] {
+            Line::Other { raw } | Line::Group { raw, .. } | Line::Entry { raw, .. } => raw,
+        };
+        if !raw.ends_with('\n') {
+            raw.push('\n');
+        }
+    }
+}
+
+fn parse_line(raw: &str) -> Line {
+    let line = raw.trim_end_matches(['\n', '\r']).trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+        return Line::Other { raw: raw.to_string() };
+    }
+    if line.starts_with('[') && line.ends_with(']') {
+        return Line::Group { name: line[1..line.len() - 1].to_string(), raw: raw.to_string() };
+    }
+    match line.split_once('=') {
+        Some((k, v)) if !k.trim().is_empty() => Line::Entry {
+            key: k.trim().to_string(),
+            value: v.trim().to_string(),
+            raw: raw.to_string(),
+        },
+        _ => Line::Other { raw: raw.to_string() },
+    }
+}
+
+fn line_ending(raw: &str) -> &'static str {
+    if raw.ends_with("\r\n") {
+        "\r\n"
+    } else if raw.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyFile;
+    use crate::services::desktop_file;
+
+    // Hand-maintained files: comments, blank lines, unknown groups, localized and
+    // duplicate keys, CRLF line endings, no final newline, escapes
+    const FIXTURES: &[(&str, &str)] = &[
+        ("firefox", include_str!("../../tests/fixtures/roundtrip/firefox.desktop")),
+        ("duplicates", include_str!("../../tests/fixtures/roundtrip/duplicates.desktop")),
+        ("crlf", include_str!("../../tests/fixtures/roundtrip/crlf.desktop")),
+        ("escapes", include_str!("../../tests/fixtures/roundtrip/escapes.desktop")),
+    ];
+
+    #[test]
+    fn untouched_documents_are_byte_identical() {
+        for (name, content) in FIXTURES {
+            assert_eq!(KeyFile::parse(content).to_text(), *content, "{}", name);
+        }
+    }
+
+    #[test]
+    fn merging_the_unchanged_entry_is_byte_identical() {
+        for (name, content) in FIXTURES {
+            let (entry, _) = desktop_file::parse_with_diagnostics(content);
+            let mut doc = KeyFile::parse(content);
+            desktop_file::merge_entry(&mut doc, &entry);
+            assert_eq!(doc.to_text(), *content, "{}", name);
+        }
+    }
+
+    #[test]
+    fn serializing_the_unchanged_entry_is_byte_identical() {
+        for (name, content) in FIXTURES {
+            let Ok(entry) = desktop_file::parse(content) else { continue };
+            assert_eq!(desktop_file::serialize(&entry, Some(content)), *content, "{}", name);
+        }
+    }
+
+    #[test]
+    fn editing_a_duplicated_key_changes_the_value_readers_use() {
+        let content = "[Desktop Entry]\nType=Application\nName=Old\nExec=tool\nName=Old2\n";
+        let (mut entry, _) = desktop_file::parse_with_diagnostics(content);
+        assert_eq!(entry.name, "Old2");
+        entry.name = "New".into();
+        let mut doc = KeyFile::parse(content);
+        desktop_file::merge_entry(&mut doc, &entry);
+        assert_eq!(doc.to_text(), "[Desktop Entry]\nType=Application\nExec=tool\nName=New\n");
+        assert_eq!(desktop_file::parse_with_diagnostics(&doc.to_text()).0.name, "New");
+    }
+
+    #[test]
+    fn editing_one_key_leaves_every_other_line_alone() {
+        let content = FIXTURES[0].1;
+        let (mut entry, _) = desktop_file::parse_with_diagnostics(content);
+        entry.exec = "firefox --new-instance %u".into();
+        let mut doc = KeyFile::parse(content);
+        desktop_file::merge_entry(&mut doc, &entry);
+        assert_eq!(doc.to_text(), content.replace("Exec=firefox %u\n", "Exec=firefox --new-instance %u\n"));
+    }
+
+    #[test]
+    fn set_keeps_line_endings_and_terminates_the_last_line() {
+        let mut doc = KeyFile::parse("[Desktop Entry]\r\nName=A\r\nExec=a");
+        doc.set("Desktop Entry", "Name", "B");
+        doc.set("Desktop Entry", "Icon", "b");
+        assert_eq!(doc.to_text(), "[Desktop Entry]\r\nName=B\r\nExec=a\nIcon=b\n");
+    }
+}
@@ -0,0 +1,194 @@
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::exec::{self, Expansion};
+use crate::domain::validation::Severity;
+use anyhow::{anyhow, bail, Context, Result};
+use std::env;
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Terminals tried for Terminal=true, with the option that runs a command
+const TERMINALS: &[(&str, &[&str])] = &[
+    ("x-terminal-emulator", &["-e"]),
+    ("gnome-terminal", &["--"]),
+    ("kgx", &["--"]),
+    ("ptyxis", &["--"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &["--"]),
+    ("foot", &[]),
+    ("xterm", &["-e"]),
+];
+
+// How long a stopped launch gets to exit on SIGTERM before it is killed
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+pub enum LaunchEvent {
+    Stdout(String),
+    Stderr(String),
+    Exited(ExitStatus),
+    Failed(String),
+}
+
+// A running test launch. Output arrives on `events`; the last event is Exited or Failed.
+pub struct LaunchHandle {
+    pub pid: u32,
+    pub argv: Vec<String>,
+    pub events: Receiver<LaunchEvent>,
+    child: Arc<Mutex<Child>>,
+}
+
+impl LaunchHandle {
+    // Ask the whole process group to terminate, then kill what is left after a grace period.
+    // The entry runs as the leader of its own group, so the group ID is its pid.
+    pub fn stop(&self) {
+        let pgid = self.pid as libc::pid_t;
+        if !signal_group(&self.child, pgid, libc::SIGTERM) {
+            return;
+        }
+        let child = self.child.clone();
+        thread::spawn(move || {
+            thread::sleep(STOP_GRACE);
+            signal_group(&child, pgid, libc::SIGKILL);
+        });
+    }
+}
+
+pub struct Launcher;
+
+impl Launcher {
+    // The argv the entry runs with, terminal wrapper included
+    pub fn command_line(entry: &DesktopEntry, targets: &[String], location: Option<&Path>) -> Result<Vec<String>> {
+        if entry.type_field != "Application" {
+            bail!("Only Type=Application entries can be launched");
+        }
+        let (line, issues) = exec::parse(&entry.exec);
+        let errors: Vec<String> = issues.into_iter().filter(|i| i.severity == Severity::Error).map(|i| i.message).collect();
+        if !errors.is_empty() {
+            bail!("Exec is invalid: {}", errors.join("; "));
+        }
+        if let Some(try_exec) = entry.try_exec.as_deref().map(str::trim).filter(|t| !t.is_empty())
+            && find_program(try_exec).is_none()
+        {
+            bail!("TryExec \"{}\" is not an installed executable, menus would hide this launcher", try_exec);
+        }
+        let location = location.map(|p| p.to_string_lossy().into_owned());
+        let ctx = Expansion { targets, icon: entry.icon.as_deref(), name: &entry.name, location: location.as_deref() };
+        let argv = exec::expand(&line, &ctx);
+        let Some(program) = argv.first() else { bail!("Exec expands to an empty command") };
+        if find_program(program).is_none() {
+            bail!("Program \"{}\" was not found in PATH", program);
+        }
+        if !entry.terminal {
+            return Ok(argv);
+        }
+        let (terminal, run_opt) = TERMINALS
+            .iter()
+            .find(|(name, _)| find_program(name).is_some())
+            .ok_or_else(|| anyhow!("Terminal=true but no terminal emulator was found"))?;
+        let mut wrapped: Vec<String> = vec![terminal.to_string()];
+        wrapped.extend(run_opt.iter().map(|s| s.to_string()));
+        wrapped.extend(argv);
+        Ok(wrapped)
+    }
+
+    // Start the entry in its own process group, streaming stdout/stderr back
+    pub fn spawn(entry: &DesktopEntry, targets: &[String], location: Option<&Path>) -> Result<LaunchHandle> {
+        let argv = Self::command_line(entry, targets, location)?;
+        let mut cmd = Command::new(&argv[0]);
+        cmd.args(&argv[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0);
+        if let Some(dir) = entry.path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            if !Path::new(dir).is_dir() {
+                bail!("Path \"{}\" is not a directory", dir);
+            }
+            cmd.current_dir(dir);
+        }
+        if let Some(location) = location {
+            // Same variable GLib sets for the programs it launches
+            cmd.env("GIO_LAUNCHED_DESKTOP_FILE", location);
+        }
+        let mut child = cmd.spawn().with_context(|| format!("Starting {}", argv[0]))?;
+        let pid = child.id();
+        let (tx, rx) = mpsc::channel();
+        let readers = [
+            child.stdout.take().map(|out| forward(out, tx.clone(), LaunchEvent::Stdout)),
+            child.stderr.take().map(|err| forward(err, tx.clone(), LaunchEvent::Stderr)),
+        ];
+        let child = Arc::new(Mutex::new(child));
+        let waited = child.clone();
+        thread::spawn(move || {
+            let status = loop {
+                let polled = waited.lock().map(|mut c| c.try_wait());
+                match polled {
+                    Ok(Ok(Some(status))) => break Ok(status),
+                    Ok(Ok(None)) => thread::sleep(Duration::from_millis(100)),
+                    Ok(Err(e)) => break Err(e.to_string()),
+                    Err(_) => break Err("process state lost".to_string()),
+                }
+            };
+            // Drain the pipes before reporting the exit
+            for reader in readers.into_iter().flatten() {
+                let _ = reader.join();
+            }
+            let _ = tx.send(match status {
+                Ok(status) => LaunchEvent::Exited(status),
+                Err(e) => LaunchEvent::Failed(e),
+            });
+        });
+        Ok(LaunchHandle { pid, argv, events: rx, child })
+    }
+}
+
+// Signal the group led by `child` unless the leader has exited. Holding the lock keeps the
+// waiting thread from reaping the leader meanwhile: while it is unreaped, its pid (and so
+// the group ID) cannot be reused by an unrelated process. Returns whether it was sent.
+fn signal_group(child: &Mutex<Child>, pgid: libc::pid_t, signal: libc::c_int) -> bool {
+    let Ok(mut child) = child.lock() else { return false };
+    if !matches!(child.try_wait(), Ok(None)) {
+        return false;
+    }
+    unsafe { libc::killpg(pgid, signal) };
+    true
+}
+
+fn forward<R: Read + Send + 'static>(pipe: R, tx: Sender<LaunchEvent>, wrap: fn(String) -> LaunchEvent) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        // Lines are read as bytes: output that is not UTF-8 is shown lossily instead of
+        // ending the reads and leaving the pipe full
+        let mut reader = BufReader::new(pipe);
+        let mut buf = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+            while matches!(buf.last(), Some(b'\n' | b'\r')) {
+                buf.pop();
+            }
+            // Keep draining after the panel went away, so the child never blocks on a write
+            let _ = tx.send(wrap(String::from_utf8_lossy(&buf).into_owned()));
+            buf.clear();
+        }
+    })
+}
+
+// Absolute paths are checked directly, bare names are looked up in PATH
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path).map(|dir| dir.join(program)).find(|p| is_executable(p))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
@@ -0,0 +1,48 @@
+// Reads the shared-mime-info database from the mime directory of every XDG data dir
+// (~/.local/share/mime included), so types registered by user packages count too.
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::domain::mime::MimeDatabase;
+use crate::services::discovery;
+
+static INSTALLED: OnceLock<MimeDatabase> = OnceLock::new();
+
+pub struct MimeDb;
+
+impl MimeDb {
+    pub fn load() -> MimeDatabase {
+        let mut db = MimeDatabase::default();
+        for dir in mime_dirs() {
+            let mut packages: Vec<PathBuf> = fs::read_dir(dir.join("packages"))
+                .map(|read| read.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "xml")).collect())
+                .unwrap_or_default();
+            packages.sort();
+            for package in packages {
+                if let Ok(xml) = fs::read_to_string(&package) {
+                    db.add_package(&xml);
+                }
+            }
+            let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap_or_default();
+            db.add_globs2(&read("globs2"));
+            db.add_aliases(&read("aliases"));
+            db.add_subclasses(&read("subclasses"));
+        }
+        db
+    }
+
+    // The database of this system, read on first use and kept for the rest of the run
+    pub fn load_installed() -> &'static MimeDatabase {
+        INSTALLED.get_or_init(Self::load)
+    }
+}
+
+// Lowest precedence first; the database is a union, so the order only matters for
+// descriptions
+fn mime_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = discovery::data_dirs().into_iter().rev().map(|d| d.join("mime")).collect();
+    dirs.extend(discovery::data_home().map(|d| d.join("mime")));
+    dirs.retain(|d| d.is_dir());
+    dirs
+}
@@ -0,0 +1,119 @@
+// mimeinfo.cache as update-desktop-database (desktop-file-utils) writes it: under
+// [MIME Cache], every MIME type listed in MimeType= by a launcher below an applications
+// directory, with the desktop-file IDs that handle it. Types are sorted; IDs follow the
+// order the launchers were found in.
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::discovery::{self, AppDir, Source};
+
+pub const FILE_NAME: &str = "mimeinfo.cache";
+
+pub struct MimeCache;
+
+impl MimeCache {
+    // Rebuild the cache of one applications directory; returns how many types it lists
+    pub fn rebuild(dir: &Path) -> Result<usize> {
+        let mut handlers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        // The source only matters for classifying launchers, not for the cache
+        for found in discovery::discover_in(&[AppDir { path: dir.to_path_buf(), source: Source::User }]) {
+            // Launchers that do not parse are skipped, as update-desktop-database does
+            let Ok(entry) = DesktopReader::read_from_path(&found.path) else { continue };
+            if entry.extra_value("Hidden").map(str::trim) == Some("true") {
+                continue;
+            }
+            for mime_type in entry.mime_type.iter().map(|m| m.trim()).filter(|m| is_valid_type(m)) {
+                let ids = handlers.entry(mime_type.to_string()).or_default();
+                if !ids.contains(&found.id) {
+                    ids.push(found.id.clone());
+                }
+            }
+        }
+        let mut text = String::from("[MIME Cache]\n");
+        for (mime_type, ids) in &handlers {
+            text.push_str(&format!("{}={};\n", mime_type, ids.join(";")));
+        }
+        // Replace the file in one step so readers never see half of it
+        fs::create_dir_all(dir).with_context(|| format!("Creating directory {}", dir.display()))?;
+        let path = dir.join(FILE_NAME);
+        let tmp = dir.join(format!(".{}.tmp", FILE_NAME));
+        fs::write(&tmp, text).with_context(|| format!("Writing {}", tmp.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o644))?;
+        }
+        fs::rename(&tmp, &path).with_context(|| format!("Replacing {}", path.display()))?;
+        Ok(handlers.len())
+    }
+
+    // After a launcher at `path` was written or deleted: rebuild the cache of the
+    // applications directory holding it. Returns that directory, None for launchers
+    // elsewhere (autostart, the desktop).
+    pub fn refresh_for(path: &Path) -> Result<Option<PathBuf>> {
+        let Some(dir) = discovery::application_dirs().into_iter().map(|d| d.path).find(|d| path.starts_with(d)) else {
+            return Ok(None);
+        };
+        Self::rebuild(&dir)?;
+        Ok(Some(dir))
+    }
+}
+
+// type/subtype with nothing else, as update-desktop-database accepts them
+fn is_valid_type(mime_type: &str) -> bool {
+    let valid_part = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?=".contains(c));
+    mime_type.split_once('/').is_some_and(|(t, s)| valid_part(t) && valid_part(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_env;
+
+    fn launcher(name: &str, extra: &str) -> String {
+        format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n{}", name, name.to_lowercase(), extra)
+    }
+
+    #[test]
+    fn types_are_sorted_and_ids_terminated() {
+        let env = test_env::isolated("mime-cache-sorted");
+        env.write("data/applications/viewer.desktop", &launcher("Viewer", "MimeType=text/plain;image/png;\n"));
+        env.write("data/applications/editor.desktop", &launcher("Editor", "MimeType=text/plain;bad type;text/;\n"));
+        let dir = env.data_home().join("applications");
+        assert_eq!(MimeCache::rebuild(&dir).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.join(FILE_NAME)).unwrap(),
+            "[MIME Cache]\nimage/png=viewer.desktop;\ntext/plain=editor.desktop;viewer.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn hidden_launchers_are_left_out() {
+        let env = test_env::isolated("mime-cache-hidden");
+        env.write("data/applications/gone.desktop", &launcher("Gone", "MimeType=text/plain;\nHidden=true\n"));
+        env.write("data/applications/kept.desktop", &launcher("Kept", "MimeType=text/plain;\nNoDisplay=true\n"));
+        let dir = env.data_home().join("applications");
+        MimeCache::rebuild(&dir).unwrap();
+        assert_eq!(fs::read_to_string(dir.join(FILE_NAME)).unwrap(), "[MIME Cache]\ntext/plain=kept.desktop;\n");
+    }
+
+    #[test]
+    fn launchers_in_subdirectories_use_their_desktop_file_id() {
+        let env = test_env::isolated("mime-cache-subdirs");
+        env.write("data/applications/vendor/tools/app.desktop", &launcher("App", "MimeType=text/x-log;\n"));
+        let dir = env.data_home().join("applications");
+        assert_eq!(MimeCache::refresh_for(&dir.join("vendor/tools/app.desktop")).unwrap(), Some(dir.clone()));
+        assert_eq!(fs::read_to_string(dir.join(FILE_NAME)).unwrap(), "[MIME Cache]\ntext/x-log=vendor-tools-app.desktop;\n");
+    }
+
+    #[test]
+    fn launchers_outside_applications_dirs_need_no_cache() {
+        let env = test_env::isolated("mime-cache-elsewhere");
+        let path = env.write("config/autostart/app.desktop", &launcher("App", "MimeType=text/plain;\n"));
+        assert_eq!(MimeCache::refresh_for(&path).unwrap(), None);
+        assert!(!env.config_home().join("autostart").join(FILE_NAME).exists());
+    }
+}
@@ -0,0 +1,229 @@
+// Default applications per the MIME Applications Associations spec. mimeapps.list files
+// are read from $XDG_CONFIG_HOME, $XDG_CONFIG_DIRS, $XDG_DATA_HOME/applications and
+// $XDG_DATA_DIRS/applications, each preceded by <desktop>-mimeapps.list for every desktop
+// in $XDG_CURRENT_DESKTOP. Changes go to ~/.config/mimeapps.list and are edited in place,
+// so unrelated lines stay as they were.
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::escape::{join_list, split_list};
+use crate::services::discovery;
+use crate::services::keyfile::KeyFile;
+
+pub const GROUP_DEFAULT: &str = "Default Applications";
+pub const GROUP_ADDED: &str = "Added Associations";
+pub const GROUP_REMOVED: &str = "Removed Associations";
+
+// Launchers that can open a type, generated by update-desktop-database
+const CACHE_GROUP: &str = "MIME Cache";
+
+#[derive(Debug, Clone)]
+pub struct Handler {
+    pub id: String, // desktop-file ID
+    pub path: PathBuf,
+}
+
+// $XDG_CURRENT_DESKTOP, lowercased as used in file names
+pub fn current_desktops() -> Vec<String> {
+    env::var("XDG_CURRENT_DESKTOP")
+        .map(|v| v.split(':').filter(|s| !s.is_empty()).map(str::to_lowercase).collect())
+        .unwrap_or_default()
+}
+
+// Every mimeapps.list location, most important first
+pub fn list_paths() -> Vec<PathBuf> {
+    let desktops = current_desktops();
+    let mut dirs: Vec<PathBuf> = discovery::config_home().into_iter().chain(discovery::config_dirs()).collect();
+    dirs.extend(discovery::user_applications_dir());
+    dirs.extend(discovery::data_dirs().into_iter().map(|d| d.join("applications")));
+    let mut paths = Vec::new();
+    for dir in dirs {
+        paths.extend(desktops.iter().map(|d| dir.join(format!("{}-mimeapps.list", d))));
+        paths.push(dir.join("mimeapps.list"));
+    }
+    let mut seen = HashSet::new();
+    paths.retain(|p| seen.insert(p.clone()));
+    paths
+}
+
+// The desktop-file ID of a launcher inside one of the applications dirs
+pub fn desktop_id(path: &Path) -> Option<String> {
+    discovery::application_dirs().iter().find_map(|d| path.starts_with(&d.path).then(|| discovery::desktop_file_id(&d.path, path)).flatten())
+}
+
+pub struct MimeApps {
+    lists: Vec<KeyFile>,                 // existing mimeapps.list files, in precedence order
+    caches: Vec<KeyFile>,                // mimeinfo.cache of each applications dir
+    installed: HashMap<String, PathBuf>, // visible launcher of each desktop-file ID
+}
+
+impl MimeApps {
+    pub fn load() -> Self {
+        let read = |p: PathBuf| fs::read_to_string(p).ok().map(|t| KeyFile::parse(&t));
+        let lists = list_paths().into_iter().filter_map(read).collect();
+        let caches = discovery::application_dirs().into_iter().filter_map(|d| read(d.path.join("mimeinfo.cache"))).collect();
+        let installed = discovery::discover().into_iter().filter(|d| d.shadowed_by.is_none()).map(|d| (d.id, d.path)).collect();
+        MimeApps { lists, caches, installed }
+    }
+
+    // The launcher that opens `mime_type`: the first installed one under Default
+    // Applications, else the most preferred association (Added Associations, then the
+    // launchers listing the type) that no more important file removed
+    pub fn default_for(&self, mime_type: &str) -> Option<Handler> {
+        let handler = |id: &String| self.installed.get(id).map(|path| Handler { id: id.clone(), path: path.clone() });
+        if let Some(found) = self.lists.iter().flat_map(|l| values(l, GROUP_DEFAULT, mime_type)).find_map(|id| handler(&id)) {
+            return Some(found);
+        }
+        let mut removed: HashSet<String> = HashSet::new();
+        for list in &self.lists {
+            if let Some(found) = values(list, GROUP_ADDED, mime_type).iter().filter(|id| !removed.contains(*id)).find_map(handler) {
+                return Some(found);
+            }
+            removed.extend(values(list, GROUP_REMOVED, mime_type));
+        }
+        self.caches.iter().flat_map(|c| values(c, CACHE_GROUP, mime_type)).filter(|id| !removed.contains(id)).find_map(|id| handler(&id))
+    }
+
+    // Make `id` the default for `types` in ~/.config/mimeapps.list, as xdg-mime does: the
+    // Default Applications entry, first place among the Added Associations, and no longer
+    // removed. A desktop-specific list in ~/.config that names other defaults for these
+    // types would win, so it is updated as well. Returns the files written.
+    pub fn set_default(id: &str, types: &[String]) -> Result<Vec<PathBuf>> {
+        let config = discovery::config_home().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        let mut written = Vec::new();
+        for desktop in current_desktops() {
+            let path = config.join(format!("{}-mimeapps.list", desktop));
+            let Ok(text) = fs::read_to_string(&path) else { continue };
+            let mut doc = KeyFile::parse(&text);
+            let overridden: Vec<&String> = types.iter().filter(|t| !values(&doc, GROUP_DEFAULT, t).is_empty()).collect();
+            if overridden.is_empty() {
+                continue;
+            }
+            for t in overridden {
+                associate(&mut doc, id, t);
+            }
+            write(&path, &doc)?;
+            written.push(path);
+        }
+        let path = config.join("mimeapps.list");
+        let mut doc = KeyFile::parse(&fs::read_to_string(&path).unwrap_or_default());
+        for t in types {
+            associate(&mut doc, id, t);
+        }
+        write(&path, &doc)?;
+        written.push(path);
+        Ok(written)
+    }
+}
+
+fn associate(doc: &mut KeyFile, id: &str, mime_type: &str) {
+    doc.set(GROUP_DEFAULT, mime_type, &join_list(&[id.to_string()]));
+    let mut added = values(doc, GROUP_ADDED, mime_type);
+    added.retain(|a| a != id);
+    added.insert(0, id.to_string());
+    doc.set(GROUP_ADDED, mime_type, &join_list(&added));
+    let mut removed = values(doc, GROUP_REMOVED, mime_type);
+    if removed.iter().any(|r| r == id) {
+        removed.retain(|r| r != id);
+        if removed.is_empty() {
+            doc.remove(GROUP_REMOVED, mime_type);
+        } else {
+            doc.set(GROUP_REMOVED, mime_type, &join_list(&removed));
+        }
+    }
+}
+
+// Desktop-file IDs listed for a type in a group
+fn values(doc: &KeyFile, group: &str, mime_type: &str) -> Vec<String> {
+    doc.numbered_entries(group)
+        .into_iter()
+        .find(|(_, k, _)| k.trim() == mime_type)
+        .map(|(_, _, v)| split_list(v.trim()).into_iter().filter(|id| !id.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+fn write(path: &Path, doc: &KeyFile) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Creating directory {}", dir.display()))?;
+    }
+    fs::write(path, doc.to_text()).with_context(|| format!("Writing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_env;
+
+    const LIST: &str = "\
+# kept as written
+[Default Applications]
+text/plain=old.desktop
+image/png=photos.desktop
+
+[Added Associations]
+text/plain=other.desktop;old.desktop;
+
+[Removed Associations]
+text/plain=editor.desktop;broken.desktop;
+";
+
+    #[test]
+    fn set_default_keeps_unrelated_lines() {
+        let env = test_env::isolated("mimeapps-default");
+        let path = env.write("config/mimeapps.list", LIST);
+        let types = ["text/plain".to_string(), "text/markdown".to_string()];
+        assert_eq!(MimeApps::set_default("editor.desktop", &types).unwrap(), [env.config_home().join("mimeapps.list")]);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\
+# kept as written
+[Default Applications]
+text/plain=editor.desktop;
+image/png=photos.desktop
+text/markdown=editor.desktop;
+
+[Added Associations]
+text/plain=editor.desktop;other.desktop;old.desktop;
+text/markdown=editor.desktop;
+
+[Removed Associations]
+text/plain=broken.desktop;
+"
+        );
+    }
+
+    #[test]
+    fn set_default_updates_a_desktop_list_that_would_win() {
+        let env = test_env::isolated("mimeapps-desktop");
+        // Only ever changed while the test environment's lock is held
+        unsafe { env::set_var("XDG_CURRENT_DESKTOP", "GNOME:Unity") };
+        let gnome = env.write("config/gnome-mimeapps.list", "[Default Applications]\ntext/plain=old.desktop\n");
+        let unity = env.write("config/unity-mimeapps.list", "[Default Applications]\nimage/png=photos.desktop\n");
+        let written = MimeApps::set_default("editor.desktop", &["text/plain".to_string()]).unwrap();
+        assert_eq!(written, [gnome.clone(), env.config_home().join("mimeapps.list")]);
+        assert_eq!(
+            fs::read_to_string(&gnome).unwrap(),
+            "[Default Applications]\ntext/plain=editor.desktop;\n\n[Added Associations]\ntext/plain=editor.desktop;\n"
+        );
+        assert_eq!(fs::read_to_string(&unity).unwrap(), "[Default Applications]\nimage/png=photos.desktop\n");
+    }
+
+    #[test]
+    fn default_for_follows_the_precedence_of_the_spec() {
+        let env = test_env::isolated("mimeapps-lookup");
+        for id in ["editor", "other", "viewer"] {
+            env.write(format!("data/applications/{}.desktop", id), &format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n", id, id));
+        }
+        env.write("data/applications/mimeinfo.cache", "[MIME Cache]\ntext/plain=viewer.desktop;\nimage/png=viewer.desktop;other.desktop;\n");
+        // The default is not installed, the first added one was removed by a more important list
+        env.write("config/mimeapps.list", "[Default Applications]\ntext/plain=missing.desktop\n[Removed Associations]\ntext/plain=other.desktop;\nimage/png=viewer.desktop;\n");
+        env.write("system-config/mimeapps.list", "[Added Associations]\ntext/plain=other.desktop;editor.desktop;\n");
+        let apps = MimeApps::load();
+        assert_eq!(apps.default_for("text/plain").unwrap().id, "editor.desktop");
+        assert_eq!(apps.default_for("image/png").unwrap().id, "other.desktop");
+        assert!(apps.default_for("text/html").is_none());
+    }
+}
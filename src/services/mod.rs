@@ -1,2 +1,22 @@
 pub mod desktop_writer;
 pub mod desktop_reader;
+pub mod keyfile;
+pub mod desktop_file;
+pub mod launcher;
+pub mod discovery;
+pub mod overrides;
+pub mod autostart;
+pub mod desktop_copies;
+pub mod provisioning;
+pub mod templates;
+pub mod squashfs;
+pub mod icons;
+pub mod appimage;
+pub mod flatpak;
+pub mod browsers;
+pub mod icon_theme;
+pub mod mime;
+pub mod mimeapps;
+pub mod mime_cache;
+#[cfg(test)]
+mod test_env;
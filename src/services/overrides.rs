@@ -0,0 +1,56 @@
+// Overriding a system launcher: a copy in the user applications dir with the same
+// desktop-file ID takes precedence over the original (see services::discovery).
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::discovery::{self, DiscoveredEntry, Source};
+use crate::services::mime_cache::MimeCache;
+
+pub struct Overrides;
+
+impl Overrides {
+    // Copy a system entry to the user dir under the same ID; returns the new file
+    pub fn create(path: &Path) -> Result<PathBuf> {
+        let entry = find(path)?;
+        if entry.source == Source::User {
+            bail!("{} is already in the user applications directory", path.display());
+        }
+        // Only launchers that parse can be edited afterwards
+        DesktopReader::read_from_path(path)?;
+        let content = fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+        let dir = discovery::user_applications_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        fs::create_dir_all(&dir).context("Creating applications directory")?;
+        let dest = dir.join(&entry.id);
+        if dest.exists() {
+            bail!("An override already exists: {}", dest.display());
+        }
+        fs::write(&dest, content).with_context(|| format!("Writing {}", dest.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&dest, fs::Permissions::from_mode(0o644))?;
+        }
+        MimeCache::refresh_for(&dest).ok();
+        Ok(dest)
+    }
+
+    // Delete a user override; returns the system file that is in effect again
+    pub fn revert(path: &Path) -> Result<PathBuf> {
+        let entry = find(path)?;
+        if !entry.is_override() {
+            bail!("{} does not override a system launcher", path.display());
+        }
+        fs::remove_file(path).with_context(|| format!("Deleting {}", path.display()))?;
+        MimeCache::refresh_for(path).ok();
+        Ok(entry.shadows[0].clone())
+    }
+}
+
+fn find(path: &Path) -> Result<DiscoveredEntry> {
+    discovery::discover()
+        .into_iter()
+        .find(|d| d.path == path)
+        .ok_or_else(|| anyhow!("{} is not in an applications directory", path.display()))
+}
@@ -0,0 +1,277 @@
+// Declarative provisioning: a directory of launcher manifests (.desktop key files or
+// .json/.toml launcher documents) is the desired state of the user applications dir.
+// Each manifest `<stem>.<ext>` becomes `<stem>.desktop`, marked with KEY_MANAGED set to
+// the ID the directory declares in OWNER_FILE, and only launchers carrying that marker
+// are updated or pruned. The ID stays the same wherever the directory is checked out.
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::desktop_writer::{self, DesktopWriter, KEY_MANAGED};
+use crate::services::discovery::{self, Source};
+
+// One line in the manifest directory naming who owns its launchers, e.g. "team-tools"
+pub const OWNER_FILE: &str = ".launcher-owner";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+    Unchanged,
+}
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            Action::Unchanged => "unchanged",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub action: Action,
+    pub path: PathBuf,             // launcher in the user applications dir
+    pub manifest: Option<PathBuf>, // None for Delete
+    entry: Option<DesktopEntry>,   // desired content, marker included
+}
+
+// What apply would do. `problems` (unreadable or invalid manifests, files owned by
+// someone else) block apply, so a broken manifest never prunes its launcher.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub owner: String,
+    pub operations: Vec<Operation>,
+    pub problems: Vec<String>,
+}
+
+impl Plan {
+    pub fn changes(&self) -> usize {
+        self.operations.iter().filter(|o| o.action != Action::Unchanged).count()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for op in &self.operations {
+            match &op.manifest {
+                Some(m) => writeln!(f, "{:<9} {} (from {})", op.action.label(), op.path.display(), m.display())?,
+                None => writeln!(f, "{:<9} {}", op.action.label(), op.path.display())?,
+            }
+        }
+        for problem in &self.problems {
+            writeln!(f, "problem   {}", problem)?;
+        }
+        write!(f, "{} change(s), {} problem(s)", self.changes(), self.problems.len())
+    }
+}
+
+pub struct Provisioning;
+
+impl Provisioning {
+    // Compare the manifests in `dir` with the user applications dir; nothing is written
+    pub fn plan(dir: &Path) -> Result<Plan> {
+        let owner = read_owner(dir)?;
+        let apps_dir = discovery::user_applications_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        let mut plan = Plan { owner: owner.clone(), operations: Vec::new(), problems: Vec::new() };
+
+        let mut manifests: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("Reading {}", dir.display()))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("desktop" | "json" | "toml")))
+            .collect();
+        manifests.sort();
+
+        let mut wanted: Vec<PathBuf> = Vec::new();
+        for manifest in manifests {
+            let Some(stem) = manifest.file_stem().map(|s| s.to_string_lossy().into_owned()) else { continue };
+            let path = apps_dir.join(format!("{}.desktop", stem));
+            if wanted.contains(&path) {
+                plan.problems.push(format!("{}: another manifest also provides {}.desktop", manifest.display(), stem));
+                continue;
+            }
+            wanted.push(path.clone());
+            let desired = match read_manifest(&manifest) {
+                Ok(entry) => desktop_writer::managed(&entry, &owner),
+                Err(e) => {
+                    plan.problems.push(format!("{:#}", e));
+                    continue;
+                }
+            };
+            if let Err(e) = desired.check() {
+                plan.problems.push(format!("{}: {}", manifest.display(), e));
+                continue;
+            }
+            let action = if !path.exists() {
+                Action::Create
+            } else {
+                match DesktopReader::read_from_path(&path) {
+                    Ok(current) if current.extra_value(KEY_MANAGED) != Some(owner.as_str()) => {
+                        plan.problems.push(format!("{} exists and is not managed by {}", path.display(), owner));
+                        continue;
+                    }
+                    Ok(current) if current.to_ini_string() == desired.to_ini_string() => Action::Unchanged,
+                    Ok(_) => Action::Update,
+                    Err(e) => {
+                        plan.problems.push(format!("{:#}", e));
+                        continue;
+                    }
+                }
+            };
+            plan.operations.push(Operation { action, path, manifest: Some(manifest), entry: Some(desired) });
+        }
+
+        // Launchers this directory created earlier whose manifest is gone
+        for found in DesktopReader::list_desktop_files() {
+            if found.source != Source::User || wanted.contains(&found.path) {
+                continue;
+            }
+            let owned = DesktopReader::read_from_path(&found.path).is_ok_and(|e| e.extra_value(KEY_MANAGED) == Some(owner.as_str()));
+            if owned {
+                plan.operations.push(Operation { action: Action::Delete, path: found.path, manifest: None, entry: None });
+            }
+        }
+        Ok(plan)
+    }
+
+    // Carry out a plan; returns the number of files created, updated or deleted
+    pub fn apply(plan: &Plan) -> Result<usize> {
+        if !plan.problems.is_empty() {
+            bail!("The manifest has {} problem(s), nothing was applied:\n{}", plan.problems.len(), plan.problems.join("\n"));
+        }
+        for op in &plan.operations {
+            match (op.action, &op.entry) {
+                (Action::Create | Action::Update, Some(entry)) => {
+                    DesktopWriter::write_managed(entry, &op.path, &plan.owner)?;
+                }
+                (Action::Delete, _) => {
+                    DesktopWriter::delete(&op.path)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(plan.changes())
+    }
+}
+
+fn read_owner(dir: &Path) -> Result<String> {
+    let path = dir.join(OWNER_FILE);
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Reading {} (the manifest directory must name its owner there)", path.display()))?;
+    let owner = text.trim();
+    if owner.is_empty() || owner.chars().any(char::is_control) {
+        bail!("{} must hold the owner ID on a single line", path.display());
+    }
+    Ok(owner.to_string())
+}
+
+fn read_manifest(path: &Path) -> Result<DesktopEntry> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("desktop") => DesktopReader::read_from_path(path),
+        _ => DesktopReader::read_document(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_env::{self, TestEnv};
+
+    fn launcher(name: &str) -> String {
+        format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n", name, name.to_lowercase())
+    }
+
+    fn manifests(env: &TestEnv, owner: &str) -> PathBuf {
+        env.write("team/.launcher-owner", &format!("{}\n", owner));
+        env.write("team/editor.desktop", &launcher("Editor"));
+        env.write("team/viewer.json", r#"{"name": "Viewer", "exec": "viewer %f"}"#);
+        env.root.join("team")
+    }
+
+    fn actions(plan: &Plan) -> Vec<(Action, String)> {
+        plan.operations.iter().map(|o| (o.action, o.path.file_name().unwrap().to_string_lossy().into_owned())).collect()
+    }
+
+    fn apps(env: &TestEnv) -> PathBuf {
+        env.data_home().join("applications")
+    }
+
+    #[test]
+    fn a_second_apply_has_nothing_to_do() {
+        let env = test_env::isolated("provisioning-idempotent");
+        let dir = manifests(&env, "team-tools");
+        let plan = Provisioning::plan(&dir).unwrap();
+        assert_eq!(actions(&plan), [(Action::Create, "editor.desktop".into()), (Action::Create, "viewer.desktop".into())]);
+        assert_eq!(Provisioning::apply(&plan).unwrap(), 2);
+        let written = DesktopReader::read_from_path(&apps(&env).join("viewer.desktop")).unwrap();
+        assert_eq!(written.extra_value(KEY_MANAGED), Some("team-tools"));
+
+        let again = Provisioning::plan(&dir).unwrap();
+        assert_eq!(again.changes(), 0, "{}", again);
+        assert!(again.problems.is_empty());
+    }
+
+    #[test]
+    fn a_drifted_launcher_is_updated() {
+        let env = test_env::isolated("provisioning-drift");
+        let dir = manifests(&env, "team-tools");
+        Provisioning::apply(&Provisioning::plan(&dir).unwrap()).unwrap();
+        let path = apps(&env).join("editor.desktop");
+        let drifted = fs::read_to_string(&path).unwrap().replace("Exec=editor", "Exec=editor --changed");
+        fs::write(&path, drifted).unwrap();
+
+        let plan = Provisioning::plan(&dir).unwrap();
+        assert_eq!(actions(&plan), [(Action::Update, "editor.desktop".into()), (Action::Unchanged, "viewer.desktop".into())]);
+        Provisioning::apply(&plan).unwrap();
+        assert_eq!(DesktopReader::read_from_path(&path).unwrap().exec, "editor");
+    }
+
+    #[test]
+    fn only_launchers_of_this_owner_are_pruned() {
+        let env = test_env::isolated("provisioning-prune");
+        let dir = manifests(&env, "team-tools");
+        Provisioning::apply(&Provisioning::plan(&dir).unwrap()).unwrap();
+        let mine = apps(&env).join("viewer.desktop");
+        let unmanaged = env.write("data/applications/mine.desktop", &launcher("Mine"));
+        let theirs = env.write("data/applications/theirs.desktop", &format!("{}{}=other-team\n", launcher("Theirs"), KEY_MANAGED));
+        fs::remove_file(dir.join("viewer.json")).unwrap();
+
+        let plan = Provisioning::plan(&dir).unwrap();
+        assert_eq!(actions(&plan), [(Action::Unchanged, "editor.desktop".into()), (Action::Delete, "viewer.desktop".into())]);
+        Provisioning::apply(&plan).unwrap();
+        assert!(!mine.exists());
+        assert!(unmanaged.exists() && theirs.exists());
+    }
+
+    #[test]
+    fn any_problem_blocks_the_whole_apply() {
+        let env = test_env::isolated("provisioning-problems");
+        let dir = manifests(&env, "team-tools");
+        env.write("team/broken.json", r#"{"name": "Broken", "colour": "red"}"#);
+        // An existing launcher the manifests do not own
+        env.write("data/applications/viewer.desktop", &launcher("Viewer"));
+
+        let plan = Provisioning::plan(&dir).unwrap();
+        assert_eq!(plan.problems.len(), 2, "{}", plan);
+        assert!(Provisioning::apply(&plan).is_err());
+        assert!(!apps(&env).join("editor.desktop").exists());
+    }
+
+    #[test]
+    fn the_folder_must_name_its_owner() {
+        let env = test_env::isolated("provisioning-owner");
+        let dir = manifests(&env, "team-tools");
+        fs::write(dir.join(OWNER_FILE), "\n").unwrap();
+        assert!(Provisioning::plan(&dir).is_err());
+        fs::remove_file(dir.join(OWNER_FILE)).unwrap();
+        assert!(Provisioning::plan(&dir).is_err());
+    }
+}
@@ -0,0 +1,429 @@
+// Minimal read-only SquashFS 4.0 reader, enough to pull single files out of an AppImage:
+// directory lookup, regular files (blocks and fragments) and symlinks, with gzip, xz and
+// zstd compression. Offsets in the image are relative to `base`, where the superblock is.
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const MAGIC: u32 = 0x7371_7368; // "hsqs"
+const METADATA_SIZE: usize = 8192;
+const NO_FRAGMENT: u32 = 0xFFFF_FFFF;
+const MAX_SYMLINKS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Dir,
+    File,
+    Symlink,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    pub name: String,
+    pub kind: Kind,
+    inode: u64, // inode reference: metadata block << 16 | offset
+}
+
+enum Inode {
+    Dir { block: u32, offset: u16, size: u32 },
+    File { blocks_start: u64, size: u64, fragment: u32, fragment_offset: u32, block_sizes: Vec<u32> },
+    Symlink { target: String },
+    Other,
+}
+
+pub struct SquashFs {
+    file: File,
+    base: u64,
+    len: u64, // bytes from `base` to the end of the file, a bound for every allocation
+    block_size: u32,
+    compression: Compression,
+    root_inode: u64,
+    inode_table: u64,
+    directory_table: u64,
+    fragment_table: u64,
+    fragment_count: u32,
+}
+
+impl SquashFs {
+    // Open the image whose superblock starts `base` bytes into `path`
+    pub fn open(path: &Path, base: u64) -> Result<SquashFs> {
+        let mut file = File::open(path).with_context(|| format!("Opening {}", path.display()))?;
+        let mut sb = [0u8; 96];
+        file.seek(SeekFrom::Start(base))?;
+        file.read_exact(&mut sb).context("Reading the SquashFS superblock")?;
+        let len = file.metadata()?.len().saturating_sub(base);
+        if u32_at(&sb, 0)? != MAGIC {
+            bail!("No SquashFS image at offset {}", base);
+        }
+        if u16_at(&sb, 28)? != 4 {
+            bail!("Unsupported SquashFS version {}.{}", u16_at(&sb, 28)?, u16_at(&sb, 30)?);
+        }
+        let block_size = u32_at(&sb, 12)?;
+        if !block_size.is_power_of_two() || !(4096..=1 << 20).contains(&block_size) {
+            bail!("Invalid SquashFS block size {}", block_size);
+        }
+        let compression = match u16_at(&sb, 20)? {
+            1 => Compression::Gzip,
+            4 => Compression::Xz,
+            6 => Compression::Zstd,
+            2 => bail!("LZMA compressed SquashFS images are not supported"),
+            3 => bail!("LZO compressed SquashFS images are not supported"),
+            5 => bail!("LZ4 compressed SquashFS images are not supported"),
+            other => bail!("Unknown SquashFS compression {}", other),
+        };
+        let (inode_table, directory_table) = (u64_at(&sb, 64)?, u64_at(&sb, 72)?);
+        let (fragment_table, fragment_count) = (u64_at(&sb, 80)?, u32_at(&sb, 16)?);
+        // Tables must start inside the image; without fragments the fragment table is unset
+        for (table, start) in [("inode", inode_table), ("directory", directory_table)]
+            .into_iter()
+            .chain((fragment_count > 0).then_some(("fragment", fragment_table)))
+        {
+            if start >= len {
+                bail!("The {} table starts past the end of the SquashFS image", table);
+            }
+        }
+        Ok(SquashFs {
+            file,
+            base,
+            len,
+            block_size,
+            compression,
+            root_inode: u64_at(&sb, 32)?,
+            inode_table,
+            directory_table,
+            fragment_table,
+            fragment_count,
+        })
+    }
+
+    // Entries of the directory at `path` ("" or "/" for the root)
+    pub fn read_dir(&mut self, path: &str) -> Result<Vec<DirEntry>> {
+        let inode = self.resolve(path)?;
+        self.list(inode)
+    }
+
+    // Contents of the regular file at `path`, following symlinks inside the image
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        let inode = self.resolve(path)?;
+        match self.inode(inode)? {
+            Inode::File { blocks_start, size, fragment, fragment_offset, block_sizes } => {
+                self.file_data(blocks_start, size, fragment, fragment_offset, &block_sizes)
+            }
+            _ => bail!("{} is not a regular file", path),
+        }
+    }
+
+    // Inode reference of `path`, following symlinks
+    fn resolve(&mut self, path: &str) -> Result<u64> {
+        let mut pending: Vec<String> = components(path).into_iter().rev().collect();
+        let mut stack: Vec<u64> = vec![self.root_inode]; // directories from the root down
+        let mut links = 0;
+        while let Some(name) = pending.pop() {
+            if name == ".." {
+                if stack.len() > 1 {
+                    stack.pop();
+                }
+                continue;
+            }
+            let dir = *stack.last().expect("root is never popped");
+            let entry = self.list(dir)?.into_iter().find(|e| e.name == name).ok_or_else(|| anyhow!("{} not found in the image", path))?;
+            if entry.kind == Kind::Symlink {
+                links += 1;
+                if links > MAX_SYMLINKS {
+                    bail!("Too many symbolic links resolving {}", path);
+                }
+                let Inode::Symlink { target } = self.inode(entry.inode)? else { bail!("Broken symlink {}", name) };
+                if target.starts_with('/') {
+                    stack.truncate(1);
+                }
+                pending.extend(components(&target).into_iter().rev());
+                continue;
+            }
+            stack.push(entry.inode);
+        }
+        Ok(*stack.last().expect("root is never popped"))
+    }
+
+    fn list(&mut self, dir: u64) -> Result<Vec<DirEntry>> {
+        let Inode::Dir { block, offset, size } = self.inode(dir)? else { bail!("Not a directory") };
+        // The stored size counts the implicit "." and ".." entries
+        let len = (size as usize).saturating_sub(3);
+        let data = self.metadata(add(self.directory_table, block as u64)?, offset as usize, len)?;
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos + 12 <= data.len() {
+            let count = u32_at(&data, pos)? as usize + 1;
+            let start = u32_at(&data, pos + 4)? as u64;
+            pos += 12;
+            for _ in 0..count {
+                if pos + 8 > data.len() {
+                    bail!("Truncated directory listing");
+                }
+                let inode_offset = u16_at(&data, pos)? as u64;
+                let kind = match u16_at(&data, pos + 4)? {
+                    1 | 8 => Kind::Dir,
+                    2 | 9 => Kind::File,
+                    3 | 10 => Kind::Symlink,
+                    _ => Kind::Other,
+                };
+                let name_len = u16_at(&data, pos + 6)? as usize + 1;
+                let name = data.get(pos + 8..pos + 8 + name_len).ok_or_else(|| anyhow!("Truncated directory entry"))?;
+                entries.push(DirEntry { name: String::from_utf8_lossy(name).into_owned(), kind, inode: (start << 16) | inode_offset });
+                pos += 8 + name_len;
+            }
+        }
+        Ok(entries)
+    }
+
+    fn inode(&mut self, reference: u64) -> Result<Inode> {
+        let block = add(self.inode_table, reference >> 16)?;
+        let offset = (reference & 0xFFFF) as usize;
+        let kind = u16_at(&self.metadata(block, offset, 16)?, 0)?;
+        // Size of the fixed part after the common 16-byte header
+        let fixed = match kind {
+            1 | 2 => 16,
+            8 => 24,
+            9 => 40,
+            3 | 10 => 8,
+            _ => return Ok(Inode::Other),
+        };
+        let head = self.metadata(block, offset, 16 + fixed)?;
+        let body = &head[16..];
+        Ok(match kind {
+            1 => Inode::Dir { block: u32_at(body, 0)?, offset: u16_at(body, 10)?, size: u16_at(body, 8)? as u32 },
+            8 => Inode::Dir { block: u32_at(body, 8)?, offset: u16_at(body, 18)?, size: u32_at(body, 4)? },
+            2 | 9 => {
+                let (blocks_start, size, fragment, fragment_offset) = if kind == 9 {
+                    (u64_at(body, 0)?, u64_at(body, 8)?, u32_at(body, 28)?, u32_at(body, 32)?)
+                } else {
+                    (u32_at(body, 0)? as u64, u32_at(body, 12)? as u64, u32_at(body, 4)?, u32_at(body, 8)?)
+                };
+                let full = size / self.block_size as u64;
+                let count = if fragment == NO_FRAGMENT && size % self.block_size as u64 != 0 { full + 1 } else { full };
+                // The block list is stored in the image, so it cannot be longer than the image
+                if count.saturating_mul(4) > self.len {
+                    bail!("File of {} bytes does not fit in the image", size);
+                }
+                let count = count as usize;
+                let all = self.metadata(block, offset, 16 + fixed + count * 4)?;
+                let block_sizes = (0..count).map(|i| u32_at(&all, 16 + fixed + i * 4)).collect::<Result<_>>()?;
+                Inode::File { blocks_start, size, fragment, fragment_offset, block_sizes }
+            }
+            _ => {
+                let len = u32_at(body, 4)? as usize;
+                let all = self.metadata(block, offset, 16 + fixed + len)?;
+                Inode::Symlink { target: String::from_utf8_lossy(&all[16 + fixed..]).into_owned() }
+            }
+        })
+    }
+
+    fn file_data(&mut self, blocks_start: u64, size: u64, fragment: u32, fragment_offset: u32, block_sizes: &[u32]) -> Result<Vec<u8>> {
+        // Reserve no more than the image holds: a corrupt size must not allocate gigabytes
+        let mut out = Vec::with_capacity(size.min(self.len) as usize);
+        let mut pos = blocks_start;
+        for &entry in block_sizes {
+            let stored = entry & 0x00FF_FFFF;
+            let want = (size - out.len() as u64).min(self.block_size as u64) as usize;
+            if stored == 0 {
+                out.resize(out.len() + want, 0); // sparse block
+                continue;
+            }
+            let data = self.data_block(pos, entry)?;
+            out.extend_from_slice(&data[..want.min(data.len())]);
+            pos = add(pos, stored as u64)?;
+        }
+        if fragment != NO_FRAGMENT && (out.len() as u64) < size {
+            if fragment >= self.fragment_count {
+                bail!("Fragment {} out of range", fragment);
+            }
+            let index = fragment as u64 * 16;
+            let mut ptr = [0u8; 8];
+            self.read_at(add(self.fragment_table, index / METADATA_SIZE as u64 * 8)?, &mut ptr)?;
+            let entry = self.metadata(u64::from_le_bytes(ptr), (index % METADATA_SIZE as u64) as usize, 16)?;
+            let data = self.data_block(u64_at(&entry, 0)?, u32_at(&entry, 8)?)?;
+            let start = fragment_offset as usize;
+            let end = (size - out.len() as u64).checked_add(start as u64).ok_or_else(|| anyhow!("Fragment shorter than expected"))?;
+            out.extend_from_slice(data.get(start..end as usize).ok_or_else(|| anyhow!("Fragment shorter than expected"))?);
+        }
+        Ok(out)
+    }
+
+    // A data or fragment block; bit 24 of the stored size marks it uncompressed
+    fn data_block(&mut self, pos: u64, entry: u32) -> Result<Vec<u8>> {
+        let stored = entry & 0x00FF_FFFF;
+        if stored > self.block_size || stored as u64 > self.len {
+            bail!("Data block of {} bytes is larger than a block", stored);
+        }
+        let mut raw = vec![0u8; stored as usize];
+        self.read_at(pos, &mut raw)?;
+        if entry & 0x0100_0000 != 0 { Ok(raw) } else { self.decompress(&raw) }
+    }
+
+    // `len` bytes of the metadata stream starting at block `pos`, `offset` bytes in
+    fn metadata(&mut self, mut pos: u64, offset: usize, len: usize) -> Result<Vec<u8>> {
+        if (offset + len) as u64 > self.len {
+            bail!("Metadata of {} bytes does not fit in the image", len);
+        }
+        let mut out = Vec::new();
+        while out.len() < offset + len {
+            let mut header = [0u8; 2];
+            self.read_at(pos, &mut header)?;
+            let header = u16::from_le_bytes(header);
+            let mut raw = vec![0u8; (header & 0x7FFF) as usize];
+            self.read_at(add(pos, 2)?, &mut raw)?;
+            pos = add(pos, 2 + raw.len() as u64)?;
+            let block = if header & 0x8000 != 0 { raw } else { self.decompress(&raw)? };
+            if block.is_empty() {
+                bail!("Empty metadata block");
+            }
+            out.extend_from_slice(&block);
+        }
+        Ok(out[offset..offset + len].to_vec())
+    }
+
+    fn decompress(&self, raw: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match self.compression {
+            Compression::Gzip => {
+                flate2::read::ZlibDecoder::new(raw).read_to_end(&mut out).context("Decompressing (zlib)")?;
+            }
+            Compression::Xz => {
+                lzma_rs::xz_decompress(&mut std::io::BufReader::new(raw), &mut out).map_err(|e| anyhow!("Decompressing (xz): {:?}", e))?;
+            }
+            Compression::Zstd => {
+                let mut decoder = ruzstd::decoding::StreamingDecoder::new(raw).map_err(|e| anyhow!("Decompressing (zstd): {}", e))?;
+                decoder.read_to_end(&mut out).context("Decompressing (zstd)")?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn read_at(&mut self, pos: u64, buf: &mut [u8]) -> Result<()> {
+        if add(pos, buf.len() as u64)? > self.len {
+            bail!("Read past the end of the SquashFS image");
+        }
+        self.file.seek(SeekFrom::Start(add(self.base, pos)?))?;
+        self.file.read_exact(buf).context("Reading the SquashFS image")
+    }
+}
+
+fn components(path: &str) -> Vec<String> {
+    path.split('/').filter(|c| !c.is_empty() && *c != ".").map(str::to_string).collect()
+}
+
+// Offsets come from the image, so their sums are checked
+fn add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or_else(|| anyhow!("SquashFS offset out of range"))
+}
+
+// Little-endian fields; reading past the end of `b` means the structure is truncated
+fn bytes_at<const N: usize>(b: &[u8], i: usize) -> Result<[u8; N]> {
+    b.get(i..i + N).and_then(|s| s.try_into().ok()).ok_or_else(|| anyhow!("Truncated SquashFS structure"))
+}
+
+fn u16_at(b: &[u8], i: usize) -> Result<u16> {
+    bytes_at(b, i).map(u16::from_le_bytes)
+}
+
+fn u32_at(b: &[u8], i: usize) -> Result<u32> {
+    bytes_at(b, i).map(u32::from_le_bytes)
+}
+
+fn u64_at(b: &[u8], i: usize) -> Result<u64> {
+    bytes_at(b, i).map(u64::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // An uncompressed image holding /hello.txt ("hello")
+    fn image() -> Vec<u8> {
+        let name = b"hello.txt";
+        let mut inodes = Vec::new();
+        // Root directory: basic dir inode, listing at the start of the directory table
+        for v in [1u16, 0o755, 0, 0] {
+            inodes.extend(v.to_le_bytes());
+        }
+        inodes.extend([0u32, 1, 0, 2].iter().flat_map(|v| v.to_le_bytes()));
+        inodes.extend(((12 + 8 + name.len() + 3) as u16).to_le_bytes());
+        inodes.extend(0u16.to_le_bytes());
+        inodes.extend(3u32.to_le_bytes());
+        // hello.txt: basic file inode, one uncompressed block
+        for v in [2u16, 0o644, 0, 0] {
+            inodes.extend(v.to_le_bytes());
+        }
+        inodes.extend([0u32, 2, 0, NO_FRAGMENT, 0, 5, 5 | 0x0100_0000].iter().flat_map(|v| v.to_le_bytes()));
+        let mut listing = Vec::new();
+        listing.extend([0u32, 0, 2].iter().flat_map(|v| v.to_le_bytes()));
+        listing.extend([32u16, 0, 2, (name.len() - 1) as u16].iter().flat_map(|v| v.to_le_bytes()));
+        listing.extend(name);
+
+        let inode_table = 96u64;
+        let directory_table = inode_table + 2 + inodes.len() as u64;
+        let data = directory_table + 2 + listing.len() as u64;
+        inodes[48..52].copy_from_slice(&(data as u32).to_le_bytes());
+
+        let mut sb = vec![0u8; 96];
+        sb[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        sb[12..16].copy_from_slice(&131072u32.to_le_bytes());
+        sb[20..22].copy_from_slice(&1u16.to_le_bytes());
+        sb[28..30].copy_from_slice(&4u16.to_le_bytes());
+        sb[64..72].copy_from_slice(&inode_table.to_le_bytes());
+        sb[72..80].copy_from_slice(&directory_table.to_le_bytes());
+        sb[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        for block in [inodes, listing] {
+            sb.extend((block.len() as u16 | 0x8000).to_le_bytes());
+            sb.extend(block);
+        }
+        sb.extend(b"hello");
+        sb
+    }
+
+    fn read(bytes: &[u8], path: &Path) -> Result<Vec<u8>> {
+        fs::write(path, bytes)?;
+        let mut fs = SquashFs::open(path, 0)?;
+        fs.read_dir("/")?;
+        fs.read_file("hello.txt")
+    }
+
+    #[test]
+    fn corrupt_images_are_errors() {
+        let path = std::env::temp_dir().join(format!("launcher-studio-squashfs-{}", std::process::id()));
+        let good = image();
+        assert_eq!(read(&good, &path).unwrap(), b"hello");
+        // Any byte set to an extreme value either still reads or fails, but never panics
+        // or allocates more than the image holds
+        for i in 0..good.len() {
+            for value in [0x00, 0x7F, 0xFF] {
+                let mut bad = good.clone();
+                bad[i] = value;
+                let _ = read(&bad, &path);
+            }
+        }
+        // Table starts and the root inode far past the end of the image
+        for field in [32, 64, 72, 80] {
+            for value in [u64::MAX, u64::MAX - 1, 1 << 63, good.len() as u64] {
+                let mut bad = good.clone();
+                bad[field..field + 8].copy_from_slice(&value.to_le_bytes());
+                let result = read(&bad, &path);
+                // Without fragments the fragment table start is never read
+                assert_eq!(result.is_ok(), field == 80, "field at {} set to {:#x}", field, value);
+            }
+        }
+        for len in 0..good.len() {
+            assert!(read(&good[..len], &path).is_err(), "truncated to {} bytes", len);
+        }
+        let _ = fs::remove_file(&path);
+    }
+}
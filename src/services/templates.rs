@@ -0,0 +1,122 @@
+// Template library: the built-in templates shipped in data/templates, followed by the
+// user's own in $XDG_DATA_HOME/launcher_studio/templates (one TOML file per template).
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::template::{Template, VariableKind};
+use crate::services::discovery;
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("appimage.toml", include_str!("../../data/templates/appimage.toml")),
+    ("terminal-script.toml", include_str!("../../data/templates/terminal-script.toml")),
+    ("python-venv.toml", include_str!("../../data/templates/python-venv.toml")),
+    ("java-jar.toml", include_str!("../../data/templates/java-jar.toml")),
+    ("wine.toml", include_str!("../../data/templates/wine.toml")),
+    ("electron-wayland.toml", include_str!("../../data/templates/electron-wayland.toml")),
+    ("web-app.toml", include_str!("../../data/templates/web-app.toml")),
+    ("folder-shortcut.toml", include_str!("../../data/templates/folder-shortcut.toml")),
+];
+
+#[derive(Debug, Clone)]
+pub struct TemplateItem {
+    pub template: Template,
+    pub path: Option<PathBuf>, // None for built-in templates
+}
+
+pub fn user_templates_dir() -> Option<PathBuf> {
+    discovery::data_home().map(|d| d.join("launcher_studio/templates"))
+}
+
+pub struct Templates;
+
+impl Templates {
+    // Built-in templates first, then user templates by file name. Templates that do not
+    // load are returned separately so the gallery can say why they are missing.
+    pub fn list() -> (Vec<TemplateItem>, Vec<String>) {
+        let mut items = Vec::new();
+        let mut problems = Vec::new();
+        for (name, text) in BUILTIN {
+            match Template::from_toml(text) {
+                Ok(template) => items.push(TemplateItem { template, path: None }),
+                Err(e) => problems.push(format!("built-in template {}: {}", name, e)),
+            }
+        }
+        let Some(dir) = user_templates_dir() else { return (items, problems) };
+        let Ok(read) = fs::read_dir(&dir) else { return (items, problems) };
+        let mut paths: Vec<PathBuf> = read.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "toml")).collect();
+        paths.sort();
+        for path in paths {
+            match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|t| Template::from_toml(&t)) {
+                Ok(template) => items.push(TemplateItem { template, path: Some(path) }),
+                Err(e) => problems.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        (items, problems)
+    }
+
+    // Fill in a template; `~/` at the start of a file or folder value means the home dir
+    pub fn instantiate(template: &Template, values: &HashMap<String, String>) -> Result<DesktopEntry> {
+        let home = directories::BaseDirs::new().map(|b| b.home_dir().to_path_buf());
+        let mut values = values.clone();
+        for v in &template.variables {
+            if !matches!(v.kind, VariableKind::File | VariableKind::Folder) {
+                continue;
+            }
+            let value = values.get(&v.id).filter(|s| !s.trim().is_empty()).cloned().or_else(|| v.default.clone());
+            if let (Some(value), Some(home)) = (value, &home) {
+                values.insert(v.id.clone(), expand_home(value.trim(), home));
+            }
+        }
+        template.instantiate(&values).map_err(|e| anyhow!(e))
+    }
+
+    // Save `template` as a user template; returns the file written
+    pub fn save(template: &Template, overwrite: bool) -> Result<PathBuf> {
+        let dir = user_templates_dir().ok_or_else(|| anyhow!("Failed to resolve XDG base directories"))?;
+        fs::create_dir_all(&dir).with_context(|| format!("Creating directory {}", dir.display()))?;
+        let stem: String = template
+            .title
+            .trim()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        if stem.trim_matches('-').is_empty() {
+            bail!("The template needs a title");
+        }
+        let path = dir.join(format!("{}.toml", stem));
+        if path.exists() && !overwrite {
+            bail!("A template named \"{}\" already exists", template.title);
+        }
+        fs::write(&path, template.to_toml()).with_context(|| format!("Writing {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn expand_home(value: &str, home: &Path) -> String {
+    match value.strip_prefix('~') {
+        Some("") => home.to_string_lossy().into_owned(),
+        Some(rest) if rest.starts_with('/') => home.join(&rest[1..]).to_string_lossy().into_owned(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::validation::{self, Severity};
+
+    #[test]
+    fn builtin_templates_load_and_fill_in() {
+        for (name, text) in BUILTIN {
+            let template = Template::from_toml(text).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            let values: HashMap<String, String> = template.variables.iter().map(|v| (v.id.clone(), format!("/opt/{}", v.id))).collect();
+            let entry = template.instantiate(&values).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            let errors: Vec<String> =
+                validation::validate_entry(&entry, None).into_iter().filter(|f| f.severity == Severity::Error).map(|f| f.to_string()).collect();
+            assert!(errors.is_empty(), "{}: {:?}", name, errors);
+        }
+    }
+}
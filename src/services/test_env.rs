@@ -0,0 +1,59 @@
+// Tests that read the XDG base directories share one process environment: each takes a
+// lock and points HOME and the XDG variables at fresh, empty directories of its own.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+pub struct TestEnv {
+    pub root: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    // $XDG_DATA_HOME
+    pub fn data_home(&self) -> PathBuf {
+        self.root.join("data")
+    }
+
+    // $XDG_CONFIG_HOME
+    pub fn config_home(&self) -> PathBuf {
+        self.root.join("config")
+    }
+
+    // Write `text` to `path` below the root, creating its directories; returns the path
+    pub fn write(&self, path: impl AsRef<Path>, text: &str) -> PathBuf {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().expect("below the root")).unwrap();
+        fs::write(&path, text).unwrap();
+        path
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+pub fn isolated(name: &str) -> TestEnv {
+    // A failed test must not fail the ones after it
+    let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let root = env::temp_dir().join(format!("launcher-studio-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for dir in ["home", "data", "config", "system-data", "system-config"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    // Only ever changed while the lock is held
+    unsafe {
+        env::set_var("HOME", root.join("home"));
+        env::set_var("XDG_DATA_HOME", root.join("data"));
+        env::set_var("XDG_CONFIG_HOME", root.join("config"));
+        env::set_var("XDG_DATA_DIRS", root.join("system-data"));
+        env::set_var("XDG_CONFIG_DIRS", root.join("system-config"));
+        env::remove_var("XDG_CURRENT_DESKTOP");
+    }
+    TestEnv { root, _lock: lock }
+}
@@ -0,0 +1,58 @@
+use gtk4::{Box as GtkBox, Button, Entry, Label, Orientation, ScrolledWindow, TextView};
+use gtk4::prelude::*;
+
+// Output of "Test Launch": sample targets, status line and the child's stdout/stderr
+pub struct LaunchPanel {
+    pub container: GtkBox,
+    pub targets_entry: Entry,
+    pub pick_btn: Button,
+    pub launch_btn: Button,
+    pub stop_btn: Button,
+    pub clear_btn: Button,
+    pub status: Label,
+    pub output: TextView,
+}
+
+pub fn build_launch_panel() -> LaunchPanel {
+    let container = GtkBox::new(Orientation::Vertical, 6);
+
+    let row = GtkBox::new(Orientation::Horizontal, 6);
+    let targets_entry = Entry::new();
+    targets_entry.set_hexpand(true);
+    targets_entry.set_placeholder_text(Some("Sample files or URLs for %f/%F/%u/%U, separated by ';'"));
+    let pick_btn = Button::with_label("Files...");
+    let launch_btn = Button::with_label("Launch");
+    launch_btn.add_css_class("suggested-action");
+    launch_btn.set_tooltip_text(Some(
+        "Run the command as the desktop would. It runs as you, with your environment and files: it is not sandboxed.",
+    ));
+    let stop_btn = Button::with_label("Stop");
+    stop_btn.set_sensitive(false);
+    let clear_btn = Button::with_label("Clear");
+    row.append(&targets_entry);
+    row.append(&pick_btn);
+    row.append(&launch_btn);
+    row.append(&stop_btn);
+    row.append(&clear_btn);
+
+    let status = Label::new(Some("Not launched"));
+    status.set_xalign(0.0);
+    status.set_selectable(true);
+
+    let output = TextView::new();
+    output.set_monospace(true);
+    output.set_editable(false);
+    output.set_cursor_visible(false);
+    let tag_table = output.buffer().tag_table();
+    let stderr_tag = gtk4::TextTag::builder().name("stderr").foreground("#c01c28").build();
+    tag_table.add(&stderr_tag);
+    let scroller = ScrolledWindow::builder().min_content_height(140).child(&output).build();
+    scroller.add_css_class("frame");
+
+    container.append(&row);
+    container.append(&status);
+    container.append(&scroller);
+    container.set_visible(false);
+
+    LaunchPanel { container, targets_entry, pick_btn, launch_btn, stop_btn, clear_btn, status, output }
+}
@@ -10,8 +10,15 @@ pub fn build_menu_bar(_app: &Application) -> PopoverMenuBar {
     // File menu
     let file_menu = Menu::new();
     file_menu.append(Some("New"), Some("app.new"));
+    file_menu.append(Some("New from Template..."), Some("app.new_from_template"));
+    file_menu.append(Some("New from Flatpak App..."), Some("app.new_from_flatpak"));
     file_menu.append(Some("Open"), Some("app.open"));
     file_menu.append(Some("Save"), Some("app.save"));
+    file_menu.append(Some("Save as Template..."), Some("app.save_as_template"));
+    file_menu.append(Some("Import..."), Some("app.import"));
+    file_menu.append(Some("Import AppImage..."), Some("app.import_appimage"));
+    file_menu.append(Some("Export..."), Some("app.export"));
+    file_menu.append(Some("Place on Desktop"), Some("app.place_on_desktop"));
     file_menu.append(Some("Refresh"), Some("app.refresh"));
     file_menu.append(Some("Quit"), Some("app.quit"));
     menu_model.append_submenu(Some("File"), &file_menu);
@@ -25,6 +32,12 @@ pub fn build_menu_bar(_app: &Application) -> PopoverMenuBar {
     let tools_menu = Menu::new();
     tools_menu.append(Some("Open System Applications"), Some("app.open_system_dir"));
     tools_menu.append(Some("Open User Applications"), Some("app.open_user_dir"));
+    tools_menu.append(Some("Override System Launcher"), Some("app.override_launcher"));
+    tools_menu.append(Some("Revert to System Version"), Some("app.revert_override"));
+    tools_menu.append(Some("Make Default for Its Types"), Some("app.make_default"));
+    tools_menu.append(Some("Update MIME Cache"), Some("app.update_mime_cache"));
+    tools_menu.append(Some("Autostart Manager"), Some("app.autostart"));
+    tools_menu.append(Some("Apply Manifest Folder..."), Some("app.apply_manifests"));
     menu_model.append_submenu(Some("Tools"), &tools_menu);
 
     // Help menu
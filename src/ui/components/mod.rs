@@ -2,6 +2,7 @@ pub mod menu_bar;
 pub mod toolbar;
 pub mod status_bar;
 pub mod sidebar;
+pub mod launch_panel;
 
 use gtk4::prelude::*;
 use gtk4::{Box as GtkBox, Entry, Label, Orientation};
@@ -4,6 +4,7 @@ use gtk4::prelude::*;
 pub struct Toolbar {
     pub container: GtkBox,
     pub btn_new: Button,
+    pub btn_template: Button,
     pub btn_open: Button,
     pub btn_save: Button,
     pub btn_refresh: Button,
@@ -18,6 +19,12 @@ pub fn build_toolbar() -> Toolbar {
     btn_new.set_child(Some(&img_new));
     btn_new.set_tooltip_text(Some("New .desktop"));
 
+    let btn_template = Button::new();
+    let img_template = Image::from_icon_name("document-new-symbolic");
+    img_template.set_pixel_size(18);
+    btn_template.set_child(Some(&img_template));
+    btn_template.set_tooltip_text(Some("New from Template"));
+
     let btn_open = Button::new();
     let img_open = Image::from_icon_name("document-open-symbolic");
     img_open.set_pixel_size(18);
@@ -37,9 +44,10 @@ pub fn build_toolbar() -> Toolbar {
     btn_refresh.set_tooltip_text(Some("Refresh"));
 
     container.append(&btn_new);
+    container.append(&btn_template);
     container.append(&btn_open);
     container.append(&btn_save);
     container.append(&btn_refresh);
 
-    Toolbar { container, btn_new, btn_open, btn_save, btn_refresh }
+    Toolbar { container, btn_new, btn_template, btn_open, btn_save, btn_refresh }
 }
@@ -1,10 +1,24 @@
 use gtk4::prelude::*;
-use gtk4::{Box as GtkBox, Orientation, Label, Entry, CheckButton, ComboBoxText, TextView, Notebook, ScrolledWindow, Button, FileChooserDialog, FileChooserAction};
+use gtk4::{Box as GtkBox, Orientation, Label, Entry, CheckButton, ComboBoxText, TextView, Notebook, ScrolledWindow, Button, FileChooserDialog, FileChooserAction, FileFilter};
 use gtk4::{EntryIconPosition};
 use gtk4::gio::File;
 use gtk4::gdk;
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
 
+use crate::domain::desktop_action::DesktopAction;
 use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::escape::{escape_string, join_list, split_list, unescape_string};
+use crate::domain::exec;
+use crate::domain::webapp::WebApp;
+use crate::services::browsers::Browsers;
+use crate::services::icon_theme::{self, IconResolver};
+use crate::services::icons::{self, Icons};
+use crate::services::desktop_reader::DesktopReader;
+use crate::services::mime::MimeDb;
+use crate::services::mimeapps::MimeApps;
+use crate::services::desktop_file::{self, Diagnostic, Severity};
 
 #[derive(Clone)]
 pub struct EntryWidgets {
@@ -19,6 +33,7 @@ pub struct EntryWidgets {
     pub startup_check: CheckButton,
     pub categories_entry: Entry,
     pub mimetype_entry: Entry,
+    pub mime_defaults_label: Label, // current default application of each type
     pub keywords_entry: Entry,
     pub onlyshowin_entry: Entry,
     pub notshowin_entry: Entry,
@@ -36,11 +51,42 @@ pub struct EntryWidgets {
     pub exec_link_box: GtkBox,
     pub exec_btn: Button,
     pub url_btn: Button,
+    // Program and arguments as the Exec line will be split
+    pub exec_tokens_row: GtkBox,
+    // Web App kind: Exec and StartupWMClass are generated from these
+    pub webapp_box: GtkBox,
+    pub webapp_url_entry: Entry,
+    pub webapp_app_id_entry: Entry,
+    pub webapp_browser_combo: ComboBoxText,
+    pub webapp_profile_entry: Entry,
+    pub webapp_class_entry: Entry,
+    pub webapp_args: Rc<RefCell<Vec<String>>>, // other browser arguments of a loaded web app
+    // [Desktop Action] groups, edited in the Actions tab
+    pub actions_list: GtkBox,
+    pub add_action_btn: Button,
+    pub action_rows: Rc<RefCell<Vec<ActionRow>>>,
+    pub action_listeners: Rc<RefCell<Vec<ChangeListener>>>,
+}
+
+pub type ChangeListener = Rc<dyn Fn()>;
+
+// Widgets of one [Desktop Action] group in the Actions tab
+#[derive(Clone)]
+pub struct ActionRow {
+    pub container: GtkBox,
+    pub id_entry: Entry,
+    pub name_entry: Entry,
+    pub icon_entry: Entry,
+    pub exec_entry: Entry,
+    pub localized_name: TextView,
+    pub extra_kv: TextView, // other keys of the group, kept as written
 }
 
 pub struct Editor {
     pub notebook: Notebook,
     pub source_view: TextView,
+    // Parse/validation problems shared by the form and the Source tab
+    pub source_status: Label,
     pub widgets: EntryWidgets,
 }
 
@@ -60,12 +106,34 @@ pub fn build_editor() -> Editor {
     advanced_box.set_margin_start(12);
     advanced_box.set_margin_end(12);
 
+    let actions_box = GtkBox::new(Orientation::Vertical, 8);
+    actions_box.set_margin_top(12);
+    actions_box.set_margin_bottom(12);
+    actions_box.set_margin_start(12);
+    actions_box.set_margin_end(12);
+
     let source_view = TextView::new();
     source_view.set_monospace(true);
     source_view.set_margin_top(12);
     source_view.set_margin_bottom(12);
     source_view.set_margin_start(12);
     source_view.set_margin_end(12);
+    source_view.set_has_tooltip(true);
+    {
+        // Underlines for parser diagnostics (see show_diagnostics)
+        let table = source_view.buffer().tag_table();
+        let error_tag = gtk4::TextTag::builder()
+            .name("diagnostic-error")
+            .underline(gtk4::pango::Underline::Error)
+            .build();
+        let warning_tag = gtk4::TextTag::builder()
+            .name("diagnostic-warning")
+            .underline(gtk4::pango::Underline::Error)
+            .underline_rgba(&gdk::RGBA::new(0.9, 0.6, 0.0, 1.0))
+            .build();
+        table.add(&error_tag);
+        table.add(&warning_tag);
+    }
 
     // Basic fields
     let type_row = GtkBox::new(Orientation::Horizontal, 8);
@@ -77,6 +145,7 @@ pub fn build_editor() -> Editor {
     type_combo.append_text("Application");
     type_combo.append_text("Link");
     type_combo.append_text("Directory");
+    type_combo.append_text("Web App");
     type_combo.set_active(Some(0));
     type_row.append(&type_label);
     type_row.append(&type_combo);
@@ -118,6 +187,70 @@ pub fn build_editor() -> Editor {
     exec_app_box.append(&exec_btn);
     exec_row.append(&exec_app_box);
 
+    // Tokenized view of Exec, aligned under the entry
+    let exec_tokens_row = GtkBox::new(Orientation::Horizontal, 8);
+    let exec_tokens_spacer = Label::new(None);
+    exec_tokens_spacer.set_width_chars(18);
+    let exec_tokens = Label::new(None);
+    exec_tokens.set_xalign(0.0);
+    exec_tokens.set_wrap(true);
+    exec_tokens.set_selectable(true);
+    exec_tokens.add_css_class("dim-label");
+    exec_tokens_row.append(&exec_tokens_spacer);
+    exec_tokens_row.append(&exec_tokens);
+    exec_entry.connect_changed({
+        let exec_tokens = exec_tokens.clone();
+        move |e| exec_tokens.set_markup(&exec_markup(&e.text()))
+    });
+
+    // Web App rows, shown under Exec (which then only displays the generated command)
+    let webapp_box = GtkBox::new(Orientation::Vertical, 8);
+    let (webapp_url_row, webapp_url_entry) = crate::ui::components::labeled_entry("Address*");
+    webapp_url_entry.set_placeholder_text(Some("https://"));
+    let (webapp_app_id_row, webapp_app_id_entry) = crate::ui::components::labeled_entry("Browser App ID");
+    webapp_app_id_entry.set_tooltip_text(Some("Set by Chrome or Chromium for web apps installed from the browser; replaces the address"));
+    let webapp_browser_row = GtkBox::new(Orientation::Horizontal, 8);
+    let webapp_browser_lbl = Label::new(Some("Browser*"));
+    webapp_browser_lbl.set_halign(gtk4::Align::End);
+    webapp_browser_lbl.set_xalign(1.0);
+    webapp_browser_lbl.set_width_chars(18);
+    let webapp_browser_combo = ComboBoxText::new();
+    webapp_browser_combo.set_hexpand(true);
+    for browser in Browsers::detect() {
+        webapp_browser_combo.append(Some(&browser.command), &browser.name);
+    }
+    webapp_browser_combo.set_active(Some(0));
+    webapp_browser_row.append(&webapp_browser_lbl);
+    webapp_browser_row.append(&webapp_browser_combo);
+    let (webapp_profile_row, webapp_profile_entry) = crate::ui::components::labeled_entry("Isolated Profile");
+    webapp_profile_entry.set_placeholder_text(Some("Optional folder for a browser profile of its own"));
+    let webapp_profile_btn = Button::with_label("Select...");
+    webapp_profile_btn.connect_clicked({
+        let profile_entry_c = webapp_profile_entry.clone();
+        move |_| {
+            let dialog = FileChooserDialog::new(Some("Select Profile Folder"), None::<&gtk4::ApplicationWindow>, FileChooserAction::SelectFolder, &[("Cancel", gtk4::ResponseType::Cancel), ("Select", gtk4::ResponseType::Accept)]);
+            let profile_entry_c2 = profile_entry_c.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == gtk4::ResponseType::Accept
+                    && let Some(path) = d.file().and_then(|f| f.path())
+                {
+                    profile_entry_c2.set_text(&path.to_string_lossy());
+                }
+                d.close();
+            });
+            dialog.show();
+        }
+    });
+    webapp_profile_row.append(&webapp_profile_btn);
+    let (webapp_class_row, webapp_class_entry) = crate::ui::components::labeled_entry("Window Class");
+    webapp_class_entry.set_tooltip_text(Some("Passed as --class and written to StartupWMClass"));
+    webapp_box.append(&webapp_url_row);
+    webapp_box.append(&webapp_app_id_row);
+    webapp_box.append(&webapp_browser_row);
+    webapp_box.append(&webapp_profile_row);
+    webapp_box.append(&webapp_class_row);
+    webapp_box.set_visible(false);
+
     // Link/Folder input box: URL entry + Select button
     let exec_link_box = GtkBox::new(Orientation::Horizontal, 6);
     exec_link_box.set_hexpand(true);
@@ -134,43 +267,18 @@ pub fn build_editor() -> Editor {
     icon_entry.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing"));
     icon_row.append(&icon_lbl);
     icon_row.append(&icon_entry);
-    // File chooser for Icon
+    // Icon browser and file chooser (connected once all fields exist, see below)
+    let icon_browse_btn = Button::with_label("Browse...");
+    icon_browse_btn.set_tooltip_text(Some("Choose from the icons of the current theme"));
+    icon_row.append(&icon_browse_btn);
     let icon_btn = Button::with_label("Select...");
-    icon_btn.connect_clicked({
-        let icon_entry_c = icon_entry.clone();
-        move |_| {
-            let dialog = FileChooserDialog::new(Some("Select Icon"), None::<&gtk4::ApplicationWindow>, FileChooserAction::Open, &[("Cancel", gtk4::ResponseType::Cancel), ("Open", gtk4::ResponseType::Accept)]);
-            let icon_entry_c2 = icon_entry_c.clone();
-            dialog.connect_response(move |d, resp| {
-                if resp == gtk4::ResponseType::Accept {
-                    if let Some(file) = d.file() { if let Some(path) = file.path() {
-                        let txt = path.to_string_lossy().to_string();
-                        icon_entry_c2.set_text(&txt);
-                    } }
-                }
-                d.close();
-            });
-            dialog.show();
-        }
-    });
     icon_row.append(&icon_btn);
+    let icon_install_check = CheckButton::with_label("Install into theme");
+    icon_install_check.set_active(true);
+    icon_install_check.set_tooltip_text(Some("Copy the chosen image into your hicolor icon theme at the standard sizes and refer to it by name; it is removed again when the launcher is deleted"));
+    icon_row.append(&icon_install_check);
 
-    {
-        icon_entry.connect_changed(move |e| {
-            let txt = e.text().to_string();
-            if txt.trim().is_empty() {
-                e.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing"));
-            } else if txt.contains('/') {
-                let f = File::for_path(&txt);
-                match gdk::Texture::from_file(&f) {
-                    Ok(tex) => e.set_icon_from_paintable(EntryIconPosition::Primary, Some(&tex)),
-                    Err(_) => e.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing")),
-                }
-            } else {
-                e.set_icon_from_icon_name(EntryIconPosition::Primary, Some(&txt));
-            }
-        });
-    }
+    icon_entry.connect_changed(update_icon_preview);
 
     // Checkbuttons (aligned)
     let terminal_check = CheckButton::with_label("Run in Terminal");
@@ -203,6 +311,19 @@ pub fn build_editor() -> Editor {
     // List-like entries
     let (categories_row, categories_entry) = crate::ui::components::labeled_entry("Categories (;) ");
     let (mimetype_row, mimetype_entry) = crate::ui::components::labeled_entry("MimeType (;) ");
+    let mimetype_btn = Button::with_label("Choose...");
+    mimetype_btn.set_tooltip_text(Some("Pick from the shared MIME database"));
+    mimetype_row.append(&mimetype_btn);
+    let mime_defaults_row = GtkBox::new(Orientation::Horizontal, 8);
+    let mime_defaults_spacer = Label::new(None);
+    mime_defaults_spacer.set_width_chars(18);
+    mime_defaults_row.append(&mime_defaults_spacer);
+    let mime_defaults_label = Label::new(None);
+    mime_defaults_label.set_xalign(0.0);
+    mime_defaults_label.set_wrap(true);
+    mime_defaults_label.add_css_class("caption");
+    mime_defaults_label.add_css_class("dim-label");
+    mime_defaults_row.append(&mime_defaults_label);
     let (keywords_row, keywords_entry) = crate::ui::components::labeled_entry("Keywords (;) ");
     let (onlyshowin_row, onlyshowin_entry) = crate::ui::components::labeled_entry("OnlyShowIn (;) ");
     let (notshowin_row, notshowin_entry) = crate::ui::components::labeled_entry("NotShowIn (;) ");
@@ -313,6 +434,8 @@ pub fn build_editor() -> Editor {
     basic_box.append(&type_row);
     basic_box.append(&name_row);
     basic_box.append(&exec_row);
+    basic_box.append(&exec_tokens_row);
+    basic_box.append(&webapp_box);
     basic_box.append(&icon_row);
     basic_box.append(&terminal_row);
 
@@ -323,6 +446,7 @@ pub fn build_editor() -> Editor {
     advanced_box.append(&startup_row);
     advanced_box.append(&categories_row);
     advanced_box.append(&mimetype_row);
+    advanced_box.append(&mime_defaults_row);
     advanced_box.append(&keywords_row);
     advanced_box.append(&onlyshowin_row);
     advanced_box.append(&notshowin_row);
@@ -355,18 +479,41 @@ pub fn build_editor() -> Editor {
     extra_kv_sw.set_child(Some(&extra_kv));
     advanced_box.append(&extra_kv_sw);
 
+    // Actions tab: one frame per [Desktop Action] group
+    let actions_help = Label::new(Some("Each action needs an ID listed in Actions (Advanced tab) and a Name."));
+    actions_help.set_wrap(true);
+    actions_help.set_xalign(0.0);
+    let actions_list = GtkBox::new(Orientation::Vertical, 12);
+    let add_action_btn = Button::with_label("Add Action");
+    add_action_btn.set_halign(gtk4::Align::Start);
+    actions_box.append(&actions_help);
+    actions_box.append(&actions_list);
+    actions_box.append(&add_action_btn);
+
     // Assemble notebook
     let basic_scroll = ScrolledWindow::builder().hexpand(true).vexpand(true).build();
     basic_scroll.set_child(Some(&basic_box));
     let adv_scroll = ScrolledWindow::builder().hexpand(true).vexpand(true).build();
     adv_scroll.set_child(Some(&advanced_box));
+    let actions_scroll = ScrolledWindow::builder().hexpand(true).vexpand(true).build();
+    actions_scroll.set_child(Some(&actions_box));
     let source_scroll = ScrolledWindow::builder().hexpand(true).vexpand(true).build();
     source_scroll.add_css_class("frame");
     source_scroll.set_child(Some(&source_view));
+    let source_status = Label::new(None);
+    source_status.set_xalign(0.0);
+    source_status.set_wrap(true);
+    source_status.add_css_class("error");
+    source_status.set_margin_start(12);
+    source_status.set_margin_bottom(6);
+    let source_box = GtkBox::new(Orientation::Vertical, 6);
+    source_box.append(&source_scroll);
+    source_box.append(&source_status);
 
     notebook.append_page(&basic_scroll, Some(&Label::new(Some("Basic"))));
     notebook.append_page(&adv_scroll, Some(&Label::new(Some("Advanced"))));
-    notebook.append_page(&source_scroll, Some(&Label::new(Some("Source"))));
+    notebook.append_page(&actions_scroll, Some(&Label::new(Some("Actions"))));
+    notebook.append_page(&source_box, Some(&Label::new(Some("Source"))));
 
     let widgets = EntryWidgets {
         type_combo,
@@ -380,6 +527,7 @@ pub fn build_editor() -> Editor {
         startup_check,
         categories_entry,
         mimetype_entry,
+        mime_defaults_label,
         keywords_entry,
         onlyshowin_entry,
         notshowin_entry,
@@ -396,8 +544,105 @@ pub fn build_editor() -> Editor {
         exec_link_box,
         exec_btn,
         url_btn,
+        exec_tokens_row,
+        webapp_box,
+        webapp_url_entry,
+        webapp_app_id_entry,
+        webapp_browser_combo,
+        webapp_profile_entry,
+        webapp_class_entry,
+        webapp_args: Rc::new(RefCell::new(Vec::new())),
+        actions_list,
+        add_action_btn,
+        action_rows: Rc::new(RefCell::new(Vec::new())),
+        action_listeners: Rc::new(RefCell::new(Vec::new())),
     };
 
+    // Add a blank action and reference it from Actions=
+    {
+        let w2 = widgets.clone();
+        widgets.add_action_btn.connect_clicked(move |_| {
+            // First action-N no row and no Actions= entry uses yet
+            let mut names = split_semicolon(&w2.actions_entry);
+            let taken: Vec<String> = w2.action_rows.borrow().iter().map(|r| r.id_entry.text().trim().to_string()).chain(names.iter().cloned()).collect();
+            let id = (1..).map(|n| format!("action-{}", n)).find(|id| !taken.contains(id)).unwrap_or_default();
+            append_action_row(&w2, &DesktopAction { id: id.clone(), ..Default::default() });
+            names.push(id);
+            w2.actions_entry.set_text(&list_text(&names));
+        });
+    }
+
+    // Pick MIME types from the database; aliases become the types they stand for
+    {
+        let w2 = widgets.clone();
+        mimetype_btn.connect_clicked(move |btn| {
+            let Some(parent) = btn.root().and_downcast::<gtk4::Window>() else { return };
+            let mimetype_entry = w2.mimetype_entry.clone();
+            crate::ui::windows::mime_window::show_mime_window(&parent, &split_semicolon(&w2.mimetype_entry), move |types| {
+                mimetype_entry.set_text(&list_text(&types));
+            });
+        });
+    }
+
+    // Show who opens each type now
+    {
+        let w2 = widgets.clone();
+        widgets.mimetype_entry.connect_changed(move |_| update_mime_defaults(&w2));
+    }
+
+    // Pick a theme icon, suggestions for this launcher first
+    {
+        let w2 = widgets.clone();
+        icon_browse_btn.connect_clicked(move |btn| {
+            let Some(parent) = btn.root().and_downcast::<gtk4::Window>() else { return };
+            let hints = icon_theme::icon_hints(&read_entry(&w2));
+            let icon_entry = w2.icon_entry.clone();
+            crate::ui::windows::icon_window::show_icon_window(&parent, &hints, move |name| icon_entry.set_text(name));
+        });
+    }
+
+    // Pick an icon file: installed into the hicolor theme under a name of its own (reusing
+    // the name this launcher installed before) or referred to by path
+    {
+        let w2 = widgets.clone();
+        icon_btn.connect_clicked(move |btn| {
+            let parent = btn.root().and_downcast::<gtk4::Window>();
+            let dialog = FileChooserDialog::new(Some("Select Icon"), parent.as_ref(), FileChooserAction::Open, &[("Cancel", gtk4::ResponseType::Cancel), ("Open", gtk4::ResponseType::Accept)]);
+            let filter = FileFilter::new();
+            filter.set_name(Some("Images (PNG, SVG, XPM)"));
+            for mime in ["image/png", "image/svg+xml", "image/x-xpixmap"] {
+                filter.add_mime_type(mime);
+            }
+            dialog.add_filter(&filter);
+            let (w3, install) = (w2.clone(), icon_install_check.clone());
+            dialog.connect_response(move |d, resp| {
+                if resp == gtk4::ResponseType::Accept
+                    && let Some(path) = d.file().and_then(|f| f.path())
+                {
+                    if install.is_active() {
+                        if let Err(e) = install_icon(&w3, &path) {
+                            show_icon_error(parent.as_ref(), &format!("{:#}\n\nThe image is used by its path instead.", e));
+                        }
+                    } else {
+                        w3.icon_entry.set_text(&path.to_string_lossy());
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+    }
+
+    // Web App: regenerate Exec whenever one of its fields changes
+    for e in [&widgets.webapp_url_entry, &widgets.webapp_app_id_entry, &widgets.webapp_profile_entry, &widgets.webapp_class_entry] {
+        let w2 = widgets.clone();
+        e.connect_changed(move |_| update_webapp_exec(&w2));
+    }
+    {
+        let w2 = widgets.clone();
+        widgets.webapp_browser_combo.connect_changed(move |_| update_webapp_exec(&w2));
+    }
+
     // Initialize type-dependent field sensitivity
     apply_type_rules(&widgets);
     // React to type changes to re-apply rules
@@ -409,7 +654,7 @@ pub fn build_editor() -> Editor {
         });
     }
 
-    Editor { notebook, source_view, widgets }
+    Editor { notebook, source_view, source_status, widgets }
 }
 
 #[allow(dead_code)]
@@ -422,69 +667,173 @@ pub fn update_source(w: &EntryWidgets, source_view: &TextView) {
 }
 
 pub fn set_form_from_entry(w: &EntryWidgets, de: &DesktopEntry) {
-    // Type
-    let idx = match de.type_field.as_str() { "Application" => 0, "Link" => 1, "Directory" => 2, _ => 0 };
+    // Type first: applying the type rules clears type-specific fields, so it must run before they are filled
+    let webapp = WebApp::from_entry(de);
+    let idx = match de.type_field.as_str() { "Application" if webapp.is_some() => 3, "Application" => 0, "Link" => 1, "Directory" => 2, _ => 0 };
     w.type_combo.set_active(Some(idx));
+    apply_type_rules(w);
     w.name_entry.set_text(&de.name);
     w.generic_name_entry.set_text(de.generic_name.as_deref().unwrap_or(""));
     w.comment_entry.set_text(de.comment.as_deref().unwrap_or(""));
     w.exec_entry.set_text(&de.exec);
     w.icon_entry.set_text(de.icon.as_deref().unwrap_or(""));
 
-    // Update icon preview inside entry
-    let txt = w.icon_entry.text().to_string();
-    if txt.trim().is_empty() {
-        w.icon_entry.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing"));
-    } else if txt.contains('/') {
-        let f = File::for_path(&txt);
-        match gdk::Texture::from_file(&f) {
-            Ok(tex) => w.icon_entry.set_icon_from_paintable(EntryIconPosition::Primary, Some(&tex)),
-            Err(_) => w.icon_entry.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing")),
-        }
-    } else {
-        w.icon_entry.set_icon_from_icon_name(EntryIconPosition::Primary, Some(&txt));
-    }
+    update_icon_preview(&w.icon_entry);
 
     w.terminal_check.set_active(de.terminal);
     w.nodisplay_check.set_active(de.no_display);
     w.startup_check.set_active(de.startup_notify);
-    w.categories_entry.set_text(&de.categories.join(";"));
-    w.mimetype_entry.set_text(&de.mime_type.join(";"));
-    w.keywords_entry.set_text(&de.keywords.join(";"));
-    w.onlyshowin_entry.set_text(&de.only_show_in.join(";"));
-    w.notshowin_entry.set_text(&de.not_show_in.join(";"));
+    w.categories_entry.set_text(&list_text(&de.categories));
+    w.mimetype_entry.set_text(&list_text(&de.mime_type));
+    w.keywords_entry.set_text(&list_text(&de.keywords));
+    w.onlyshowin_entry.set_text(&list_text(&de.only_show_in));
+    w.notshowin_entry.set_text(&list_text(&de.not_show_in));
     w.tryexec_entry.set_text(de.try_exec.as_deref().unwrap_or(""));
     w.path_entry.set_text(de.path.as_deref().unwrap_or(""));
     w.url_entry.set_text(de.url.as_deref().unwrap_or(""));
-    w.actions_entry.set_text(&de.actions.join(";"));
+    w.actions_entry.set_text(&list_text(&de.actions));
 
     // Localized
-    let ln: Vec<String> = de.name_localized.iter().map(|(l,v)| format!("{}={}", l, v)).collect();
-    let lg: Vec<String> = de.generic_name_localized.iter().map(|(l,v)| format!("{}={}", l, v)).collect();
-    let lc: Vec<String> = de.comment_localized.iter().map(|(l,v)| format!("{}={}", l, v)).collect();
+    let ln: Vec<String> = de.name_localized.iter().map(|(l,v)| format!("{}={}", l, escape_string(v))).collect();
+    let lg: Vec<String> = de.generic_name_localized.iter().map(|(l,v)| format!("{}={}", l, escape_string(v))).collect();
+    let lc: Vec<String> = de.comment_localized.iter().map(|(l,v)| format!("{}={}", l, escape_string(v))).collect();
     w.localized_name.buffer().set_text(&ln.join("\n"));
     w.localized_gname.buffer().set_text(&lg.join("\n"));
     w.localized_comment.buffer().set_text(&lc.join("\n"));
 
-    // Extra
-    let extra: Vec<String> = de.extra.iter().map(|(k,v)| format!("{}={}", k, v)).collect();
+    // Extra (a web app's StartupWMClass is its Window Class)
+    let extra: Vec<String> = de.extra.iter()
+        .filter(|(k, _)| webapp.is_none() || k.trim() != "StartupWMClass")
+        .map(|(k,v)| format!("{}={}", k, v)).collect();
     w.extra_kv.buffer().set_text(&extra.join("\n"));
 
-    // Apply type rules after setting fields
-    apply_type_rules(w);
+    set_action_rows(w, &de.desktop_actions);
+
+    if let Some(app) = webapp {
+        *w.webapp_args.borrow_mut() = app.args.clone();
+        if !w.webapp_browser_combo.set_active_id(Some(&app.browser)) {
+            w.webapp_browser_combo.append(Some(&app.browser), &app.browser);
+            w.webapp_browser_combo.set_active_id(Some(&app.browser));
+        }
+        w.webapp_url_entry.set_text(&app.url);
+        w.webapp_app_id_entry.set_text(app.app_id.as_deref().unwrap_or(""));
+        w.webapp_profile_entry.set_text(app.profile.as_deref().unwrap_or(""));
+        w.webapp_class_entry.set_text(app.class.as_deref().unwrap_or(""));
+    }
+}
+
+pub fn set_action_rows(w: &EntryWidgets, actions: &[DesktopAction]) {
+    let old: Vec<ActionRow> = w.action_rows.borrow_mut().drain(..).collect();
+    for row in old {
+        w.actions_list.remove(&row.container);
+    }
+    for action in actions {
+        append_action_row(w, action);
+    }
+}
+
+fn append_action_row(w: &EntryWidgets, action: &DesktopAction) {
+    let container = GtkBox::new(Orientation::Vertical, 6);
+    container.add_css_class("card");
+    container.set_margin_bottom(6);
+    let (id_row, id_entry) = crate::ui::components::labeled_entry("Action ID*");
+    let (name_row, name_entry) = crate::ui::components::labeled_entry("Name*");
+    let (icon_row, icon_entry) = crate::ui::components::labeled_entry("Icon");
+    let (exec_row, exec_entry) = crate::ui::components::labeled_entry("Exec");
+    id_entry.set_text(&action.id);
+    name_entry.set_text(&action.name);
+    icon_entry.set_text(action.icon.as_deref().unwrap_or(""));
+    exec_entry.set_text(action.exec.as_deref().unwrap_or(""));
+    let localized_name = TextView::new();
+    localized_name.set_monospace(true);
+    localized_name.set_size_request(-1, 40);
+    let ln: Vec<String> = action.name_localized.iter().map(|(l,v)| format!("{}={}", l, escape_string(v))).collect();
+    localized_name.buffer().set_text(&ln.join("\n"));
+    let localized_sw = ScrolledWindow::builder().hexpand(true).vexpand(false).build();
+    localized_sw.add_css_class("frame");
+    localized_sw.set_child(Some(&localized_name));
+    let extra_kv = TextView::new();
+    extra_kv.set_monospace(true);
+    extra_kv.set_size_request(-1, 40);
+    let extra: Vec<String> = action.extra.iter().map(|(k,v)| format!("{}={}", k, v)).collect();
+    extra_kv.buffer().set_text(&extra.join("\n"));
+    let extra_sw = ScrolledWindow::builder().hexpand(true).vexpand(false).build();
+    extra_sw.add_css_class("frame");
+    extra_sw.set_child(Some(&extra_kv));
+    let remove_btn = Button::with_label("Remove Action");
+    remove_btn.add_css_class("destructive-action");
+    remove_btn.set_halign(gtk4::Align::End);
+
+    container.append(&id_row);
+    container.append(&name_row);
+    container.append(&icon_row);
+    container.append(&exec_row);
+    container.append(&Label::new(Some("Name[lang]=value lines")));
+    container.append(&localized_sw);
+    container.append(&Label::new(Some("Other key=value lines")));
+    container.append(&extra_sw);
+    container.append(&remove_btn);
+    w.actions_list.append(&container);
+
+    let row = ActionRow { container, id_entry, name_entry, icon_entry, exec_entry, localized_name, extra_kv };
+
+    // Propagate edits to whoever listens (Source tab sync)
+    let notify = {
+        let listeners = w.action_listeners.clone();
+        move || {
+            let cbs: Vec<ChangeListener> = listeners.borrow().clone();
+            for cb in cbs { cb(); }
+        }
+    };
+    for e in [&row.id_entry, &row.name_entry, &row.icon_entry, &row.exec_entry] {
+        let notify = notify.clone();
+        e.connect_changed(move |_| notify());
+    }
+    for tv in [&row.localized_name, &row.extra_kv] {
+        let notify = notify.clone();
+        tv.buffer().connect_changed(move |_| notify());
+    }
+    {
+        let w2 = w.clone();
+        let row_c = row.clone();
+        remove_btn.connect_clicked(move |_| {
+            let id = row_c.id_entry.text().to_string();
+            w2.action_rows.borrow_mut().retain(|r| r.container != row_c.container);
+            w2.actions_list.remove(&row_c.container);
+            let names: Vec<String> = split_semicolon(&w2.actions_entry).into_iter().filter(|s| *s != id).collect();
+            w2.actions_entry.set_text(&list_text(&names));
+            notify();
+        });
+    }
+
+    w.action_rows.borrow_mut().push(row);
+}
+
+pub fn collect_actions(w: &EntryWidgets) -> Vec<DesktopAction> {
+    w.action_rows.borrow().iter().map(|r| DesktopAction {
+        id: r.id_entry.text().trim().to_string(),
+        name: r.name_entry.text().to_string(),
+        name_localized: parse_lang_lines(&buffer_text(&r.localized_name)),
+        icon: opt_text(&r.icon_entry),
+        exec: opt_text(&r.exec_entry),
+        extra: parse_kv_lines(&buffer_text(&r.extra_kv)),
+    }).collect()
 }
 
 pub fn apply_type_rules(w: &EntryWidgets) {
     let ty = w.type_combo.active_text().map(|s| s.to_string()).unwrap_or_else(|| "Application".into());
-    let is_app = ty == "Application";
+    let is_web = ty == "Web App";
+    let is_app = ty == "Application" || is_web;
     let is_link = ty == "Link";
     let is_dir = ty == "Directory";
+    w.webapp_box.set_visible(is_web);
 
     // Toggle dynamic Exec/URL row visibility and label
     if is_app {
         w.exec_lbl.set_visible(true);
         w.exec_lbl.set_text("Exec*");
         w.exec_app_box.set_visible(true);
+        w.exec_tokens_row.set_visible(true);
         w.exec_link_box.set_visible(false);
         // For Application, URL controls are irrelevant
         w.url_btn.set_visible(false);
@@ -493,6 +842,7 @@ pub fn apply_type_rules(w: &EntryWidgets) {
         w.exec_lbl.set_visible(true);
         w.exec_lbl.set_text("URL*");
         w.exec_app_box.set_visible(false);
+        w.exec_tokens_row.set_visible(false);
         w.exec_link_box.set_visible(true);
         // Link: only a text entry (no chooser button)
         w.url_btn.set_visible(false);
@@ -502,6 +852,7 @@ pub fn apply_type_rules(w: &EntryWidgets) {
         w.exec_lbl.set_visible(true);
         w.exec_lbl.set_text("Folder*");
         w.exec_app_box.set_visible(false);
+        w.exec_tokens_row.set_visible(false);
         w.exec_link_box.set_visible(true);
         w.url_btn.set_visible(true);
         w.url_entry.set_visible(true);
@@ -516,6 +867,8 @@ pub fn apply_type_rules(w: &EntryWidgets) {
     w.url_entry.set_sensitive(false); // will be enabled for Link/Directory below
     w.startup_check.set_sensitive(false); w.startup_check.set_active(false);
     w.actions_entry.set_sensitive(false); w.actions_entry.set_text("");
+    w.actions_list.set_sensitive(false);
+    w.add_action_btn.set_sensitive(false);
 
     // Leave these always enabled: name, icon, generic/comment, visibility, categories, etc.
     w.name_entry.set_sensitive(true);
@@ -534,13 +887,21 @@ pub fn apply_type_rules(w: &EntryWidgets) {
     w.extra_kv.set_sensitive(true);
 
     if is_app {
-        w.exec_entry.set_sensitive(true);
+        // A web app's Exec comes from its fields
+        w.exec_entry.set_sensitive(!is_web);
+        w.exec_btn.set_sensitive(!is_web);
         w.tryexec_entry.set_sensitive(true);
         w.terminal_check.set_sensitive(true);
         w.path_entry.set_sensitive(true);
         w.startup_check.set_sensitive(true);
         w.actions_entry.set_sensitive(true);
+        w.actions_list.set_sensitive(true);
+        w.add_action_btn.set_sensitive(true);
         w.url_entry.set_sensitive(false); w.url_entry.set_text("");
+        if is_web {
+            w.webapp_args.borrow_mut().clear();
+            update_webapp_exec(w);
+        }
     } else if is_link {
         // URL text entry only
         w.url_entry.set_sensitive(true);
@@ -565,7 +926,24 @@ pub fn apply_type_rules(w: &EntryWidgets) {
 }
 
 pub fn collect_entry(w: &EntryWidgets) -> Result<DesktopEntry, String> {
-    let type_field = w.type_combo.active_text().map(|s| s.to_string()).unwrap_or_else(|| "Application".into());
+    let de = read_entry(w);
+    if is_webapp(w) {
+        let app = webapp_from_form(w);
+        if app.browser.is_empty() {
+            return Err("Browser: no Chromium-based browser found".into());
+        }
+        if app.url.is_empty() && app.app_id.is_none() {
+            return Err("Address: required for a web app".into());
+        }
+    }
+    de.check()?;
+    Ok(de)
+}
+
+// The form as an entry, without rejecting invalid values
+pub fn read_entry(w: &EntryWidgets) -> DesktopEntry {
+    let kind = w.type_combo.active_text().map(|s| s.to_string()).unwrap_or_else(|| "Application".into());
+    let type_field = if kind == "Web App" { "Application".to_string() } else { kind };
     let name = w.name_entry.text().to_string();
     let generic_name = opt_text(&w.generic_name_entry);
     let comment = opt_text(&w.comment_entry);
@@ -587,8 +965,9 @@ pub fn collect_entry(w: &EntryWidgets) -> Result<DesktopEntry, String> {
     let generic_name_localized = parse_lang_lines(&buffer_text(&w.localized_gname));
     let comment_localized = parse_lang_lines(&buffer_text(&w.localized_comment));
     let extra = parse_kv_lines(&buffer_text(&w.extra_kv));
+    let desktop_actions = if type_field == "Application" { collect_actions(w) } else { Vec::new() };
 
-    let de = DesktopEntry {
+    let mut de = DesktopEntry {
         type_field,
         name,
         generic_name,
@@ -608,16 +987,163 @@ pub fn collect_entry(w: &EntryWidgets) -> Result<DesktopEntry, String> {
         url,
         actions,
         extra,
+        desktop_actions,
         name_localized,
         generic_name_localized,
         comment_localized,
     };
+    if is_webapp(w) {
+        webapp_from_form(w).apply(&mut de);
+    }
+    de
+}
 
-    de.validate()?;
-    Ok(de)
+fn is_webapp(w: &EntryWidgets) -> bool {
+    w.type_combo.active_text().is_some_and(|t| t == "Web App")
+}
+
+fn webapp_from_form(w: &EntryWidgets) -> WebApp {
+    WebApp {
+        browser: w.webapp_browser_combo.active_id().map(|s| s.to_string()).unwrap_or_default(),
+        url: w.webapp_url_entry.text().trim().to_string(),
+        app_id: opt_text(&w.webapp_app_id_entry),
+        profile: opt_text(&w.webapp_profile_entry),
+        class: opt_text(&w.webapp_class_entry),
+        args: w.webapp_args.borrow().clone(),
+    }
+}
+
+// Show the command the web app fields produce, and the class used when none is given
+fn update_webapp_exec(w: &EntryWidgets) {
+    if !is_webapp(w) {
+        return;
+    }
+    let app = webapp_from_form(w);
+    w.webapp_class_entry.set_placeholder_text(Some(&app.default_class()));
+    w.exec_entry.set_text(&app.exec());
+}
+
+// The default application of each MimeType (see services::mimeapps)
+pub fn update_mime_defaults(w: &EntryWidgets) {
+    let types = split_semicolon(&w.mimetype_entry);
+    if let Some(row) = w.mime_defaults_label.parent() {
+        row.set_visible(!types.is_empty());
+    }
+    if types.is_empty() {
+        return;
+    }
+    let apps = MimeApps::load();
+    let lines: Vec<String> = types
+        .iter()
+        .map(|t| match apps.default_for(t) {
+            Some(h) => {
+                let name = DesktopReader::read_from_path(&h.path).map(|e| e.name).unwrap_or_default();
+                if name.is_empty() { format!("{}: {}", t, h.id) } else { format!("{}: {} ({})", t, name, h.id) }
+            }
+            None => format!("{}: no default application", t),
+        })
+        .collect();
+    w.mime_defaults_label.set_text(&format!("Opened by default with\n{}", lines.join("\n")));
 }
 
-fn split_semicolon(e: &Entry) -> Vec<String> { e.text().split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect() }
+// Install `path` as a theme icon and point Icon= at it, marking it as this launcher's.
+// When that fails Icon= refers to the file by path, and the error is returned.
+fn install_icon(w: &EntryWidgets, path: &Path) -> anyhow::Result<()> {
+    let mut extra = parse_kv_lines(&buffer_text(&w.extra_kv));
+    let current = w.icon_entry.text().to_string();
+    let name = extra
+        .iter()
+        .find(|(k, v)| k == icons::KEY_ICON && *v == current)
+        .map(|(_, v)| v.clone())
+        .unwrap_or_else(|| Icons::unused_name(path));
+    match Icons::install_file(path, &name) {
+        Ok(_) => {
+            extra.retain(|(k, _)| k != icons::KEY_ICON);
+            extra.push((icons::KEY_ICON.to_string(), name.clone()));
+            let lines: Vec<String> = extra.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            w.extra_kv.buffer().set_text(&lines.join("\n"));
+            w.icon_entry.set_text(&name);
+            Ok(())
+        }
+        Err(e) => {
+            // Keep the image usable by path
+            w.icon_entry.set_text(&path.to_string_lossy());
+            Err(e)
+        }
+    }
+}
+
+fn show_icon_error(parent: Option<&gtk4::Window>, msg: &str) {
+    let dialog = gtk4::MessageDialog::builder()
+        .modal(true)
+        .title("Error")
+        .text("Icon not installed")
+        .secondary_text(msg)
+        .build();
+    dialog.set_transient_for(parent);
+    dialog.add_button("Close", gtk4::ResponseType::Close);
+    dialog.connect_response(|d, _| d.close());
+    dialog.show();
+}
+
+// Preview the file the icon theme lookup picks, with the file used at each size in the
+// tooltip; a value that resolves to nothing is marked as an error
+fn update_icon_preview(e: &Entry) {
+    let txt = e.text().trim().to_string();
+    e.set_icon_from_icon_name(EntryIconPosition::Secondary, None);
+    e.set_icon_tooltip_text(EntryIconPosition::Primary, None);
+    if txt.is_empty() {
+        e.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing"));
+        return;
+    }
+    let mut resolver = IconResolver::new(&icon_theme::current_theme_name());
+    let report = resolver.report(&txt);
+    let preview = report.iter().find(|(size, _)| *size == 48).and_then(|(_, p)| p.clone());
+    match preview.and_then(|p| gdk::Texture::from_file(&File::for_path(p)).ok()) {
+        Some(tex) => {
+            e.set_icon_from_paintable(EntryIconPosition::Primary, Some(&tex));
+            let lines: Vec<String> = report
+                .iter()
+                .map(|(size, p)| format!("{} px: {}", size, p.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".into())))
+                .collect();
+            e.set_icon_tooltip_text(EntryIconPosition::Primary, Some(&format!("Theme: {}\n{}", resolver.theme(), lines.join("\n"))));
+        }
+        None => {
+            e.set_icon_from_icon_name(EntryIconPosition::Primary, Some("image-missing"));
+            e.set_icon_from_icon_name(EntryIconPosition::Secondary, Some("dialog-error-symbolic"));
+            e.set_icon_tooltip_text(EntryIconPosition::Secondary, Some(&resolver.missing_message(&txt)));
+        }
+    }
+}
+
+// "program ‹arg› ‹%U›" followed by one line per quoting/field code problem
+fn exec_markup(exec: &str) -> String {
+    if exec.trim().is_empty() {
+        return String::new();
+    }
+    let (line, issues) = exec::parse(exec);
+    let mut out = String::new();
+    for (i, arg) in line.args.iter().enumerate() {
+        let text = gtk4::glib::markup_escape_text(&arg.text()).to_string();
+        if i == 0 {
+            out.push_str(&format!("<b>{}</b>", text));
+        } else if arg.codes().next().is_some() {
+            out.push_str(&format!("  <i>‹{}›</i>", text));
+        } else {
+            out.push_str(&format!("  ‹<tt>{}</tt>›", text));
+        }
+    }
+    for issue in issues {
+        let color = match issue.severity { Severity::Error => "#c01c28", Severity::Warning => "#c64600" };
+        let message = gtk4::glib::markup_escape_text(&issue.message);
+        out.push_str(&format!("\n<span foreground=\"{}\">{}</span>", color, message));
+    }
+    out
+}
+
+// Lists are shown like in the file (minus the trailing ';'), so items may contain \;
+fn split_semicolon(e: &Entry) -> Vec<String> { split_list(&e.text()) }
+fn list_text(items: &[String]) -> String { let s = join_list(items); s.strip_suffix(';').unwrap_or(&s).to_string() }
 fn opt_text(e: &Entry) -> Option<String> { let s = e.text().trim().to_string(); if s.is_empty() { None } else { Some(s) } }
 fn buffer_text(tv: &TextView) -> String { let buf = tv.buffer(); buf.text(&buf.start_iter(), &buf.end_iter(), true).to_string() }
 fn parse_lang_lines(s: &str) -> Vec<(String, String)> {
@@ -626,7 +1152,7 @@ fn parse_lang_lines(s: &str) -> Vec<(String, String)> {
         if line.is_empty() { return None; }
         if let Some((lang, val)) = line.split_once('=') {
             let lang = lang.trim().to_string();
-            let val = val.trim().to_string();
+            let val = unescape_string(val.trim());
             if lang.is_empty() || val.is_empty() { None } else { Some((lang, val)) }
         } else { None }
     }).collect()
@@ -644,81 +1170,35 @@ fn parse_kv_lines(s: &str) -> Vec<(String, String)> {
 }
 
 
-// --- Added: parse from source and wire two-way sync for Source tab ---
-pub fn parse_desktop_source(content: &str) -> DesktopEntry {
-    let mut entry = DesktopEntry::default();
-    let mut in_desktop = false;
-    for line in content.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') || line.starts_with(';') { continue; }
-        if line.starts_with('[') && line.ends_with(']') {
-            in_desktop = line == "[Desktop Entry]";
-            continue;
-        }
-        if !in_desktop { continue; }
-        if let Some((k, v)) = line.split_once('=') {
-            let key = k.trim();
-            let val = v.trim().to_string();
-            match key {
-                "Type" => entry.type_field = val,
-                "Name" => entry.name = val,
-                _ if key.starts_with("Name[") && key.ends_with("]") => {
-                    let lang = key.trim_start_matches("Name[").trim_end_matches("]").to_string();
-                    entry.name_localized.push((lang, val));
-                }
-                "GenericName" => entry.generic_name = Some(val),
-                _ if key.starts_with("GenericName[") && key.ends_with("]") => {
-                    let lang = key.trim_start_matches("GenericName[").trim_end_matches("]").to_string();
-                    entry.generic_name_localized.push((lang, val));
-                }
-                "Comment" => entry.comment = Some(val),
-                _ if key.starts_with("Comment[") && key.ends_with("]") => {
-                    let lang = key.trim_start_matches("Comment[").trim_end_matches("]").to_string();
-                    entry.comment_localized.push((lang, val));
-                }
-                "Exec" => entry.exec = val,
-                "TryExec" => entry.try_exec = Some(val),
-                "Icon" => entry.icon = Some(val),
-                "Path" => entry.path = Some(val),
-                "URL" => entry.url = Some(val),
-                "Terminal" => entry.terminal = val.eq_ignore_ascii_case("true"),
-                "NoDisplay" => entry.no_display = val.eq_ignore_ascii_case("true"),
-                "StartupNotify" => entry.startup_notify = val.eq_ignore_ascii_case("true"),
-                "Categories" => entry.categories = val.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
-                "MimeType" => entry.mime_type = val.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
-                "Keywords" => entry.keywords = val.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
-                "OnlyShowIn" => entry.only_show_in = val.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
-                "NotShowIn" => entry.not_show_in = val.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
-                "Actions" => entry.actions = val.split(';').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect(),
-                _ => entry.extra.push((key.to_string(), val)),
-            }
-        }
-    }
-    if entry.type_field.is_empty() { entry.type_field = "Application".into(); }
-    entry
-}
-
+// --- Two-way sync for Source tab ---
 pub fn wire_source_sync(editor: &Editor) {
-    use std::rc::Rc;
-    use std::cell::RefCell;
-
     let widgets = &editor.widgets;
     let source_view = editor.source_view.clone();
+    let source_status = editor.source_status.clone();
 
     // Guard to avoid infinite loops when programmatically updating
     let guard = Rc::new(RefCell::new(false));
+    // Diagnostics of the current source text, for hover tooltips
+    let diagnostics: Rc<RefCell<Vec<Diagnostic>>> = Rc::new(RefCell::new(Vec::new()));
 
     let update_from_fields = {
         let w = clone_widgets(widgets);
         let source_view = source_view.clone();
+        let source_status = source_status.clone();
         let guard = guard.clone();
+        let diagnostics = diagnostics.clone();
         move || {
             if *guard.borrow() { return; }
-            if let Ok(de) = collect_entry(&w) {
-                *guard.borrow_mut() = true;
-                let buf = source_view.buffer();
-                buf.set_text(&de.to_ini_string());
-                *guard.borrow_mut() = false;
+            match collect_entry(&w) {
+                Ok(de) => {
+                    diagnostics.borrow_mut().clear();
+                    *guard.borrow_mut() = true;
+                    let buf = source_view.buffer();
+                    buf.set_text(&de.to_ini_string());
+                    *guard.borrow_mut() = false;
+                    source_status.set_text("");
+                }
+                Err(e) => source_status.set_text(&format!("Invalid: {}", e)),
             }
         }
     };
@@ -760,19 +1240,52 @@ pub fn wire_source_sync(editor: &Editor) {
     connect_textview(&widgets.localized_gname);
     connect_textview(&widgets.localized_comment);
     connect_textview(&widgets.extra_kv);
+    widgets.action_listeners.borrow_mut().push(Rc::new(update_from_fields.clone()));
 
     // Connect source buffer changes to parse back into fields
     {
         let w = clone_widgets(widgets);
         let source_buf = source_view.buffer();
         let guard = guard.clone();
+        let source_status = source_status.clone();
+        let diagnostics = diagnostics.clone();
+        let view = source_view.clone();
         source_buf.connect_changed(move |buf| {
             if *guard.borrow() { return; }
             let text = buf.text(&buf.start_iter(), &buf.end_iter(), true).to_string();
-            let de = parse_desktop_source(&text);
-            *guard.borrow_mut() = true;
-            set_form_from_entry(&w, &de);
-            *guard.borrow_mut() = false;
+            let (de, mut found) = desktop_file::parse_with_diagnostics(&text);
+            // Keep the form as it was until the source parses again
+            if !found.iter().any(|d| d.severity == Severity::Error) {
+                *guard.borrow_mut() = true;
+                set_form_from_entry(&w, &de);
+                *guard.borrow_mut() = false;
+            }
+            found.extend(desktop_file::validation_diagnostics(&text, &de, Some(MimeDb::load_installed())));
+            found.extend(desktop_file::finding_diagnostics(&text, icon_theme::check_icons(&de)));
+            found.sort_by_key(|d| (d.line, d.column));
+            show_diagnostics(&view, &found);
+            let summary = match found.first() {
+                Some(first) if found.len() > 1 => format!("{} problems, first: {}", found.len(), first),
+                Some(first) => first.to_string(),
+                None => String::new(),
+            };
+            source_status.set_text(&summary);
+            *diagnostics.borrow_mut() = found;
+        });
+    }
+
+    // Explain underlined lines on hover
+    {
+        let diagnostics = diagnostics.clone();
+        source_view.connect_query_tooltip(move |view, x, y, keyboard, tooltip| {
+            if keyboard { return false; }
+            let (bx, by) = view.window_to_buffer_coords(gtk4::TextWindowType::Widget, x, y);
+            let Some(iter) = view.iter_at_location(bx, by) else { return false };
+            let line = iter.line() as usize + 1;
+            let messages: Vec<String> = diagnostics.borrow().iter().filter(|d| d.line == line).map(|d| d.to_string()).collect();
+            if messages.is_empty() { return false; }
+            tooltip.set_text(Some(&messages.join("\n")));
+            true
         });
     }
 
@@ -780,6 +1293,25 @@ pub fn wire_source_sync(editor: &Editor) {
     update_from_fields();
 }
 
+// Underline each diagnostic from its column to the end of its line
+fn show_diagnostics(view: &TextView, diagnostics: &[Diagnostic]) {
+    let buf = view.buffer();
+    let (start, end) = buf.bounds();
+    buf.remove_tag_by_name("diagnostic-error", &start, &end);
+    buf.remove_tag_by_name("diagnostic-warning", &start, &end);
+    for d in diagnostics {
+        let line = d.line as i32 - 1;
+        let Some(line_start) = buf.iter_at_line(line) else { continue };
+        let mut to = line_start;
+        if !to.ends_line() { to.forward_to_line_end(); }
+        let mut from = buf.iter_at_line_offset(line, d.column as i32 - 1).unwrap_or(line_start);
+        // Nothing to underline after the column (e.g. empty line): mark the whole line
+        if from >= to { from = line_start; }
+        let tag = match d.severity { Severity::Error => "diagnostic-error", Severity::Warning => "diagnostic-warning" };
+        buf.apply_tag_by_name(tag, &from, &to);
+    }
+}
+
 fn clone_widgets(w: &EntryWidgets) -> EntryWidgets {
     EntryWidgets {
         type_combo: w.type_combo.clone(),
@@ -793,6 +1325,7 @@ fn clone_widgets(w: &EntryWidgets) -> EntryWidgets {
         startup_check: w.startup_check.clone(),
         categories_entry: w.categories_entry.clone(),
         mimetype_entry: w.mimetype_entry.clone(),
+        mime_defaults_label: w.mime_defaults_label.clone(),
         keywords_entry: w.keywords_entry.clone(),
         onlyshowin_entry: w.onlyshowin_entry.clone(),
         notshowin_entry: w.notshowin_entry.clone(),
@@ -809,5 +1342,17 @@ fn clone_widgets(w: &EntryWidgets) -> EntryWidgets {
         exec_link_box: w.exec_link_box.clone(),
         exec_btn: w.exec_btn.clone(),
         url_btn: w.url_btn.clone(),
+        exec_tokens_row: w.exec_tokens_row.clone(),
+        webapp_box: w.webapp_box.clone(),
+        webapp_url_entry: w.webapp_url_entry.clone(),
+        webapp_app_id_entry: w.webapp_app_id_entry.clone(),
+        webapp_browser_combo: w.webapp_browser_combo.clone(),
+        webapp_profile_entry: w.webapp_profile_entry.clone(),
+        webapp_class_entry: w.webapp_class_entry.clone(),
+        webapp_args: w.webapp_args.clone(),
+        actions_list: w.actions_list.clone(),
+        add_action_btn: w.add_action_btn.clone(),
+        action_rows: w.action_rows.clone(),
+        action_listeners: w.action_listeners.clone(),
     }
 }
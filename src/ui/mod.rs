@@ -1,4 +1,3 @@
 pub mod windows;
 pub mod components;
 pub mod editor;
-pub mod helpers;
@@ -0,0 +1,264 @@
+use gtk4::{self, Align, Box as GtkBox, Button, CheckButton, Entry, FileChooserAction, FileChooserDialog, Image, Label, ListBox, ListBoxRow, Orientation, ResponseType, ScrolledWindow, Switch};
+use adw::{HeaderBar as AdwHeaderBar, ToolbarView, Window as AdwWindow};
+use adw::prelude::*;
+use std::path::{Path, PathBuf};
+
+use crate::services::autostart::{self, Autostart, AutostartItem, State};
+use crate::services::discovery::{self, Source};
+
+// Autostart manager: user and system login items, their effective state and options
+pub fn show_autostart_window(parent: &impl IsA<gtk4::Window>) {
+    let win = AdwWindow::builder()
+        .transient_for(parent)
+        .title("Autostart")
+        .default_width(720)
+        .default_height(520)
+        .build();
+    let header = AdwHeaderBar::new();
+    header.add_css_class("flat");
+
+    let root = GtkBox::new(Orientation::Vertical, 8);
+    root.set_margin_bottom(12);
+    root.set_margin_start(12);
+    root.set_margin_end(12);
+
+    let top = GtkBox::new(Orientation::Horizontal, 6);
+    let add_btn = Button::with_label("Add Launcher...");
+    let refresh_btn = Button::with_label("Refresh");
+    top.append(&add_btn);
+    top.append(&refresh_btn);
+
+    let listbox = ListBox::new();
+    listbox.add_css_class("boxed-list");
+    listbox.set_selection_mode(gtk4::SelectionMode::None);
+    let scroller = ScrolledWindow::builder().hexpand(true).vexpand(true).child(&listbox).build();
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    status.set_wrap(true);
+
+    root.append(&top);
+    root.append(&scroller);
+    root.append(&status);
+
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&root));
+    win.set_content(Some(&toolbar_view));
+
+    populate(&listbox, &status);
+
+    {
+        let listbox = listbox.clone();
+        let status = status.clone();
+        refresh_btn.connect_clicked(move |_| populate(&listbox, &status));
+    }
+    {
+        let listbox = listbox.clone();
+        let status = status.clone();
+        let win_c = win.clone();
+        add_btn.connect_clicked(move |_| {
+            let dialog = FileChooserDialog::new(Some("Add Launcher to Autostart"), Some(&win_c), FileChooserAction::Open, &[("Cancel", ResponseType::Cancel), ("Add", ResponseType::Accept)]);
+            let filter = gtk4::FileFilter::new();
+            filter.add_pattern("*.desktop");
+            filter.set_name(Some("Launchers"));
+            dialog.add_filter(&filter);
+            if let Some(dir) = discovery::user_applications_dir().filter(|d| d.is_dir()) {
+                let _ = dialog.set_current_folder(Some(&gtk4::gio::File::for_path(dir)));
+            }
+            let listbox = listbox.clone();
+            let status = status.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept
+                    && let Some(path) = d.file().and_then(|f| f.path())
+                {
+                    report(&status, Autostart::add(&path).map(|p| format!("Added {}", p.display())));
+                    populate(&listbox, &status);
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+    }
+
+    win.present();
+}
+
+fn populate(listbox: &ListBox, status: &Label) {
+    while let Some(child) = listbox.first_child() { listbox.remove(&child); }
+    let items = Autostart::list();
+    if items.is_empty() {
+        let row = ListBoxRow::new();
+        let lbl = Label::new(Some("No autostart entries"));
+        lbl.add_css_class("dim-label");
+        lbl.set_margin_top(12);
+        lbl.set_margin_bottom(12);
+        row.set_child(Some(&lbl));
+        listbox.append(&row);
+    }
+    for item in &items {
+        listbox.append(&build_row(item, listbox, status));
+    }
+}
+
+// Rebuild once the signal that triggered it is done with the row's widgets
+fn populate_later(listbox: &ListBox, status: &Label) {
+    let (listbox, status) = (listbox.clone(), status.clone());
+    gtk4::glib::idle_add_local_once(move || populate(&listbox, &status));
+}
+
+fn build_row(item: &AutostartItem, listbox: &ListBox, status: &Label) -> ListBoxRow {
+    let row = ListBoxRow::new();
+    row.set_activatable(false);
+    let hb = GtkBox::new(Orientation::Horizontal, 10);
+    hb.set_margin_top(6);
+    hb.set_margin_bottom(6);
+    hb.set_margin_start(6);
+    hb.set_margin_end(6);
+
+    let icon = item.entry.as_ref().and_then(|e| e.icon.clone()).unwrap_or_else(|| "application-x-executable-symbolic".into());
+    let img = if icon.contains('/') { Image::from_file(icon) } else { Image::from_icon_name(&icon) };
+    img.set_pixel_size(32);
+    hb.append(&img);
+
+    let text = GtkBox::new(Orientation::Vertical, 2);
+    text.set_hexpand(true);
+    let name = item.entry.as_ref().map(|e| e.name.clone()).filter(|n| !n.is_empty()).unwrap_or_else(|| item.file_name.clone());
+    let title = Label::new(Some(&name));
+    title.set_xalign(0.0);
+    let state = match &item.state {
+        State::Enabled => "Starts at login".to_string(),
+        State::Disabled(reason) => format!("Disabled: {}", reason),
+    };
+    let subtitle = Label::new(Some(&format!("{} · {} · {}", item.source.label(), item.file_name, state)));
+    subtitle.set_xalign(0.0);
+    subtitle.add_css_class("dim-label");
+    subtitle.add_css_class("caption");
+    subtitle.set_tooltip_text(Some(&item.path.to_string_lossy()));
+    text.append(&title);
+    text.append(&subtitle);
+    hb.append(&text);
+
+    // A system entry replaced by a user file is only shown; the user file is the one to edit
+    if item.overridden_by.is_some() {
+        row.add_css_class("dim-label");
+        row.set_child(Some(&hb));
+        return row;
+    }
+    let Some(entry) = &item.entry else {
+        row.set_child(Some(&hb));
+        return row;
+    };
+
+    let hidden = CheckButton::with_label("Hidden");
+    hidden.set_active(entry.extra_value(autostart::KEY_HIDDEN) == Some("true"));
+    hidden.set_valign(Align::Center);
+    hidden.set_tooltip_text(Some("Hidden=true: treated as deleted by every session"));
+    {
+        let (path, listbox, status) = (item.path.clone(), listbox.clone(), status.clone());
+        hidden.connect_toggled(move |b| {
+            report(&status, Autostart::set_hidden(&path, b.is_active()).map(|p| format!("Updated {}", p.display())));
+            populate_later(&listbox, &status);
+        });
+    }
+
+    let enabled = Switch::new();
+    enabled.set_active(entry.extra_value(autostart::KEY_GNOME_ENABLED) != Some("false"));
+    enabled.set_valign(Align::Center);
+    enabled.set_tooltip_text(Some("X-GNOME-Autostart-enabled"));
+    {
+        let (path, listbox, status) = (item.path.clone(), listbox.clone(), status.clone());
+        enabled.connect_state_set(move |_, on| {
+            report(&status, Autostart::set_gnome_enabled(&path, on).map(|p| format!("Updated {}", p.display())));
+            populate_later(&listbox, &status);
+            gtk4::glib::Propagation::Proceed
+        });
+    }
+
+    let options_btn = Button::with_label("Options...");
+    options_btn.set_valign(Align::Center);
+    {
+        let (path, listbox, status) = (item.path.clone(), listbox.clone(), status.clone());
+        let values = [
+            entry.extra_value(autostart::KEY_GNOME_DELAY).unwrap_or("").to_string(),
+            entry.extra_value(autostart::KEY_GNOME_PHASE).unwrap_or("").to_string(),
+            entry.extra_value(autostart::KEY_CONDITION).unwrap_or("").to_string(),
+        ];
+        options_btn.connect_clicked(move |b| show_options(b, &path, &values, &listbox, &status));
+    }
+
+    hb.append(&hidden);
+    hb.append(&enabled);
+    hb.append(&options_btn);
+
+    if item.source == Source::User {
+        let remove_btn = Button::from_icon_name("user-trash-symbolic");
+        remove_btn.set_valign(Align::Center);
+        remove_btn.set_tooltip_text(Some("Remove from autostart (a replaced system entry comes back)"));
+        let (path, listbox, status) = (item.path.clone(), listbox.clone(), status.clone());
+        remove_btn.connect_clicked(move |_| {
+            report(&status, Autostart::remove(&path).map(|_| format!("Removed {}", path.display())));
+            populate_later(&listbox, &status);
+        });
+        hb.append(&remove_btn);
+    }
+
+    row.set_child(Some(&hb));
+    row
+}
+
+// Delay, phase and condition; empty fields remove the key
+fn show_options(button: &Button, path: &Path, values: &[String; 3], listbox: &ListBox, status: &Label) {
+    let Some(parent) = button.root().and_downcast::<gtk4::Window>() else { return };
+    let dialog = gtk4::MessageDialog::builder()
+        .transient_for(&parent)
+        .modal(true)
+        .title("Autostart options")
+        .text("Session options")
+        .build();
+    let area = dialog.message_area().downcast::<GtkBox>().expect("message area is a box");
+    let mut entries: Vec<(&'static str, Entry)> = Vec::new();
+    for ((key, label), value) in [
+        (autostart::KEY_GNOME_DELAY, "Delay (seconds)"),
+        (autostart::KEY_GNOME_PHASE, "Phase"),
+        (autostart::KEY_CONDITION, "Condition"),
+    ]
+    .into_iter()
+    .zip(values.iter())
+    {
+        let (row, entry) = crate::ui::components::labeled_entry(label);
+        entry.set_text(value);
+        entry.set_tooltip_text(Some(key));
+        area.append(&row);
+        entries.push((key, entry));
+    }
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Apply", ResponseType::Accept);
+    let path: PathBuf = path.to_path_buf();
+    let (listbox, status) = (listbox.clone(), status.clone());
+    dialog.connect_response(move |d, resp| {
+        if resp == ResponseType::Accept {
+            let values: Vec<(&str, String)> = entries.iter().map(|(k, e)| (*k, e.text().trim().to_string())).collect();
+            if let Some((_, delay)) = values.iter().find(|(k, v)| *k == autostart::KEY_GNOME_DELAY && !v.is_empty() && v.parse::<u32>().is_err()) {
+                status.set_text(&format!("Delay must be a number of seconds, not \"{}\"", delay));
+            } else {
+                let result = Autostart::update(&path, |e| {
+                    for (key, value) in &values {
+                        e.set_extra(key, Some(value.as_str()).filter(|v| !v.is_empty()));
+                    }
+                });
+                report(&status, result.map(|p| format!("Updated {}", p.display())));
+                populate(&listbox, &status);
+            }
+        }
+        d.close();
+    });
+    dialog.show();
+}
+
+fn report(status: &Label, result: anyhow::Result<String>) {
+    match result {
+        Ok(text) => status.set_text(&text),
+        Err(e) => status.set_text(&format!("Failed: {:#}", e)),
+    }
+}
@@ -0,0 +1,122 @@
+use gtk4::{self, Align, Box as GtkBox, Button, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, SearchEntry};
+use adw::{HeaderBar as AdwHeaderBar, ToolbarView, Window as AdwWindow};
+use adw::prelude::*;
+use std::rc::Rc;
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::services::flatpak::{Flatpak, FlatpakApp};
+
+// Installed Flatpak apps; `on_create` receives a launcher for the chosen one (with a
+// `flatpak run` Exec for its installed branch) and the app ID.
+pub fn show_flatpak_window(parent: &impl IsA<gtk4::Window>, on_create: impl Fn(DesktopEntry, &str) + 'static) {
+    let win = AdwWindow::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("New from Flatpak App")
+        .default_width(560)
+        .default_height(520)
+        .build();
+    let header = AdwHeaderBar::new();
+    header.add_css_class("flat");
+    let create_btn = Button::with_label("Create");
+    create_btn.add_css_class("suggested-action");
+    create_btn.set_sensitive(false);
+    header.pack_end(&create_btn);
+
+    let search = SearchEntry::new();
+    search.set_placeholder_text(Some("Search by name or ID"));
+    search.set_margin_start(12);
+    search.set_margin_end(12);
+    search.set_margin_top(6);
+
+    let listbox = ListBox::new();
+    listbox.add_css_class("navigation-sidebar");
+    let scroller = ScrolledWindow::builder().vexpand(true).child(&listbox).build();
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    status.set_wrap(true);
+    status.add_css_class("dim-label");
+    status.set_margin_start(12);
+    status.set_margin_end(12);
+    status.set_margin_bottom(8);
+
+    let root = GtkBox::new(Orientation::Vertical, 6);
+    root.append(&search);
+    root.append(&scroller);
+    root.append(&status);
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&root));
+    win.set_content(Some(&toolbar_view));
+
+    let apps = Rc::new(Flatpak::list());
+    if apps.is_empty() {
+        status.set_text("No Flatpak apps are installed for this user or system-wide.");
+    }
+    for app in apps.iter() {
+        listbox.append(&build_row(app));
+    }
+
+    {
+        let (apps, search_c) = (apps.clone(), search.clone());
+        listbox.set_filter_func(move |row| {
+            let query = search_c.text().to_lowercase();
+            apps.get(row.index() as usize)
+                .is_some_and(|a| query.is_empty() || a.name.to_lowercase().contains(&query) || a.app.id.to_lowercase().contains(&query))
+        });
+    }
+    {
+        let listbox = listbox.clone();
+        search.connect_search_changed(move |_| listbox.invalidate_filter());
+    }
+    {
+        let create_btn = create_btn.clone();
+        listbox.connect_row_selected(move |_, row| create_btn.set_sensitive(row.is_some()));
+    }
+    {
+        let (apps, listbox, status, win_c) = (apps.clone(), listbox.clone(), status.clone(), win.clone());
+        create_btn.connect_clicked(move |_| {
+            let Some(app) = listbox.selected_row().and_then(|r| apps.get(r.index() as usize)) else { return };
+            match Flatpak::launcher(app) {
+                Ok(entry) => {
+                    on_create(entry, &app.app.id);
+                    win_c.close();
+                }
+                Err(e) => status.set_text(&format!("{:#}", e)),
+            }
+        });
+    }
+
+    win.present();
+}
+
+fn build_row(app: &FlatpakApp) -> ListBoxRow {
+    let row = ListBoxRow::new();
+    let vb = GtkBox::new(Orientation::Vertical, 2);
+    vb.set_margin_top(6);
+    vb.set_margin_bottom(6);
+    vb.set_margin_start(6);
+    vb.set_margin_end(6);
+    let top = GtkBox::new(Orientation::Horizontal, 6);
+    let name = Label::new(Some(&app.name));
+    name.set_xalign(0.0);
+    name.set_hexpand(true);
+    top.append(&name);
+    let badge = Label::new(Some(app.scope.label()));
+    badge.add_css_class("caption");
+    badge.add_css_class("dim-label");
+    badge.set_valign(Align::Center);
+    top.append(&badge);
+    vb.append(&top);
+    let details = Label::new(Some(&format!("{} · {} · {}", app.app.id, app.app.branch, app.app.arch)));
+    details.set_xalign(0.0);
+    details.add_css_class("caption");
+    details.add_css_class("dim-label");
+    vb.append(&details);
+    if let Some(runtime) = &app.runtime {
+        row.set_tooltip_text(Some(&format!("Runtime: {}", runtime)));
+    }
+    row.set_child(Some(&vb));
+    row
+}
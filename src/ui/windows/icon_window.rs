@@ -0,0 +1,203 @@
+use gtk4::{self, Align, Box as GtkBox, Button, ComboBoxText, CustomFilter, FilterChange, FilterListModel, GridView, Image, Label, ListItem, Orientation, ScrolledWindow, SearchEntry, SignalListItemFactory, SingleSelection, StringList, StringObject};
+use adw::{HeaderBar as AdwHeaderBar, ToolbarView, Window as AdwWindow};
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::services::icon_theme::{self, IconCategory, IconResolver, REPORT_SIZES};
+
+// Icon names of the current theme and its fallbacks, the best matches for `hints` (see
+// icon_theme::icon_hints) first. `on_pick` receives the chosen name.
+pub fn show_icon_window(parent: &impl IsA<gtk4::Window>, hints: &[String], on_pick: impl Fn(&str) + 'static) {
+    let win = AdwWindow::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Choose Icon")
+        .default_width(720)
+        .default_height(600)
+        .build();
+    let header = AdwHeaderBar::new();
+    header.add_css_class("flat");
+    let select_btn = Button::with_label("Select");
+    select_btn.add_css_class("suggested-action");
+    select_btn.set_sensitive(false);
+    header.pack_end(&select_btn);
+
+    let search = SearchEntry::new();
+    search.set_placeholder_text(Some("Search icon names"));
+    search.set_hexpand(true);
+    let category = ComboBoxText::new();
+    for (id, label) in [("all", "All"), ("apps", "Applications"), ("mimetypes", "MIME Types"), ("places", "Places"), ("symbolic", "Symbolic")] {
+        category.append(Some(id), label);
+    }
+    category.set_active_id(Some("all"));
+    let filters = GtkBox::new(Orientation::Horizontal, 6);
+    filters.set_margin_start(12);
+    filters.set_margin_end(12);
+    filters.set_margin_top(6);
+    filters.append(&search);
+    filters.append(&category);
+
+    let resolver = Rc::new(RefCell::new(IconResolver::new(&icon_theme::current_theme_name())));
+    let mut icons = resolver.borrow_mut().list();
+    let scores: HashMap<String, u32> = icons.iter().map(|i| (i.name.clone(), icon_theme::suggestion_score(i, hints))).collect();
+    icons.sort_by_key(|i| Reverse(scores[&i.name]));
+    let suggested = scores.values().filter(|s| **s > 0).count();
+    let categories: Rc<HashMap<String, IconCategory>> = Rc::new(icons.iter().map(|i| (i.name.clone(), i.category)).collect());
+    let names = StringList::new(&icons.iter().map(|i| i.name.as_str()).collect::<Vec<_>>());
+
+    let filter = {
+        let (search, category, categories) = (search.clone(), category.clone(), categories.clone());
+        CustomFilter::new(move |obj| {
+            let Some(name) = obj.downcast_ref::<StringObject>().map(|s| s.string()) else { return false };
+            let query = search.text().to_lowercase();
+            if !query.split_whitespace().all(|word| name.to_lowercase().contains(word)) {
+                return false;
+            }
+            let kind = categories.get(name.as_str()).copied().unwrap_or(IconCategory::Other);
+            match category.active_id().as_deref() {
+                Some("apps") => kind == IconCategory::Apps,
+                Some("mimetypes") => kind == IconCategory::MimeTypes,
+                Some("places") => kind == IconCategory::Places,
+                Some("symbolic") => name.ends_with("-symbolic"),
+                _ => true,
+            }
+        })
+    };
+    let filtered = FilterListModel::new(Some(names), Some(filter.clone()));
+    let selection = SingleSelection::new(Some(filtered.clone()));
+    selection.set_autoselect(false);
+    selection.set_can_unselect(true);
+
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        let Some(item) = item.downcast_ref::<ListItem>() else { return };
+        let cell = GtkBox::new(Orientation::Vertical, 4);
+        cell.set_margin_top(6);
+        cell.set_margin_bottom(6);
+        let image = Image::new();
+        image.set_pixel_size(48);
+        cell.append(&image);
+        let label = Label::new(None);
+        label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+        label.set_max_width_chars(14);
+        label.add_css_class("caption");
+        cell.append(&label);
+        item.set_child(Some(&cell));
+    });
+    factory.connect_bind(|_, item| {
+        let Some(item) = item.downcast_ref::<ListItem>() else { return };
+        let (Some(name), Some(cell)) = (item.item().and_downcast::<StringObject>().map(|s| s.string()), item.child()) else { return };
+        if let Some(image) = cell.first_child().and_downcast::<Image>() {
+            image.set_icon_name(Some(&name));
+        }
+        if let Some(label) = cell.last_child().and_downcast::<Label>() {
+            label.set_text(&name);
+        }
+        cell.set_tooltip_text(Some(&name));
+    });
+    let grid = GridView::new(Some(selection.clone()), Some(factory));
+    grid.set_min_columns(3);
+    grid.set_max_columns(12);
+    let scroller = ScrolledWindow::builder().vexpand(true).child(&grid).build();
+
+    // The selected icon at each size, with the file the lookup uses
+    let preview = GtkBox::new(Orientation::Horizontal, 12);
+    preview.set_margin_start(12);
+    preview.set_margin_end(12);
+    let preview_images: Vec<Image> = REPORT_SIZES
+        .iter()
+        .filter(|s| **s <= 128)
+        .map(|&size| {
+            let image = Image::new();
+            image.set_pixel_size(size as i32);
+            image.set_valign(Align::End);
+            image.set_tooltip_text(Some(&format!("{0}×{0}", size)));
+            preview.append(&image);
+            image
+        })
+        .collect();
+    let preview_path = Label::new(None);
+    preview_path.set_xalign(0.0);
+    preview_path.set_wrap(true);
+    preview_path.set_selectable(true);
+    preview_path.add_css_class("dim-label");
+    preview_path.set_margin_start(12);
+    preview_path.set_margin_end(12);
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    status.add_css_class("dim-label");
+    status.set_margin_start(12);
+    status.set_margin_end(12);
+    status.set_margin_bottom(8);
+
+    let root = GtkBox::new(Orientation::Vertical, 6);
+    root.append(&filters);
+    root.append(&scroller);
+    root.append(&preview);
+    root.append(&preview_path);
+    root.append(&status);
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&root));
+    win.set_content(Some(&toolbar_view));
+
+    let theme = resolver.borrow().theme().to_string();
+    let update_status = {
+        let (status, filtered) = (status.clone(), filtered.clone());
+        move || {
+            let mut text = format!("{} icons from the {} theme and its fallbacks", filtered.n_items(), theme);
+            if suggested > 0 {
+                text.push_str(&format!("; {} matching this launcher are listed first", suggested));
+            }
+            status.set_text(&text);
+        }
+    };
+    update_status();
+    filtered.connect_items_changed(move |_, _, _, _| update_status());
+    {
+        let filter = filter.clone();
+        search.connect_search_changed(move |_| filter.changed(FilterChange::Different));
+    }
+    category.connect_changed(move |_| filter.changed(FilterChange::Different));
+
+    let selected_name = |selection: &SingleSelection| selection.selected_item().and_downcast::<StringObject>().map(|s| s.string().to_string());
+    {
+        let (select_btn, preview_path) = (select_btn.clone(), preview_path.clone());
+        selection.connect_selection_changed(move |selection, _, _| {
+            let name = selected_name(selection);
+            select_btn.set_sensitive(name.is_some());
+            for image in &preview_images {
+                image.set_icon_name(name.as_deref());
+            }
+            let path = name.as_deref().and_then(|n| resolver.borrow_mut().lookup(n, 48, 1));
+            preview_path.set_text(&path.map(|p| p.display().to_string()).unwrap_or_default());
+        });
+    }
+
+    let on_pick = Rc::new(on_pick);
+    {
+        let (on_pick, win_c) = (on_pick.clone(), win.clone());
+        grid.connect_activate(move |grid, position| {
+            let name = grid.model().and_then(|m| m.item(position)).and_downcast::<StringObject>().map(|s| s.string());
+            if let Some(name) = name {
+                on_pick(&name);
+                win_c.close();
+            }
+        });
+    }
+    {
+        let win_c = win.clone();
+        select_btn.connect_clicked(move |_| {
+            if let Some(name) = selected_name(&selection) {
+                on_pick(&name);
+                win_c.close();
+            }
+        });
+    }
+
+    win.present();
+}
@@ -5,10 +5,25 @@ use adw::prelude::*;
 use std::cell::RefCell;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 
 use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::template::Template;
+use crate::domain::webapp::WebApp;
+use crate::domain::validation::Severity;
+use crate::services::appimage::AppImage;
 use crate::services::desktop_writer::DesktopWriter;
+use crate::services::mime::MimeDb;
+use crate::services::mimeapps::{self, MimeApps};
+use crate::services::mime_cache::{self, MimeCache};
 use crate::services::desktop_reader::DesktopReader;
+use crate::services::discovery::DiscoveredEntry;
+use crate::services::desktop_copies::DesktopCopies;
+use crate::services::icon_theme::{self, IconResolver};
+use crate::services::overrides::Overrides;
+use crate::services::provisioning::Provisioning;
+use crate::services::templates::Templates;
+use crate::services::launcher::{LaunchEvent, LaunchHandle, Launcher};
 
 pub fn show_main_window(app: &impl IsA<Application>) {
     // Upcast and take a strong reference to GtkApplication (works for both Gtk and Adw apps)
@@ -119,7 +134,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
     let menubar = crate::ui::components::menu_bar::build_menu_bar(&app);
 
     // Toolbar with icons: New, Open, Save, Refresh (icon-only buttons)
-    let crate::ui::components::toolbar::Toolbar { container: toolbar, btn_new, btn_open, btn_save, btn_refresh } = crate::ui::components::toolbar::build_toolbar();
+    let crate::ui::components::toolbar::Toolbar { container: toolbar, btn_new, btn_template, btn_open, btn_save, btn_refresh } = crate::ui::components::toolbar::build_toolbar();
 
     // Main area: sidebar + editor tabs
     let main_area = GtkBox::new(Orientation::Horizontal, 12);
@@ -141,26 +156,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
     // Expose editor widgets locally to reuse existing wiring below
     let type_combo = editor.widgets.type_combo.clone();
     let name_entry = editor.widgets.name_entry.clone();
-    let generic_name_entry = editor.widgets.generic_name_entry.clone();
-    let comment_entry = editor.widgets.comment_entry.clone();
-    let exec_entry = editor.widgets.exec_entry.clone();
     let icon_entry = editor.widgets.icon_entry.clone();
-    let terminal_check = editor.widgets.terminal_check.clone();
-    let nodisplay_check = editor.widgets.nodisplay_check.clone();
-    let startup_check = editor.widgets.startup_check.clone();
-    let categories_entry = editor.widgets.categories_entry.clone();
-    let mimetype_entry = editor.widgets.mimetype_entry.clone();
-    let keywords_entry = editor.widgets.keywords_entry.clone();
-    let onlyshowin_entry = editor.widgets.onlyshowin_entry.clone();
-    let notshowin_entry = editor.widgets.notshowin_entry.clone();
-    let tryexec_entry = editor.widgets.tryexec_entry.clone();
-    let path_entry = editor.widgets.path_entry.clone();
-    let url_entry = editor.widgets.url_entry.clone();
-    let actions_entry = editor.widgets.actions_entry.clone();
-    let localized_name = editor.widgets.localized_name.clone();
-    let localized_gname = editor.widgets.localized_gname.clone();
-    let localized_comment = editor.widgets.localized_comment.clone();
-    let extra_kv = editor.widgets.extra_kv.clone();
     // let source_view = editor.source_view.clone();
 
     // Buttons for Preview/Save
@@ -168,12 +164,23 @@ pub fn show_main_window(app: &impl IsA<Application>) {
     buttons.set_halign(Align::End);
     let delete_btn = Button::with_label("Delete");
     delete_btn.add_css_class("destructive-action");
+    let test_btn = ToggleButton::with_label("Test Launch");
+    let validate_btn = Button::with_label("Validate");
     let preview_btn = Button::with_label("Preview");
     let save_btn = Button::with_label("Save .desktop");
     buttons.append(&delete_btn);
+    buttons.append(&test_btn);
+    buttons.append(&validate_btn);
     buttons.append(&preview_btn);
     buttons.append(&save_btn);
 
+    // Test launch panel, shown with the Test Launch toggle
+    let crate::ui::components::launch_panel::LaunchPanel { container: launch_panel, targets_entry, pick_btn, launch_btn, stop_btn, clear_btn, status: launch_status, output: launch_output } = crate::ui::components::launch_panel::build_launch_panel();
+    {
+        let launch_panel = launch_panel.clone();
+        test_btn.connect_toggled(move |b| launch_panel.set_visible(b.is_active()));
+    }
+
     // Main area composition
     main_area.append(&sidebar_scroller);
     main_area.append(&scroller);
@@ -182,6 +189,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
     root.append(&menubar);
     root.append(&toolbar);
     root.append(&main_area);
+    root.append(&launch_panel);
     root.append(&buttons);
     root.append(&status_bar);
 
@@ -203,64 +211,28 @@ pub fn show_main_window(app: &impl IsA<Application>) {
     // Helpers
 
     let set_form_from_entry = {
-        let type_combo = editor.widgets.type_combo.clone();
-        let name_entry = name_entry.clone();
-        let generic_name_entry = generic_name_entry.clone();
-        let comment_entry = comment_entry.clone();
-        let exec_entry = exec_entry.clone();
-        let icon_entry = icon_entry.clone();
-        let terminal_check = terminal_check.clone();
-        let nodisplay_check = nodisplay_check.clone();
-        let startup_check = startup_check.clone();
-        let categories_entry = categories_entry.clone();
-        let mimetype_entry = mimetype_entry.clone();
-        let keywords_entry = keywords_entry.clone();
-        let onlyshowin_entry = onlyshowin_entry.clone();
-        let notshowin_entry = notshowin_entry.clone();
-        let tryexec_entry = tryexec_entry.clone();
-        let path_entry = path_entry.clone();
-        let url_entry = url_entry.clone();
-        let actions_entry = actions_entry.clone();
-        let localized_name = localized_name.clone();
-        let localized_gname = localized_gname.clone();
-        let localized_comment = localized_comment.clone();
-        let extra_kv = extra_kv.clone();
-        move |de: &DesktopEntry| {
-            // Type
-            let idx = match de.type_field.as_str() { "Application" => 0, "Link" => 1, "Directory" => 2, _ => 0 };
-            type_combo.set_active(Some(idx));
-            name_entry.set_text(&de.name);
-            generic_name_entry.set_text(de.generic_name.as_deref().unwrap_or(""));
-            comment_entry.set_text(de.comment.as_deref().unwrap_or(""));
-            exec_entry.set_text(&de.exec);
-            icon_entry.set_text(de.icon.as_deref().unwrap_or(""));
-            terminal_check.set_active(de.terminal);
-            nodisplay_check.set_active(de.no_display);
-            startup_check.set_active(de.startup_notify);
-            categories_entry.set_text(&de.categories.join(";"));
-            mimetype_entry.set_text(&de.mime_type.join(";"));
-            keywords_entry.set_text(&de.keywords.join(";"));
-            onlyshowin_entry.set_text(&de.only_show_in.join(";"));
-            notshowin_entry.set_text(&de.not_show_in.join(";"));
-            tryexec_entry.set_text(de.try_exec.as_deref().unwrap_or(""));
-            path_entry.set_text(de.path.as_deref().unwrap_or(""));
-            url_entry.set_text(de.url.as_deref().unwrap_or(""));
-            actions_entry.set_text(&de.actions.join(";"));
-            // Localized
-            let ln: Vec<String> = de.name_localized.iter().map(|(l,v)| format!("{}={}", l, v)).collect();
-            let lg: Vec<String> = de.generic_name_localized.iter().map(|(l,v)| format!("{}={}", l, v)).collect();
-            let lc: Vec<String> = de.comment_localized.iter().map(|(l,v)| format!("{}={}", l, v)).collect();
-            localized_name.buffer().set_text(&ln.join("\n"));
-            localized_gname.buffer().set_text(&lg.join("\n"));
-            localized_comment.buffer().set_text(&lc.join("\n"));
-            // Extra
-            let extra: Vec<String> = de.extra.iter().map(|(k,v)| format!("{}={}", k, v)).collect();
-            extra_kv.buffer().set_text(&extra.join("\n"));
-        }
+        let widgets = editor.widgets.clone();
+        move |de: &DesktopEntry| crate::ui::editor::entry_form::set_form_from_entry(&widgets, de)
     };
 
-    // Create or update the temporary in-edit row (disabled/grey)
     use std::rc::Rc as StdRc;
+    // A file that does not parse opens in the Source tab, where its diagnostics are underlined
+    let open_unparsed: StdRc<dyn Fn(&std::path::Path)> = {
+        let set_form = set_form_from_entry.clone();
+        let source_view = editor.source_view.clone();
+        let notebook = editor.notebook.clone();
+        let state = state.clone();
+        StdRc::new(move |path| {
+            if let Ok(text) = std::fs::read_to_string(path) {
+                set_form(&DesktopEntry { type_field: "Application".into(), ..Default::default() });
+                source_view.buffer().set_text(&text);
+                notebook.set_current_page(Some(notebook.n_pages() - 1));
+                state.borrow_mut().selected_path = Some(path.to_path_buf());
+            }
+        })
+    };
+
+    // Create or update the temporary in-edit row (disabled/grey)
     // Create or update the temporary in-edit row (disabled/grey)
     let ensure_temp_row: StdRc<dyn Fn()> = {
         let listbox = listbox.clone();
@@ -318,46 +290,85 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         move || {
             // Clear existing
             while let Some(child) = listbox.first_child() { listbox.remove(&child); }
-            match DesktopReader::list_desktop_files() {
-                Ok(paths) => {
-                    for path in paths {
-                        let (name, icon_str) = match DesktopReader::read_from_path(&path) {
-                            Ok(de) => (de.name, de.icon),
-                            Err(_) => (path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string(), None),
-                        };
-                        let row = ListBoxRow::new();
-                        let hb = GtkBox::new(Orientation::Horizontal, 6);
-
-                        // Icon image (theme name or file path)
-                        let img = if let Some(icon_value) = icon_str.clone() {
-                            if icon_value.contains('/') {
-                                Image::from_file(icon_value)
-                            } else {
-                                Image::from_icon_name(&icon_value)
-                            }
-                        } else {
-                            Image::from_icon_name("application-x-executable-symbolic")
-                        };
-                        img.set_pixel_size(16);
-                        hb.append(&img);
-
-                        let lbl = Label::new(Some(&name));
-                        lbl.set_xalign(0.0);
-                        hb.append(&lbl);
-                        row.set_child(Some(&hb));
-                        row.set_selectable(true);
-                        row.add_css_class("activatable");
-                        // store path on row via data
-                        row.set_widget_name(&path.to_string_lossy());
-                        listbox.append(&row);
-                    }
-                    status_label.set_text("List refreshed");
-                    // If we are creating a new entry, keep showing the temporary grey row
-                    if state_c.borrow().in_edit {
-                        (ensure_temp_row_c)();
+            // One titled section per source (User, System, Flatpak, Snap), sorted by name
+            let mut found: Vec<(DiscoveredEntry, String, Option<String>, bool)> = DesktopReader::list_desktop_files()
+                .into_iter()
+                .map(|d| {
+                    let (name, icon, web) = match DesktopReader::read_from_path(&d.path) {
+                        Ok(de) => { let web = WebApp::from_entry(&de).is_some(); (de.name, de.icon, web) }
+                        Err(_) => (d.id.trim_end_matches(".desktop").to_string(), None, false),
+                    };
+                    (d, name, icon, web)
+                })
+                .collect();
+            found.sort_by_key(|(d, name, _, _)| (d.source, name.to_lowercase()));
+            let mut current_source = None;
+            let mut icons = IconResolver::new(&icon_theme::current_theme_name());
+            for (d, name, icon_str, web) in &found {
+                if current_source != Some(d.source) {
+                    current_source = Some(d.source);
+                    let header = ListBoxRow::new();
+                    let title = Label::new(Some(d.source.label()));
+                    title.set_xalign(0.0);
+                    title.add_css_class("heading");
+                    header.set_child(Some(&title));
+                    header.set_selectable(false);
+                    header.set_activatable(false);
+                    header.set_widget_name(":header");
+                    listbox.append(&header);
+                }
+                let row = ListBoxRow::new();
+                let hb = GtkBox::new(Orientation::Horizontal, 6);
+
+                // Icon image: the file the theme lookup picks, a visible placeholder when none
+                let img = match icon_str.as_deref() {
+                    Some(icon_value) => match icons.lookup(icon_value, 16, listbox.scale_factor().max(1) as u32) {
+                        Some(file) => Image::from_file(file),
+                        None => Image::from_icon_name("image-missing"),
+                    },
+                    None => Image::from_icon_name("application-x-executable-symbolic"),
+                };
+                img.set_pixel_size(16);
+                hb.append(&img);
+
+                let lbl = Label::new(Some(name));
+                lbl.set_xalign(0.0);
+                hb.append(&lbl);
+                if d.is_override() {
+                    let badge = Label::new(Some("overridden"));
+                    badge.add_css_class("caption");
+                    badge.add_css_class("accent");
+                    badge.set_hexpand(true);
+                    badge.set_halign(Align::End);
+                    hb.append(&badge);
+                }
+                if *web {
+                    let badge = Label::new(Some("web app"));
+                    badge.add_css_class("caption");
+                    badge.add_css_class("dim-label");
+                    badge.set_hexpand(true);
+                    badge.set_halign(Align::End);
+                    hb.append(&badge);
+                }
+                row.set_child(Some(&hb));
+                row.set_selectable(true);
+                row.add_css_class("activatable");
+                // A file hidden by another one with the same desktop-file ID
+                match &d.shadowed_by {
+                    Some(winner) => {
+                        row.add_css_class("dim-label");
+                        row.set_tooltip_text(Some(&format!("{}\nShadowed by {}", d.id, winner.display())));
                     }
+                    None => row.set_tooltip_text(Some(&format!("{}\n{}", d.id, d.path.display()))),
                 }
-                Err(e) => status_label.set_text(&format!("Failed to list: {}", e)),
+                // store path on row via data
+                row.set_widget_name(&d.path.to_string_lossy());
+                listbox.append(&row);
+            }
+            status_label.set_text(&format!("{} launchers found", found.len()));
+            // If we are creating a new entry, keep showing the temporary grey row
+            if state_c.borrow().in_edit {
+                (ensure_temp_row_c)();
             }
         }
     };
@@ -370,9 +381,10 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         let status_label = status_label.clone();
         let remove_temp_row_c = remove_temp_row.clone();
         let type_combo_sel = type_combo.clone();
+        let open_unparsed = open_unparsed.clone();
         listbox.connect_row_activated(move |_, row| {
-            // Ignore activation on temporary in-edit row
-            if row.widget_name() == ":unsaved" {
+            // Ignore activation on temporary in-edit row and section headers
+            if row.widget_name().starts_with(':') {
                 return;
             }
             // If we were editing a new entry, stop and remove temp row
@@ -390,7 +402,8 @@ pub fn show_main_window(app: &impl IsA<Application>) {
                     status_label.set_text(&path.to_string_lossy());
                 }
                 Err(e) => {
-                    status_label.set_text(&format!("Open failed: {}", e));
+                    open_unparsed(&path);
+                    status_label.set_text(&format!("Open failed: {:#}", e));
                 }
             }
         });
@@ -422,6 +435,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         let remove_temp_row_c = remove_temp_row.clone();
         let state_c2 = state.clone();
         let type_combo_open_btn = editor.widgets.type_combo.clone();
+        let open_unparsed_btn = open_unparsed.clone();
         btn_open.connect_clicked(move |_| {
             let dialog = FileChooserDialog::new(Some("Open .desktop"), None::<&ApplicationWindow>, FileChooserAction::Open, &[("Cancel", ResponseType::Cancel), ("Open", ResponseType::Accept)]);
             let status_label2 = status_label.clone();
@@ -429,6 +443,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
             let remove_temp_row_c2 = remove_temp_row_c.clone();
             let state_c3 = state_c2.clone();
             let type_combo_open_btn2 = type_combo_open_btn.clone();
+            let open_unparsed2 = open_unparsed_btn.clone();
             dialog.connect_response(move |d, resp| {
                 if resp == ResponseType::Accept {
                     (remove_temp_row_c2)();
@@ -442,7 +457,10 @@ pub fn show_main_window(app: &impl IsA<Application>) {
                                 state_c3.borrow_mut().selected_path = Some(path.clone());
                                 status_label2.set_text(&path.to_string_lossy());
                             }
-                            Err(e) => status_label2.set_text(&format!("Open failed: {}", e)),
+                            Err(e) => {
+                                open_unparsed2(&path);
+                                status_label2.set_text(&format!("Open failed: {:#}", e));
+                            }
                         }
                     }}
                 }
@@ -452,36 +470,15 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         });
     }
     {
-        let type_combo = editor.widgets.type_combo.clone();
-        let name_entry = name_entry.clone();
-        let generic_name_entry = generic_name_entry.clone();
-        let comment_entry = comment_entry.clone();
-        let exec_entry = exec_entry.clone();
-        let icon_entry = icon_entry.clone();
-        let terminal_check = terminal_check.clone();
-        let nodisplay_check = nodisplay_check.clone();
-        let startup_check = startup_check.clone();
-        let categories_entry = categories_entry.clone();
-        let mimetype_entry = mimetype_entry.clone();
-        let keywords_entry = keywords_entry.clone();
-        let onlyshowin_entry = onlyshowin_entry.clone();
-        let notshowin_entry = notshowin_entry.clone();
-        let tryexec_entry = tryexec_entry.clone();
-        let path_entry = path_entry.clone();
-        let url_entry = url_entry.clone();
-        let actions_entry = actions_entry.clone();
-        let localized_name = localized_name.clone();
-        let localized_gname = localized_gname.clone();
-        let localized_comment = localized_comment.clone();
-        let extra_kv = extra_kv.clone();
+        let widgets = editor.widgets.clone();
         let status_label = status_label.clone();
         let state_c = state.clone();
         btn_save.connect_clicked(move |_| {
-            match crate::ui::helpers::collect_entry(&type_combo, &name_entry, &generic_name_entry, &comment_entry, &exec_entry, &icon_entry, &terminal_check, &nodisplay_check, &startup_check, &categories_entry, &mimetype_entry, &keywords_entry, &onlyshowin_entry, &notshowin_entry, &tryexec_entry, &path_entry, &url_entry, &actions_entry, &localized_name, &localized_gname, &localized_comment, &extra_kv) {
+            match crate::ui::editor::entry_form::collect_entry(&widgets) {
                 Ok(de) => {
                     if let Some(path) = state_c.borrow().selected_path.clone() {
                         match DesktopWriter::write_to_path(&de, &path) {
-                            Ok(_) => { status_label.set_text(&format!("Updated: {}", path.display())); }
+                            Ok(_) => { status_label.set_text(&format!("Updated: {}{}", path.display(), sync_desktop_copies(&de, &path))); }
                             Err(e) => status_label.set_text(&format!("Save failed: {}", e)),
                         }
                     } else {
@@ -523,6 +520,102 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         });
         app_add_new.add_action(&new_action);
 
+        // app.new_from_template: gallery, then the filled-in template as a new entry
+        let set_form = set_form_from_entry.clone();
+        let template_action = SimpleAction::new("new_from_template", None);
+        let win_template = win.clone();
+        let state_template = state.clone();
+        let ensure_temp_row_template = ensure_temp_row.clone();
+        let type_combo_template = editor.widgets.type_combo.clone();
+        let status_label_template = status_label.clone();
+        template_action.connect_activate(move |_, _| {
+            let set_form = set_form.clone();
+            let state_template = state_template.clone();
+            let ensure_temp_row_template = ensure_temp_row_template.clone();
+            let type_combo_template = type_combo_template.clone();
+            let status_label_template = status_label_template.clone();
+            crate::ui::windows::template_window::show_template_window(&win_template, move |de, title| {
+                set_form(&de);
+                let mut st = state_template.borrow_mut();
+                st.selected_path = None;
+                st.in_edit = true;
+                drop(st);
+                (ensure_temp_row_template)();
+                type_combo_template.set_sensitive(true);
+                status_label_template.set_text(&format!("New entry from template \"{}\" (not saved yet)", title));
+            });
+        });
+        app.add_action(&template_action);
+        {
+            let app_c = app.clone();
+            btn_template.connect_clicked(move |_| app_c.activate_action("new_from_template", None));
+        }
+
+        // app.new_from_flatpak: installed Flatpak apps, then a `flatpak run` launcher for one
+        let set_form = set_form_from_entry.clone();
+        let flatpak_action = SimpleAction::new("new_from_flatpak", None);
+        let win_flatpak = win.clone();
+        let state_flatpak = state.clone();
+        let ensure_temp_row_flatpak = ensure_temp_row.clone();
+        let type_combo_flatpak = editor.widgets.type_combo.clone();
+        let status_label_flatpak = status_label.clone();
+        flatpak_action.connect_activate(move |_, _| {
+            let set_form = set_form.clone();
+            let state_flatpak = state_flatpak.clone();
+            let ensure_temp_row_flatpak = ensure_temp_row_flatpak.clone();
+            let type_combo_flatpak = type_combo_flatpak.clone();
+            let status_label_flatpak = status_label_flatpak.clone();
+            crate::ui::windows::flatpak_window::show_flatpak_window(&win_flatpak, move |de, id| {
+                set_form(&de);
+                let mut st = state_flatpak.borrow_mut();
+                st.selected_path = None;
+                st.in_edit = true;
+                drop(st);
+                (ensure_temp_row_flatpak)();
+                type_combo_flatpak.set_sensitive(true);
+                status_label_flatpak.set_text(&format!("New entry for Flatpak app {} (not saved yet)", id));
+            });
+        });
+        app.add_action(&flatpak_action);
+
+        // app.save_as_template: the form as a user template, without variables
+        let save_template_action = SimpleAction::new("save_as_template", None);
+        let win_save_template = win.clone();
+        let widgets_template = editor.widgets.clone();
+        let status_label_save_template = status_label.clone();
+        save_template_action.connect_activate(move |_, _| {
+            let de = crate::ui::editor::entry_form::read_entry(&widgets_template);
+            let dialog = gtk4::MessageDialog::builder()
+                .transient_for(&win_save_template)
+                .modal(true)
+                .title("Save as Template")
+                .text("Save the current entry as a template")
+                .secondary_text("Edit the saved file to turn values into {{variables}}.")
+                .build();
+            let area = dialog.message_area().downcast::<GtkBox>().expect("message area is a box");
+            let (title_row, title_entry) = crate::ui::components::labeled_entry("Title");
+            title_entry.set_text(&de.name);
+            let (desc_row, desc_entry) = crate::ui::components::labeled_entry("Description");
+            area.append(&title_row);
+            area.append(&desc_row);
+            dialog.add_button("Cancel", ResponseType::Cancel);
+            dialog.add_button("Save", ResponseType::Accept);
+            let win_save_template = win_save_template.clone();
+            let status_label_save_template = status_label_save_template.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept {
+                    let template = Template::from_entry(&de, title_entry.text().trim(), desc_entry.text().trim());
+                    match Templates::save(&template, false) {
+                        Ok(path) => status_label_save_template.set_text(&format!("Template saved: {}", path.display())),
+                        Err(e) => show_error(&win_save_template, &format!("{:#}", e)),
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+        app.add_action(&save_template_action);
+
         // app.open
         let set_form = set_form_from_entry.clone();
         let status_label_open = status_label.clone();
@@ -531,12 +624,14 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         let type_combo_open_action = editor.widgets.type_combo.clone();
         let _remove_temp_row_c = remove_temp_row.clone();
         let state_for_open_action = state.clone();
+        let open_unparsed_action = open_unparsed.clone();
         open_action.connect_activate(move |_, _| {
             let dialog = FileChooserDialog::new(Some("Open .desktop"), None::<&ApplicationWindow>, FileChooserAction::Open, &[("Cancel", ResponseType::Cancel), ("Open", ResponseType::Accept)]);
             let status_label2 = status_label_open.clone();
             let set_form2 = set_form.clone();
             let type_combo_open_action2 = type_combo_open_action.clone();
             let state_open = state_for_open_action.clone();
+            let open_unparsed2 = open_unparsed_action.clone();
             dialog.connect_response(move |d, resp| {
                 if resp == ResponseType::Accept {
                     if let Some(file) = d.file() { if let Some(path) = file.path() {
@@ -548,7 +643,10 @@ pub fn show_main_window(app: &impl IsA<Application>) {
                                 state_open.borrow_mut().selected_path = Some(path.clone());
                                 status_label2.set_text(&path.to_string_lossy());
                             }
-                            Err(e) => status_label2.set_text(&format!("Open failed: {}", e)),
+                            Err(e) => {
+                                open_unparsed2(&path);
+                                status_label2.set_text(&format!("Open failed: {:#}", e));
+                            }
                         }
                     }}
                 }
@@ -559,49 +657,28 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         app_add_open.add_action(&open_action);
 
         // app.save
-        let type_combo = editor.widgets.type_combo.clone();
-        let name_entry = name_entry.clone();
-        let generic_name_entry = generic_name_entry.clone();
-        let comment_entry = comment_entry.clone();
-        let exec_entry = exec_entry.clone();
-        let icon_entry = icon_entry.clone();
-        let terminal_check = terminal_check.clone();
-        let nodisplay_check = nodisplay_check.clone();
-        let startup_check = startup_check.clone();
-        let categories_entry = categories_entry.clone();
-        let mimetype_entry = mimetype_entry.clone();
-        let keywords_entry = keywords_entry.clone();
-        let onlyshowin_entry = onlyshowin_entry.clone();
-        let notshowin_entry = notshowin_entry.clone();
-        let tryexec_entry = tryexec_entry.clone();
-        let path_entry = path_entry.clone();
-        let url_entry = url_entry.clone();
-        let actions_entry = actions_entry.clone();
-        let localized_name = localized_name.clone();
-        let localized_gname = localized_gname.clone();
-        let localized_comment = localized_comment.clone();
-        let extra_kv = extra_kv.clone();
-        let status_label = status_label.clone();
+        let widgets = editor.widgets.clone();
+        let status_label_save = status_label.clone();
         let app_c = app.clone();
         let save_action = SimpleAction::new("save", None);
         let state_c = state.clone();
         save_action.connect_activate(move |_, _| {
-            match crate::ui::helpers::collect_entry(&type_combo, &name_entry, &generic_name_entry, &comment_entry, &exec_entry, &icon_entry, &terminal_check, &nodisplay_check, &startup_check, &categories_entry, &mimetype_entry, &keywords_entry, &onlyshowin_entry, &notshowin_entry, &tryexec_entry, &path_entry, &url_entry, &actions_entry, &localized_name, &localized_gname, &localized_comment, &extra_kv) {
+            match crate::ui::editor::entry_form::collect_entry(&widgets) {
                 Ok(de) => {
                     if let Some(path) = state_c.borrow().selected_path.clone() {
                         match DesktopWriter::write_to_path(&de, &path) {
-                            Ok(_) => { status_label.set_text(&format!("Updated: {}", path.display())); }
-                            Err(e) => status_label.set_text(&format!("Save failed: {}", e)),
+                            Ok(_) => { status_label_save.set_text(&format!("Updated: {}{}", path.display(), sync_desktop_copies(&de, &path))); }
+                            Err(e) => status_label_save.set_text(&format!("Save failed: {}", e)),
                         }
                     } else {
                         let fname = if !de.name.trim().is_empty() { de.name.clone() } else { "desktop-entry".into() };
                         match DesktopWriter::write(&de, &fname, true) {
-                            Ok(path) => { status_label.set_text(&format!("Saved: {}", path.display())); }
-                            Err(e) => status_label.set_text(&format!("Save failed: {}", e)),
+                            Ok(path) => { status_label_save.set_text(&format!("Saved: {}", path.display())); }
+                            Err(e) => status_label_save.set_text(&format!("Save failed: {}", e)),
                         }
                     }
                 }
-                Err(e) => status_label.set_text(&format!("Invalid: {}", e)),
+                Err(e) => status_label_save.set_text(&format!("Invalid: {}", e)),
             }
         });
         app_c.add_action(&save_action);
@@ -642,8 +719,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         open_user.connect_activate(move |_, _| {
             #[cfg(target_os = "linux")]
             {
-                if let Some(base) = directories::BaseDirs::new() {
-                    let path = base.data_dir().join("applications");
+                if let Some(path) = crate::services::discovery::user_applications_dir() {
                     if let Err(e) = open::that(&path) {
                         show_error(&win_err2, &format!("Failed to open user dir: {}", e));
                     }
@@ -654,6 +730,370 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         });
         app_for_add.add_action(&open_user);
 
+        // Tools: copy the selected system launcher to the user dir and edit the copy
+        let app_for_add = app.clone();
+        let override_action = SimpleAction::new("override_launcher", None);
+        let win_override = win.clone();
+        let state_override = state.clone();
+        let set_form = set_form_from_entry.clone();
+        let refresh = refresh_list.clone();
+        let status_label_override = status_label.clone();
+        let type_combo_override = editor.widgets.type_combo.clone();
+        override_action.connect_activate(move |_, _| {
+            let Some(path) = state_override.borrow().selected_path.clone() else {
+                show_error(&win_override, "Select a system launcher to override");
+                return;
+            };
+            match Overrides::create(&path).and_then(|dest| DesktopReader::read_from_path(&dest).map(|de| (dest, de))) {
+                Ok((dest, de)) => {
+                    set_form(&de);
+                    type_combo_override.set_sensitive(false);
+                    state_override.borrow_mut().selected_path = Some(dest.clone());
+                    refresh();
+                    status_label_override.set_text(&format!("Editing override {}", dest.display()));
+                }
+                Err(e) => show_error(&win_override, &format!("{:#}", e)),
+            }
+        });
+        app_for_add.add_action(&override_action);
+
+        // Tools: delete the selected override so the system version applies again
+        let app_for_add = app.clone();
+        let revert_action = SimpleAction::new("revert_override", None);
+        let win_revert = win.clone();
+        let state_revert = state.clone();
+        let set_form = set_form_from_entry.clone();
+        let refresh = refresh_list.clone();
+        let status_label_revert = status_label.clone();
+        revert_action.connect_activate(move |_, _| {
+            let Some(path) = state_revert.borrow().selected_path.clone() else {
+                show_error(&win_revert, "Select an overridden launcher to revert");
+                return;
+            };
+            let dialog = gtk4::MessageDialog::builder()
+                .transient_for(&win_revert)
+                .modal(true)
+                .title("Revert to system version")
+                .text("Delete your override?")
+                .secondary_text(format!("Your changes in {} will be lost.", path.display()))
+                .build();
+            dialog.add_button("Cancel", ResponseType::Cancel);
+            dialog.add_button("Revert", ResponseType::Accept);
+            let win_revert = win_revert.clone();
+            let state_revert = state_revert.clone();
+            let set_form = set_form.clone();
+            let refresh = refresh.clone();
+            let status_label_revert = status_label_revert.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept {
+                    match Overrides::revert(&path) {
+                        Ok(system) => {
+                            if let Ok(de) = DesktopReader::read_from_path(&system) {
+                                set_form(&de);
+                            }
+                            state_revert.borrow_mut().selected_path = Some(system.clone());
+                            refresh();
+                            status_label_revert.set_text(&format!("Reverted to {}", system.display()));
+                        }
+                        Err(e) => show_error(&win_revert, &format!("{:#}", e)),
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+        app_for_add.add_action(&revert_action);
+
+        // File: import a JSON/TOML launcher document as a new, unsaved entry
+        let set_form = set_form_from_entry.clone();
+        let import_action = SimpleAction::new("import", None);
+        let win_import = win.clone();
+        let state_import = state.clone();
+        let ensure_temp_row_import = ensure_temp_row.clone();
+        let type_combo_import = editor.widgets.type_combo.clone();
+        let status_label_import = status_label.clone();
+        import_action.connect_activate(move |_, _| {
+            let dialog = FileChooserDialog::new(Some("Import Launcher Document"), Some(&win_import), FileChooserAction::Open, &[("Cancel", ResponseType::Cancel), ("Import", ResponseType::Accept)]);
+            let filter = gtk4::FileFilter::new();
+            filter.add_pattern("*.json");
+            filter.add_pattern("*.toml");
+            filter.set_name(Some("Launcher documents (JSON, TOML)"));
+            dialog.add_filter(&filter);
+            let set_form = set_form.clone();
+            let win_import = win_import.clone();
+            let state_import = state_import.clone();
+            let ensure_temp_row_import = ensure_temp_row_import.clone();
+            let type_combo_import = type_combo_import.clone();
+            let status_label_import = status_label_import.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept
+                    && let Some(path) = d.file().and_then(|f| f.path())
+                {
+                    match DesktopReader::read_document(&path) {
+                        Ok(de) => {
+                            set_form(&de);
+                            let mut st = state_import.borrow_mut();
+                            st.selected_path = None;
+                            st.in_edit = true;
+                            drop(st);
+                            (ensure_temp_row_import)();
+                            type_combo_import.set_sensitive(true);
+                            status_label_import.set_text(&format!("Imported {} (not saved yet)", path.display()));
+                        }
+                        Err(e) => show_error(&win_import, &format!("{:#}", e)),
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+        app.add_action(&import_action);
+
+        // File: build a new, unsaved entry from the .desktop file and icon inside an AppImage
+        let set_form = set_form_from_entry.clone();
+        let import_appimage_action = SimpleAction::new("import_appimage", None);
+        let win_appimage = win.clone();
+        let state_appimage = state.clone();
+        let ensure_temp_row_appimage = ensure_temp_row.clone();
+        let type_combo_appimage = editor.widgets.type_combo.clone();
+        let status_label_appimage = status_label.clone();
+        import_appimage_action.connect_activate(move |_, _| {
+            let dialog = FileChooserDialog::new(Some("Import AppImage"), Some(&win_appimage), FileChooserAction::Open, &[("Cancel", ResponseType::Cancel), ("Import", ResponseType::Accept)]);
+            let filter = gtk4::FileFilter::new();
+            filter.add_pattern("*.AppImage");
+            filter.add_pattern("*.appimage");
+            filter.set_name(Some("AppImages"));
+            dialog.add_filter(&filter);
+            let set_form = set_form.clone();
+            let win_appimage = win_appimage.clone();
+            let state_appimage = state_appimage.clone();
+            let ensure_temp_row_appimage = ensure_temp_row_appimage.clone();
+            let type_combo_appimage = type_combo_appimage.clone();
+            let status_label_appimage = status_label_appimage.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept
+                    && let Some(path) = d.file().and_then(|f| f.path())
+                {
+                    match AppImage::import(&path) {
+                        Ok(imported) => {
+                            set_form(&imported.entry);
+                            let mut st = state_appimage.borrow_mut();
+                            st.selected_path = None;
+                            st.in_edit = true;
+                            drop(st);
+                            (ensure_temp_row_appimage)();
+                            type_combo_appimage.set_sensitive(true);
+                            let mut msg = format!("Imported {} (not saved yet)", path.display());
+                            if !imported.icons.is_empty() {
+                                msg.push_str(&format!("; icon installed into the hicolor theme ({} files)", imported.icons.len()));
+                            }
+                            for note in &imported.notes {
+                                msg.push_str(&format!("; {}", note));
+                            }
+                            status_label_appimage.set_text(&msg);
+                        }
+                        Err(e) => show_error(&win_appimage, &format!("{:#}", e)),
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+        app.add_action(&import_appimage_action);
+
+        // File: export the form as a JSON/TOML launcher document
+        let export_action = SimpleAction::new("export", None);
+        let win_export = win.clone();
+        let widgets_export = editor.widgets.clone();
+        let status_label_export = status_label.clone();
+        export_action.connect_activate(move |_, _| {
+            let de = crate::ui::editor::entry_form::read_entry(&widgets_export);
+            let dialog = FileChooserDialog::new(Some("Export Launcher Document"), Some(&win_export), FileChooserAction::Save, &[("Cancel", ResponseType::Cancel), ("Export", ResponseType::Accept)]);
+            let base = if de.name.trim().is_empty() { "launcher".to_string() } else { de.name.trim().to_string() };
+            dialog.set_current_name(&format!("{}.json", base));
+            let win_export = win_export.clone();
+            let status_label_export = status_label_export.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept
+                    && let Some(path) = d.file().and_then(|f| f.path())
+                {
+                    match DesktopWriter::write_document(&de, &path) {
+                        Ok(p) => status_label_export.set_text(&format!("Exported {}", p.display())),
+                        Err(e) => show_error(&win_export, &format!("{:#}", e)),
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+        app.add_action(&export_action);
+
+        // File: executable, trusted copy of the saved launcher on the desktop
+        let app_for_add = app.clone();
+        let place_action = SimpleAction::new("place_on_desktop", None);
+        let win_place = win.clone();
+        let state_place = state.clone();
+        let widgets_place = editor.widgets.clone();
+        let status_label_place = status_label.clone();
+        place_action.connect_activate(move |_, _| {
+            let Some(path) = state_place.borrow().selected_path.clone() else {
+                show_error(&win_place, "Save the launcher before placing it on the desktop");
+                return;
+            };
+            let result = crate::ui::editor::entry_form::collect_entry(&widgets_place)
+                .map_err(|e| anyhow::anyhow!(e))
+                .and_then(|de| DesktopCopies::place(&de, &path));
+            match result {
+                Ok(placed) => {
+                    let note = match placed.trusted {
+                        Ok(()) => String::new(),
+                        Err(e) => format!(" (could not mark as trusted: {})", e),
+                    };
+                    status_label_place.set_text(&format!("Placed on desktop: {}{}", placed.path.display(), note));
+                }
+                Err(e) => show_error(&win_place, &format!("{:#}", e)),
+            }
+        });
+        app_for_add.add_action(&place_action);
+
+        // Tools: make the saved launcher the default for its MimeType types and URL schemes
+        let app_for_add = app.clone();
+        let default_action = SimpleAction::new("make_default", None);
+        let win_default = win.clone();
+        let state_default = state.clone();
+        let widgets_default = editor.widgets.clone();
+        let status_label_default = status_label.clone();
+        default_action.connect_activate(move |_, _| {
+            let Some(path) = state_default.borrow().selected_path.clone() else {
+                show_error(&win_default, "Save the launcher before making it a default application");
+                return;
+            };
+            let Some(id) = mimeapps::desktop_id(&path) else {
+                show_error(&win_default, "Only launchers in an applications directory can be default applications");
+                return;
+            };
+            let types = crate::ui::editor::entry_form::read_entry(&widgets_default).mime_type;
+            if types.is_empty() {
+                show_error(&win_default, "Add the types it opens to MimeType first (x-scheme-handler/<scheme> for URL schemes)");
+                return;
+            }
+            let apps = MimeApps::load();
+            let lines: Vec<String> = types
+                .iter()
+                .map(|t| match apps.default_for(t) {
+                    Some(h) if h.id == id => format!("{} (already the default)", t),
+                    Some(h) => format!("{} (now {})", t, h.id),
+                    None => t.clone(),
+                })
+                .collect();
+            let dialog = gtk4::MessageDialog::builder()
+                .transient_for(&win_default)
+                .modal(true)
+                .title("Make Default")
+                .text(format!("Open these types with {}?", id))
+                .secondary_text(lines.join("\n"))
+                .build();
+            dialog.add_button("Cancel", ResponseType::Cancel);
+            dialog.add_button("Make Default", ResponseType::Accept);
+            let (win_c, widgets_c, status_c) = (win_default.clone(), widgets_default.clone(), status_label_default.clone());
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept {
+                    match MimeApps::set_default(&id, &types) {
+                        Ok(written) => {
+                            crate::ui::editor::entry_form::update_mime_defaults(&widgets_c);
+                            let files: Vec<String> = written.iter().map(|p| p.display().to_string()).collect();
+                            status_c.set_text(&format!("{} is now the default for {} type(s); updated {}", id, types.len(), files.join(", ")));
+                        }
+                        Err(e) => show_error(&win_c, &format!("{:#}", e)),
+                    }
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+        app_for_add.add_action(&default_action);
+
+        // Tools: rebuild mimeinfo.cache of the user applications dir, as update-desktop-database would
+        let app_for_add = app.clone();
+        let mime_cache_action = SimpleAction::new("update_mime_cache", None);
+        let win_mime_cache = win.clone();
+        let widgets_mime_cache = editor.widgets.clone();
+        let status_label_mime_cache = status_label.clone();
+        mime_cache_action.connect_activate(move |_, _| {
+            let result = DesktopWriter::user_applications_dir().and_then(|dir| MimeCache::rebuild(&dir).map(|n| (dir, n)));
+            match result {
+                Ok((dir, count)) => {
+                    crate::ui::editor::entry_form::update_mime_defaults(&widgets_mime_cache);
+                    status_label_mime_cache.set_text(&format!("Updated {} ({} types)", dir.join(mime_cache::FILE_NAME).display(), count));
+                }
+                Err(e) => show_error(&win_mime_cache, &format!("{:#}", e)),
+            }
+        });
+        app_for_add.add_action(&mime_cache_action);
+
+        // Tools: autostart manager
+        let app_for_add = app.clone();
+        let autostart_action = SimpleAction::new("autostart", None);
+        let win_autostart = win.clone();
+        autostart_action.connect_activate(move |_, _| {
+            crate::ui::windows::autostart_window::show_autostart_window(&win_autostart);
+        });
+        app_for_add.add_action(&autostart_action);
+
+        // Tools: apply a folder of launcher manifests, after showing the plan
+        let app_for_add = app.clone();
+        let apply_action = SimpleAction::new("apply_manifests", None);
+        let win_apply = win.clone();
+        let refresh = refresh_list.clone();
+        let status_label_apply = status_label.clone();
+        apply_action.connect_activate(move |_, _| {
+            let chooser = FileChooserDialog::new(Some("Apply Manifest Folder"), Some(&win_apply), FileChooserAction::SelectFolder, &[("Cancel", ResponseType::Cancel), ("Review", ResponseType::Accept)]);
+            let win_apply = win_apply.clone();
+            let refresh = refresh.clone();
+            let status_label_apply = status_label_apply.clone();
+            chooser.connect_response(move |d, resp| {
+                let dir = d.file().and_then(|f| f.path());
+                d.close();
+                let Some(dir) = dir.filter(|_| resp == ResponseType::Accept) else { return };
+                let plan = match Provisioning::plan(&dir) {
+                    Ok(plan) => plan,
+                    Err(e) => {
+                        show_error(&win_apply, &format!("{:#}", e));
+                        return;
+                    }
+                };
+                let dialog = gtk4::MessageDialog::builder()
+                    .transient_for(&win_apply)
+                    .modal(true)
+                    .title("Apply manifests")
+                    .text(if plan.problems.is_empty() { "Apply these changes?" } else { "Fix these problems first" })
+                    .secondary_text(plan.to_string())
+                    .build();
+                dialog.add_button("Cancel", ResponseType::Cancel);
+                if plan.problems.is_empty() && plan.changes() > 0 {
+                    dialog.add_button("Apply", ResponseType::Accept);
+                }
+                let win_apply = win_apply.clone();
+                let refresh = refresh.clone();
+                let status_label_apply = status_label_apply.clone();
+                dialog.connect_response(move |d, resp| {
+                    if resp == ResponseType::Accept {
+                        match Provisioning::apply(&plan) {
+                            Ok(n) => {
+                                refresh();
+                                status_label_apply.set_text(&format!("Applied {} change(s) from {}", n, plan.owner));
+                            }
+                            Err(e) => show_error(&win_apply, &format!("{:#}", e)),
+                        }
+                    }
+                    d.close();
+                });
+                dialog.show();
+            });
+            chooser.show();
+        });
+        app_for_add.add_action(&apply_action);
+
         // Help: About dialog
         let app_for_add = app.clone();
         let about = SimpleAction::new("about", None);
@@ -702,7 +1142,6 @@ pub fn show_main_window(app: &impl IsA<Application>) {
 
     // Delete handler
     {
-        use std::fs;
         let win_del = win.clone();
         let state_del = state.clone();
         let set_form = set_form_from_entry.clone();
@@ -729,20 +1168,27 @@ pub fn show_main_window(app: &impl IsA<Application>) {
                 let type_combo_del2 = type_combo_del.clone();
                 dialog.connect_response(move |d, resp| {
                     if resp == ResponseType::Accept {
-                        if let Err(e) = fs::remove_file(&path) {
-                            let err = format!("Failed to delete: {}", e);
-                            show_error(&win_del_c, &err);
-                        } else {
-                            // Clear form
-                            set_form_c(&DesktopEntry { name: String::new(), type_field: "Application".into(), ..Default::default() });
-                            // Allow changing type after deletion (blank state)
-                            type_combo_del2.set_sensitive(true);
-                            // Reset selection
-                            state_del_c.borrow_mut().selected_path = None;
-                            // Refresh list
-                            refresh_c();
-                            // Update status
-                            status_label_del_c.set_text("Deleted");
+                        match DesktopWriter::delete(&path) {
+                            Err(e) => {
+                                let err = format!("Failed to delete: {:#}", e);
+                                show_error(&win_del_c, &err);
+                            }
+                            Ok(removed_icons) => {
+                                // Clear form
+                                set_form_c(&DesktopEntry { name: String::new(), type_field: "Application".into(), ..Default::default() });
+                                // Allow changing type after deletion (blank state)
+                                type_combo_del2.set_sensitive(true);
+                                // Reset selection
+                                state_del_c.borrow_mut().selected_path = None;
+                                // Refresh list
+                                refresh_c();
+                                // Update status
+                                if removed_icons.is_empty() {
+                                    status_label_del_c.set_text("Deleted");
+                                } else {
+                                    status_label_del_c.set_text(&format!("Deleted, along with its installed icon ({} files)", removed_icons.len()));
+                                }
+                            }
                         }
                     }
                     d.close();
@@ -755,37 +1201,123 @@ pub fn show_main_window(app: &impl IsA<Application>) {
         });
     }
 
+    // Validate handler: every finding, warnings included
+    let widgets_validate = editor.widgets.clone();
+    let win_validate = win.clone();
+    validate_btn.connect_clicked(move |_| {
+        let de = crate::ui::editor::entry_form::read_entry(&widgets_validate);
+        let findings = de.validate(Some(MimeDb::load_installed()));
+        let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+        let text = if findings.is_empty() {
+            "No problems found".to_string()
+        } else {
+            format!("{} error(s), {} warning(s)", errors, findings.len() - errors)
+        };
+        let details: Vec<String> = findings.iter().map(|f| f.to_string()).collect();
+        let dialog = gtk4::MessageDialog::builder()
+            .transient_for(&win_validate)
+            .modal(true)
+            .title("Validate")
+            .text(&text)
+            .secondary_text(details.join("\n"))
+            .build();
+        dialog.add_button("Close", ResponseType::Close);
+        dialog.connect_response(|d, _| d.close());
+        dialog.show();
+    });
+
+    // Test launch handlers
+    let running: Rc<RefCell<Option<LaunchHandle>>> = Rc::new(RefCell::new(None));
+    {
+        let targets_entry = targets_entry.clone();
+        let win_pick = win.clone();
+        pick_btn.connect_clicked(move |_| {
+            let dialog = FileChooserDialog::new(Some("Sample Files"), Some(&win_pick), FileChooserAction::Open, &[("Cancel", ResponseType::Cancel), ("Select", ResponseType::Accept)]);
+            dialog.set_select_multiple(true);
+            let targets_entry = targets_entry.clone();
+            dialog.connect_response(move |d, resp| {
+                if resp == ResponseType::Accept {
+                    let files = d.files();
+                    let mut targets: Vec<String> = split_targets(&targets_entry.text());
+                    for i in 0..files.n_items() {
+                        if let Some(path) = files.item(i).and_downcast::<gtk4::gio::File>().and_then(|f| f.path()) {
+                            targets.push(path.to_string_lossy().into_owned());
+                        }
+                    }
+                    targets_entry.set_text(&targets.join("; "));
+                }
+                d.close();
+            });
+            dialog.show();
+        });
+    }
+    {
+        let widgets_launch = editor.widgets.clone();
+        let state = state.clone();
+        let running = running.clone();
+        let launch_status = launch_status.clone();
+        let launch_output = launch_output.clone();
+        let stop_btn = stop_btn.clone();
+        launch_btn.connect_clicked(move |btn| {
+            let de = crate::ui::editor::entry_form::read_entry(&widgets_launch);
+            let targets = split_targets(&targets_entry.text());
+            let location = state.borrow().selected_path.clone();
+            match Launcher::spawn(&de, &targets, location.as_deref()) {
+                Ok(handle) => {
+                    append_output(&launch_output, &format!("$ {}\n", handle.argv.join(" ")), false);
+                    launch_status.set_text(&format!("Running (pid {})", handle.pid));
+                    *running.borrow_mut() = Some(handle);
+                    btn.set_sensitive(false);
+                    stop_btn.set_sensitive(true);
+                    // Poll the child's output from the main loop
+                    let running = running.clone();
+                    let launch_status = launch_status.clone();
+                    let launch_output = launch_output.clone();
+                    let launch_btn = btn.clone();
+                    let stop_btn = stop_btn.clone();
+                    gtk4::glib::timeout_add_local(Duration::from_millis(100), move || {
+                        let mut finished = None;
+                        if let Some(handle) = running.borrow().as_ref() {
+                            while let Ok(event) = handle.events.try_recv() {
+                                match event {
+                                    LaunchEvent::Stdout(line) => append_output(&launch_output, &format!("{}\n", line), false),
+                                    LaunchEvent::Stderr(line) => append_output(&launch_output, &format!("{}\n", line), true),
+                                    LaunchEvent::Exited(status) => finished = Some(format!("Finished: {}", status)),
+                                    LaunchEvent::Failed(err) => finished = Some(format!("Lost track of the process: {}", err)),
+                                }
+                            }
+                        }
+                        match finished {
+                            Some(text) => {
+                                launch_status.set_text(&text);
+                                *running.borrow_mut() = None;
+                                launch_btn.set_sensitive(true);
+                                stop_btn.set_sensitive(false);
+                                gtk4::glib::ControlFlow::Break
+                            }
+                            None => gtk4::glib::ControlFlow::Continue,
+                        }
+                    });
+                }
+                Err(err) => launch_status.set_text(&format!("Launch failed: {:#}", err)),
+            }
+        });
+    }
+    {
+        let running = running.clone();
+        stop_btn.connect_clicked(move |_| {
+            if let Some(handle) = running.borrow().as_ref() {
+                handle.stop();
+            }
+        });
+    }
+    clear_btn.connect_clicked(move |_| launch_output.buffer().set_text(""));
+
     // Preview handler
-    let type_combo_preview = type_combo.clone();
-    let name_entry_preview = name_entry.clone();
-    let generic_name_entry_preview = generic_name_entry.clone();
-    let comment_entry_preview = comment_entry.clone();
-    let exec_entry_preview = exec_entry.clone();
-    let icon_entry_preview = icon_entry.clone();
-    let terminal_check_preview = terminal_check.clone();
-    let nodisplay_check_preview = nodisplay_check.clone();
-    let startup_check_preview = startup_check.clone();
-    let categories_entry_preview = categories_entry.clone();
-    let mimetype_entry_preview = mimetype_entry.clone();
-    let keywords_entry_preview = keywords_entry.clone();
-    let onlyshowin_entry_preview = onlyshowin_entry.clone();
-    let notshowin_entry_preview = notshowin_entry.clone();
-    let tryexec_entry_preview = tryexec_entry.clone();
-    let path_entry_preview = path_entry.clone();
-    let url_entry_preview = url_entry.clone();
-    let actions_entry_preview = actions_entry.clone();
-    let localized_name_preview = localized_name.clone();
-    let localized_gname_preview = localized_gname.clone();
-    let localized_comment_preview = localized_comment.clone();
-    let extra_kv_preview = extra_kv.clone();
+    let widgets_preview = editor.widgets.clone();
     let win_preview = win.clone();
     preview_btn.connect_clicked(move |_| {
-        let entry = crate::ui::helpers::collect_entry(
-            &type_combo_preview, &name_entry_preview, &generic_name_entry_preview, &comment_entry_preview, &exec_entry_preview, &icon_entry_preview,
-            &terminal_check_preview, &nodisplay_check_preview, &startup_check_preview, &categories_entry_preview, &mimetype_entry_preview,
-            &keywords_entry_preview, &onlyshowin_entry_preview, &notshowin_entry_preview, &tryexec_entry_preview, &path_entry_preview,
-            &url_entry_preview, &actions_entry_preview, &localized_name_preview, &localized_gname_preview, &localized_comment_preview, &extra_kv_preview
-        );
+        let entry = crate::ui::editor::entry_form::collect_entry(&widgets_preview);
         match entry {
             Ok(de) => {
                 let content = de.to_ini_string();
@@ -805,37 +1337,11 @@ pub fn show_main_window(app: &impl IsA<Application>) {
     });
 
     // Save handler
-    let type_combo_save = type_combo.clone();
-    let name_entry_save = name_entry.clone();
-    let generic_name_entry_save = generic_name_entry.clone();
-    let comment_entry_save = comment_entry.clone();
-    let exec_entry_save = exec_entry.clone();
-    let icon_entry_save = icon_entry.clone();
-    let terminal_check_save = terminal_check.clone();
-    let nodisplay_check_save = nodisplay_check.clone();
-    let startup_check_save = startup_check.clone();
-    let categories_entry_save = categories_entry.clone();
-    let mimetype_entry_save = mimetype_entry.clone();
-    let keywords_entry_save = keywords_entry.clone();
-    let onlyshowin_entry_save = onlyshowin_entry.clone();
-    let notshowin_entry_save = notshowin_entry.clone();
-    let tryexec_entry_save = tryexec_entry.clone();
-    let path_entry_save = path_entry.clone();
-    let url_entry_save = url_entry.clone();
-    let actions_entry_save = actions_entry.clone();
-    let localized_name_save = localized_name.clone();
-    let localized_gname_save = localized_gname.clone();
-    let localized_comment_save = localized_comment.clone();
-    let extra_kv_save = extra_kv.clone();
+    let widgets_save = editor.widgets.clone();
     let win_save = win.clone();
     let state_c = state.clone();
     save_btn.connect_clicked(move |_| {
-        let entry = crate::ui::helpers::collect_entry(
-            &type_combo_save, &name_entry_save, &generic_name_entry_save, &comment_entry_save, &exec_entry_save, &icon_entry_save,
-            &terminal_check_save, &nodisplay_check_save, &startup_check_save, &categories_entry_save, &mimetype_entry_save,
-            &keywords_entry_save, &onlyshowin_entry_save, &notshowin_entry_save, &tryexec_entry_save, &path_entry_save,
-            &url_entry_save, &actions_entry_save, &localized_name_save, &localized_gname_save, &localized_comment_save, &extra_kv_save
-        );
+        let entry = crate::ui::editor::entry_form::collect_entry(&widgets_save);
         match entry {
             Ok(de) => {
                 if let Some(sel_path) = state_c.borrow().selected_path.clone() {
@@ -847,7 +1353,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
                                 .modal(true)
                                 .title("Saved")
                                 .text(".desktop file updated")
-                                .secondary_text(&format!("Updated {}", sp.display()))
+                                .secondary_text(&format!("Updated {}{}{}", sp.display(), sync_desktop_copies(&de, &sp), conformance_note(&de)))
                                 .build();
                             dialog.add_button("Open Folder", ResponseType::Accept);
                             dialog.add_button("Close", ResponseType::Close);
@@ -873,7 +1379,7 @@ pub fn show_main_window(app: &impl IsA<Application>) {
                                 .modal(true)
                                 .title("Saved")
                                 .text(".desktop file created")
-                                .secondary_text(&format!("Saved to {}", path.display()))
+                                .secondary_text(&format!("Saved to {}{}", path.display(), conformance_note(&de)))
                                 .build();
                             dialog.add_button("Open Folder", ResponseType::Accept);
                             dialog.add_button("Close", ResponseType::Close);
@@ -901,6 +1407,39 @@ pub fn show_main_window(app: &impl IsA<Application>) {
 
 
 
+// Spec errors the launcher was saved with; text to append to the save message
+fn conformance_note(de: &DesktopEntry) -> String {
+    let errors: Vec<String> = de.conformance_errors().iter().map(|f| f.to_string()).collect();
+    if errors.is_empty() {
+        return String::new();
+    }
+    format!("\n\nSaved with {} error(s) against the specification:\n{}", errors.len(), errors.join("\n"))
+}
+
+// Refresh the desktop copies of a saved launcher; text to append to the save message
+fn sync_desktop_copies(de: &DesktopEntry, path: &std::path::Path) -> String {
+    match DesktopCopies::sync(de, path) {
+        Ok(0) => String::new(),
+        Ok(n) => format!(" (and {} desktop cop{})", n, if n == 1 { "y" } else { "ies" }),
+        Err(e) => format!(" (desktop copies not updated: {:#})", e),
+    }
+}
+
+fn split_targets(text: &str) -> Vec<String> {
+    text.split(';').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect()
+}
+
+fn append_output(view: &gtk4::TextView, text: &str, stderr: bool) {
+    let buf = view.buffer();
+    let mut end = buf.end_iter();
+    if stderr {
+        buf.insert_with_tags_by_name(&mut end, text, &["stderr"]);
+    } else {
+        buf.insert(&mut end, text);
+    }
+    view.scroll_to_iter(&mut buf.end_iter(), 0.0, false, 0.0, 0.0);
+}
+
 fn show_error<W: IsA<gtk4::Window>>(parent: &W, msg: &str) {
     let dialog = gtk4::MessageDialog::builder()
         .transient_for(parent)
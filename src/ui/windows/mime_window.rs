@@ -0,0 +1,169 @@
+use gtk4::{self, Box as GtkBox, Button, CheckButton, CustomFilter, FilterChange, FilterListModel, Label, ListItem, ListView, NoSelection, Orientation, ScrolledWindow, SearchEntry, SignalListItemFactory, StringList, StringObject};
+use adw::{HeaderBar as AdwHeaderBar, ToolbarView, Window as AdwWindow};
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::domain::mime::{self, MimeDatabase};
+use crate::services::mime::MimeDb;
+
+// MIME types from the shared-mime-info database, searchable by type, description or file
+// extension. `current` is pre-selected with aliases replaced by the types they stand for;
+// `on_apply` receives the selection in order.
+pub fn show_mime_window(parent: &impl IsA<gtk4::Window>, current: &[String], on_apply: impl Fn(Vec<String>) + 'static) {
+    let win = AdwWindow::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Choose MIME Types")
+        .default_width(640)
+        .default_height(560)
+        .build();
+    let header = AdwHeaderBar::new();
+    header.add_css_class("flat");
+    let apply_btn = Button::with_label("Apply");
+    apply_btn.add_css_class("suggested-action");
+    header.pack_end(&apply_btn);
+
+    let search = SearchEntry::new();
+    search.set_placeholder_text(Some("Search by type, description or extension (.md)"));
+    search.set_margin_start(12);
+    search.set_margin_end(12);
+    search.set_margin_top(6);
+
+    let summary = Label::new(None);
+    summary.set_xalign(0.0);
+    summary.set_wrap(true);
+    summary.set_selectable(true);
+    summary.set_margin_start(12);
+    summary.set_margin_end(12);
+
+    let db = MimeDb::load_installed();
+    let mut selected: Vec<String> = Vec::new();
+    for name in current {
+        let name = db.canonical(name).map(str::to_string).unwrap_or_else(|| name.trim().to_string());
+        if !name.is_empty() && !selected.contains(&name) {
+            selected.push(name);
+        }
+    }
+    let selected = Rc::new(RefCell::new(selected));
+
+    // Selected types first (unknown ones included, so they can be removed), then the rest
+    let mut names: Vec<String> = selected.borrow().clone();
+    names.extend(db.types().map(|t| t.name.clone()).filter(|n| !selected.borrow().contains(n)));
+    let model = StringList::new(&names.iter().map(String::as_str).collect::<Vec<_>>());
+
+    let filter = {
+        let search = search.clone();
+        CustomFilter::new(move |obj| {
+            let Some(name) = obj.downcast_ref::<StringObject>().map(|s| s.string()) else { return false };
+            let query = search.text();
+            match db.get(&name) {
+                Some(t) => t.matches(&query),
+                None => name.contains(query.trim().to_lowercase().as_str()),
+            }
+        })
+    };
+    let filtered = FilterListModel::new(Some(model), Some(filter.clone()));
+
+    let update_summary = {
+        let (summary, selected) = (summary.clone(), selected.clone());
+        move || {
+            let selected = selected.borrow();
+            summary.set_text(&if selected.is_empty() { "Nothing selected".to_string() } else { format!("Selected: {}", selected.join("; ")) });
+        }
+    };
+    update_summary();
+
+    let factory = SignalListItemFactory::new();
+    {
+        let (selected, update_summary) = (selected.clone(), update_summary.clone());
+        factory.connect_setup(move |_, item| {
+            let Some(item) = item.downcast_ref::<ListItem>() else { return };
+            let row = GtkBox::new(Orientation::Horizontal, 8);
+            row.set_margin_top(4);
+            row.set_margin_bottom(4);
+            row.set_margin_start(6);
+            let check = CheckButton::new();
+            row.append(&check);
+            let labels = GtkBox::new(Orientation::Vertical, 2);
+            let name = Label::new(None);
+            name.set_xalign(0.0);
+            let details = Label::new(None);
+            details.set_xalign(0.0);
+            details.set_wrap(true);
+            details.add_css_class("caption");
+            details.add_css_class("dim-label");
+            labels.append(&name);
+            labels.append(&details);
+            row.append(&labels);
+            item.set_child(Some(&row));
+            let (selected, update_summary) = (selected.clone(), update_summary.clone());
+            check.connect_toggled(move |check| {
+                let name = name.text().to_string();
+                let mut list = selected.borrow_mut();
+                let present = list.contains(&name);
+                if check.is_active() && !present {
+                    list.push(name);
+                } else if !check.is_active() && present {
+                    list.retain(|n| *n != name);
+                } else {
+                    return;
+                }
+                drop(list);
+                update_summary();
+            });
+        });
+    }
+    {
+        let selected = selected.clone();
+        factory.connect_bind(move |_, item| {
+            let Some(item) = item.downcast_ref::<ListItem>() else { return };
+            let (Some(name), Some(row)) = (item.item().and_downcast::<StringObject>().map(|s| s.string()), item.child()) else { return };
+            let Some(check) = row.first_child().and_downcast::<CheckButton>() else { return };
+            let Some(labels) = row.last_child() else { return };
+            if let (Some(name_label), Some(details)) = (labels.first_child().and_downcast::<Label>(), labels.last_child().and_downcast::<Label>()) {
+                // Label first: the toggled handler reads the type from it
+                name_label.set_text(&name);
+                details.set_text(&describe(db, &name));
+            }
+            check.set_active(selected.borrow().iter().any(|n| *n == name.as_str()));
+        });
+    }
+    let list = ListView::new(Some(NoSelection::new(Some(filtered))), Some(factory));
+    let scroller = ScrolledWindow::builder().vexpand(true).child(&list).build();
+
+    let root = GtkBox::new(Orientation::Vertical, 6);
+    root.append(&search);
+    root.append(&summary);
+    root.append(&scroller);
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&root));
+    win.set_content(Some(&toolbar_view));
+
+    search.connect_search_changed(move |_| filter.changed(FilterChange::Different));
+    {
+        let win_c = win.clone();
+        apply_btn.connect_clicked(move |_| {
+            on_apply(selected.borrow().clone());
+            win_c.close();
+        });
+    }
+
+    win.present();
+}
+
+// Description, file name patterns and aliases of a type
+fn describe(db: &MimeDatabase, name: &str) -> String {
+    let Some(t) = db.get(name) else {
+        return if mime::is_scheme_handler(name) { "URL scheme handler".into() } else { "Not in the shared MIME database".into() };
+    };
+    let mut parts: Vec<String> = t.comment.iter().cloned().collect();
+    if !t.globs.is_empty() {
+        parts.push(t.globs.join(", "));
+    }
+    if !t.aliases.is_empty() {
+        parts.push(format!("also known as {}", t.aliases.join(", ")));
+    }
+    parts.join(" · ")
+}
@@ -1 +1,6 @@
 pub mod main_window;
+pub mod autostart_window;
+pub mod template_window;
+pub mod flatpak_window;
+pub mod icon_window;
+pub mod mime_window;
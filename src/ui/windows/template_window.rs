@@ -0,0 +1,183 @@
+use gtk4::{self, Align, Box as GtkBox, Button, Entry, FileChooserAction, FileChooserDialog, Label, ListBox, ListBoxRow, Orientation, Paned, ResponseType, ScrolledWindow};
+use adw::{HeaderBar as AdwHeaderBar, ToolbarView, Window as AdwWindow};
+use adw::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::domain::desktop_entry::DesktopEntry;
+use crate::domain::template::VariableKind;
+use crate::services::templates::{TemplateItem, Templates};
+
+// Template gallery: pick a template, fill in its variables, get a new unsaved entry.
+// `on_create` receives the entry and the template title.
+pub fn show_template_window(parent: &impl IsA<gtk4::Window>, on_create: impl Fn(DesktopEntry, &str) + 'static) {
+    let win = AdwWindow::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("New from Template")
+        .default_width(760)
+        .default_height(480)
+        .build();
+    let header = AdwHeaderBar::new();
+    header.add_css_class("flat");
+
+    let listbox = ListBox::new();
+    listbox.add_css_class("navigation-sidebar");
+    let list_scroller = ScrolledWindow::builder().vexpand(true).child(&listbox).build();
+    list_scroller.set_size_request(260, -1);
+
+    let form = GtkBox::new(Orientation::Vertical, 8);
+    form.set_margin_top(12);
+    form.set_margin_bottom(12);
+    form.set_margin_start(12);
+    form.set_margin_end(12);
+    let form_scroller = ScrolledWindow::builder().hexpand(true).vexpand(true).child(&form).build();
+
+    let paned = Paned::new(Orientation::Horizontal);
+    paned.set_start_child(Some(&list_scroller));
+    paned.set_end_child(Some(&form_scroller));
+    paned.set_resize_start_child(false);
+    paned.set_shrink_start_child(false);
+
+    let status = Label::new(None);
+    status.set_xalign(0.0);
+    status.set_wrap(true);
+    status.add_css_class("dim-label");
+    status.set_margin_start(12);
+    status.set_margin_end(12);
+    status.set_margin_bottom(8);
+
+    let create_btn = Button::with_label("Create");
+    create_btn.add_css_class("suggested-action");
+    create_btn.set_sensitive(false);
+    header.pack_end(&create_btn);
+
+    let root = GtkBox::new(Orientation::Vertical, 0);
+    root.append(&paned);
+    root.append(&status);
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&root));
+    win.set_content(Some(&toolbar_view));
+
+    let (items, problems) = Templates::list();
+    if !problems.is_empty() {
+        status.set_text(&format!("Some user templates were skipped:\n{}", problems.join("\n")));
+    }
+    for item in &items {
+        listbox.append(&build_row(item));
+    }
+
+    // Inputs of the selected template, by variable id
+    let inputs: Rc<RefCell<Vec<(String, Entry)>>> = Rc::new(RefCell::new(Vec::new()));
+    let items = Rc::new(items);
+    {
+        let (items, inputs, form, create_btn, win_c) = (items.clone(), inputs.clone(), form.clone(), create_btn.clone(), win.clone());
+        listbox.connect_row_selected(move |_, row| {
+            while let Some(child) = form.first_child() { form.remove(&child); }
+            inputs.borrow_mut().clear();
+            let Some(item) = row.and_then(|r| items.get(r.index() as usize)) else {
+                create_btn.set_sensitive(false);
+                return;
+            };
+            let title = Label::new(Some(&item.template.title));
+            title.add_css_class("title-3");
+            title.set_xalign(0.0);
+            form.append(&title);
+            if !item.template.description.is_empty() {
+                let desc = Label::new(Some(&item.template.description));
+                desc.set_xalign(0.0);
+                desc.set_wrap(true);
+                desc.add_css_class("dim-label");
+                form.append(&desc);
+            }
+            for v in &item.template.variables {
+                let (row, entry) = crate::ui::components::labeled_entry(&v.label);
+                entry.set_hexpand(true);
+                if let Some(default) = &v.default {
+                    entry.set_placeholder_text(Some(default));
+                }
+                if let Some(action) = match v.kind {
+                    VariableKind::File => Some(FileChooserAction::Open),
+                    VariableKind::Folder => Some(FileChooserAction::SelectFolder),
+                    _ => None,
+                } {
+                    let pick = Button::from_icon_name("document-open-symbolic");
+                    pick.set_tooltip_text(Some("Choose..."));
+                    let (entry_c, win_c, label) = (entry.clone(), win_c.clone(), v.label.clone());
+                    pick.connect_clicked(move |_| {
+                        let dialog = FileChooserDialog::new(Some(&label), Some(&win_c), action, &[("Cancel", ResponseType::Cancel), ("Select", ResponseType::Accept)]);
+                        let entry_c = entry_c.clone();
+                        dialog.connect_response(move |d, resp| {
+                            if resp == ResponseType::Accept
+                                && let Some(path) = d.file().and_then(|f| f.path())
+                            {
+                                entry_c.set_text(&path.to_string_lossy());
+                            }
+                            d.close();
+                        });
+                        dialog.show();
+                    });
+                    row.append(&pick);
+                }
+                form.append(&row);
+                inputs.borrow_mut().push((v.id.clone(), entry));
+            }
+            create_btn.set_sensitive(true);
+        });
+    }
+    if let Some(first) = listbox.row_at_index(0) {
+        listbox.select_row(Some(&first));
+    }
+
+    {
+        let (items, inputs, listbox, status, win_c) = (items.clone(), inputs.clone(), listbox.clone(), status.clone(), win.clone());
+        create_btn.connect_clicked(move |_| {
+            let Some(item) = listbox.selected_row().and_then(|r| items.get(r.index() as usize)) else { return };
+            let values: HashMap<String, String> = inputs.borrow().iter().map(|(id, e)| (id.clone(), e.text().to_string())).collect();
+            match Templates::instantiate(&item.template, &values) {
+                Ok(entry) => {
+                    on_create(entry, &item.template.title);
+                    win_c.close();
+                }
+                Err(e) => status.set_text(&format!("{:#}", e)),
+            }
+        });
+    }
+
+    win.present();
+}
+
+fn build_row(item: &TemplateItem) -> ListBoxRow {
+    let row = ListBoxRow::new();
+    let vb = GtkBox::new(Orientation::Vertical, 2);
+    vb.set_margin_top(6);
+    vb.set_margin_bottom(6);
+    vb.set_margin_start(6);
+    vb.set_margin_end(6);
+    let top = GtkBox::new(Orientation::Horizontal, 6);
+    let title = Label::new(Some(&item.template.title));
+    title.set_xalign(0.0);
+    title.set_hexpand(true);
+    top.append(&title);
+    if let Some(path) = &item.path {
+        let badge = Label::new(Some("user"));
+        badge.add_css_class("caption");
+        badge.add_css_class("dim-label");
+        badge.set_valign(Align::Center);
+        badge.set_tooltip_text(Some(&path.to_string_lossy()));
+        top.append(&badge);
+    }
+    vb.append(&top);
+    if !item.template.description.is_empty() {
+        let desc = Label::new(Some(&item.template.description));
+        desc.set_xalign(0.0);
+        desc.set_wrap(true);
+        desc.add_css_class("caption");
+        desc.add_css_class("dim-label");
+        vb.append(&desc);
+    }
+    row.set_child(Some(&vb));
+    row
+}